@@ -0,0 +1,221 @@
+use super::capture::Capture;
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{
+    AtomicBool,
+    AtomicUsize,
+    Ordering,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+    MutexGuard,
+};
+use tokio::sync::Notify;
+// Keeps only the latest update of every account. The update that has not been received yet is overwritten by the newer
+// one, so the sender never waits for the busy receiver, and the last state of the quiet account is never lost.
+pub struct AccountMailbox;
+impl AccountMailbox {
+    pub fn create<T>() -> (AccountMailboxSender<T>, AccountMailboxReceiver<T>) {
+        let account_mailbox_state = Arc::new(
+            AccountMailboxState {
+                update_registry: Mutex::new(BTreeMap::new()),
+                notify: Notify::new(),
+                senders_quantity: AtomicUsize::new(1),
+                is_receiver_dropped: AtomicBool::new(false),
+            },
+        );
+        (
+            AccountMailboxSender {
+                account_mailbox_state: account_mailbox_state.clone(),
+            },
+            AccountMailboxReceiver {
+                account_mailbox_state,
+            },
+        )
+    }
+}
+struct AccountMailboxState<T> {
+    update_registry: Mutex<BTreeMap<Pubkey, T>>,
+    notify: Notify,
+    senders_quantity: AtomicUsize,
+    is_receiver_dropped: AtomicBool,
+}
+impl<T> AccountMailboxState<T> {
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, BTreeMap<Pubkey, T>>, Error> {
+        self.update_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+pub struct AccountMailboxSender<T> {
+    account_mailbox_state: Arc<AccountMailboxState<T>>,
+}
+impl<T> AccountMailboxSender<T> {
+    // Returns false if the receiver has been dropped, the update is discarded then.
+    pub fn send<'a>(&'a self, account_pubkey: Pubkey, update: T) -> Result<bool, Error> {
+        if self.is_closed() {
+            return Ok(false);
+        }
+        let _ = self.account_mailbox_state.lock()?.insert(
+            account_pubkey,
+            update,
+        );
+        // The permit is stored if the receiver is not waiting yet, so the update can not be missed.
+        self.account_mailbox_state.notify.notify_one();
+        Ok(true)
+    }
+    pub fn is_closed<'a>(&'a self) -> bool {
+        self.account_mailbox_state.is_receiver_dropped.load(Ordering::Acquire)
+    }
+}
+impl<T> Clone for AccountMailboxSender<T> {
+    fn clone<'a>(&'a self) -> Self {
+        let _ = self.account_mailbox_state.senders_quantity.fetch_add(
+            1,
+            Ordering::AcqRel,
+        );
+        Self {
+            account_mailbox_state: self.account_mailbox_state.clone(),
+        }
+    }
+}
+impl<T> Drop for AccountMailboxSender<T> {
+    fn drop<'a>(&'a mut self) -> () {
+        if self.account_mailbox_state.senders_quantity.fetch_sub(
+            1,
+            Ordering::AcqRel,
+        ) == 1 {
+            self.account_mailbox_state.notify.notify_one();
+        }
+    }
+}
+pub struct AccountMailboxReceiver<T> {
+    account_mailbox_state: Arc<AccountMailboxState<T>>,
+}
+impl<T> AccountMailboxReceiver<T>
+where
+    T: Send,
+{
+    // Waits for the update of any account. It is an error if every sender has been dropped, because nothing can come
+    // anymore.
+    pub fn recv<'a>(&'a mut self) -> impl Future<Output = Result<T, Error>> + Send + Capture<&'a ()> {
+        async move {
+            '_a: loop {
+                if let Some(update) = self.try_recv()? {
+                    return Ok(update);
+                }
+                if self.account_mailbox_state.senders_quantity.load(Ordering::Acquire) == 0 {
+                    return Err(
+                        Error::new_(
+                            Common::UnreachableState,
+                            Backtrace::new(
+                                line!(),
+                                file!(),
+                            ),
+                        ),
+                    );
+                }
+                self.account_mailbox_state.notify.notified().await;
+            }
+        }
+    }
+    pub fn try_recv<'a>(&'a mut self) -> Result<Option<T>, Error> {
+        Ok(
+            self.account_mailbox_state.lock()?.pop_first().map(
+                |(_, update): _| -> _ {
+                    update
+                }
+            ),
+        )
+    }
+}
+impl<T> Drop for AccountMailboxReceiver<T> {
+    fn drop<'a>(&'a mut self) -> () {
+        self.account_mailbox_state.is_receiver_dropped.store(
+            true,
+            Ordering::Release,
+        );
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::error::ResultConverter;
+    #[tokio::test]
+    async fn keep_latest_update_of_every_account() -> Result<(), Error> {
+        let (
+            account_mailbox_sender,
+            mut account_mailbox_receiver,
+        ) = AccountMailbox::create::<u64>();
+        let first_account_pubkey = Pubkey::new_unique();
+        let second_account_pubkey = Pubkey::new_unique();
+        assert!(account_mailbox_sender.send(first_account_pubkey, 1)?);
+        assert!(account_mailbox_sender.send(second_account_pubkey, 2)?);
+        assert!(account_mailbox_sender.send(first_account_pubkey, 3)?);
+        let mut update_registry = vec![
+            account_mailbox_receiver.recv().await?,
+            account_mailbox_receiver.recv().await?,
+        ];
+        update_registry.sort();
+        assert_eq!(update_registry, vec![2, 3]);
+        assert_eq!(account_mailbox_receiver.try_recv()?, None);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn wake_waiting_receiver() -> Result<(), Error> {
+        let (
+            account_mailbox_sender,
+            mut account_mailbox_receiver,
+        ) = AccountMailbox::create::<u64>();
+        let receiving = tokio::spawn(
+            async move {
+                account_mailbox_receiver.recv().await
+            },
+        );
+        tokio::task::yield_now().await;
+        assert!(account_mailbox_sender.send(Pubkey::new_unique(), 1)?);
+        assert_eq!(
+            receiving.await.into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )??,
+            1,
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn close_on_drop() -> Result<(), Error> {
+        let (
+            account_mailbox_sender,
+            mut account_mailbox_receiver,
+        ) = AccountMailbox::create::<u64>();
+        let account_mailbox_sender_ = account_mailbox_sender.clone();
+        assert!(account_mailbox_sender.send(Pubkey::new_unique(), 1)?);
+        drop(account_mailbox_sender);
+        drop(account_mailbox_sender_);
+        // The update that has been sent before the senders are dropped is still received.
+        assert_eq!(account_mailbox_receiver.recv().await?, 1);
+        assert!(account_mailbox_receiver.recv().await.is_err());
+        let (
+            account_mailbox_sender,
+            account_mailbox_receiver,
+        ) = AccountMailbox::create::<u64>();
+        drop(account_mailbox_receiver);
+        assert!(account_mailbox_sender.is_closed());
+        assert!(!account_mailbox_sender.send(Pubkey::new_unique(), 1)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+    OptionConverter,
+    ResultConverter,
+};
+use super::unix_time::UnixTime;
+use std::fs::{
+    File,
+    OpenOptions,
+};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+    OnceLock,
+};
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+// Every control command accepted by the HTTP server is appended as the JSON line and flushed at once, so the entry
+// survives the crash that the command could cause.
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+impl AuditLog {
+    pub fn initialize<'a>(audit_log_file_path: &'a str) -> Result<(), Error> {
+        let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_log_file_path)
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        AUDIT_LOG.set(
+            Self {
+                file: Mutex::new(file),
+            },
+        )
+        .map_err(
+            |_: _| -> _ {
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+            }
+        )
+    }
+    pub fn get() -> Result<&'static Self, Error> {
+        AUDIT_LOG.get().into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    pub fn write<'a>(
+        &'a self,
+        remote_socket_address: &'a SocketAddr,
+        method: &'a str,
+        path: &'a str,
+        body: &'a [u8],
+        status_code: u16,
+    ) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(
+            &AuditLogEntry {
+                timestamp: UnixTime::get_timestamp()?,
+                remote_socket_address: remote_socket_address.to_string(),
+                method,
+                path,
+                body: String::from_utf8_lossy(body).as_ref(),
+                status_code,
+            },
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        line.push(b'\n');
+        let mut file = self.lock()?;
+        file.write_all(line.as_slice()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        file.flush().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, File>, Error> {
+        self.file.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+#[derive(serde::Serialize)]
+struct AuditLogEntry<'a> {
+    timestamp: u64,
+    remote_socket_address: String,
+    method: &'a str,
+    path: &'a str,
+    body: &'a str,
+    status_code: u16,
+}
@@ -0,0 +1,166 @@
+use super::error::{
+    Backtrace,
+    Error,
+    LockResultConverter,
+    OptionConverter,
+    ResultConverter,
+};
+use solana_sdk::hash::Hash;
+use std::str::FromStr;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+use yellowstone_grpc_proto::geyser::SubscribeUpdateBlockMeta;
+pub static BLOCKHASH_CACHE: BlockhashCache = BlockhashCache::new();
+// The latest blockhash is taken from the block meta updates of the Geyser stream, so nothing is requested from the RPC
+// before the signing. The cache is stale if no block has been received for a while, for example while the Geyser
+// reconnects, then the blockhash and the block height have to be requested from the RPC.
+pub struct BlockhashCache {
+    cached_blockhash: Mutex<Option<CachedBlockhash>>,
+}
+impl BlockhashCache {
+    // https://github.com/anza-xyz/agave/blob/v2.0.18/sdk/program/src/clock.rs
+    const MAXIMUM_PROCESSING_AGE: u64 = 150;
+    const MAXIMUM_STALENESS_SECONDS: u64 = 5;
+    const fn new() -> Self {
+        Self {
+            cached_blockhash: Mutex::new(None),
+        }
+    }
+    // The block that is not higher than the cached one is ignored, so the copies from the several endpoints and the
+    // updates processed out of order do not move the cache back.
+    pub fn observe<'a>(&'a self, subscribe_update_block_meta: &'a SubscribeUpdateBlockMeta, instant: Instant) -> Result<(), Error> {
+        let block_height = subscribe_update_block_meta
+        .block_height
+        .as_ref()
+        .into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .block_height;
+        let mut cached_blockhash = self.lock()?;
+        if let Some(ref cached_blockhash_) = *cached_blockhash {
+            if block_height <= cached_blockhash_.block_height {
+                return Ok(());
+            }
+        }
+        *cached_blockhash = Some(
+            CachedBlockhash {
+                blockhash: Hash::from_str(subscribe_update_block_meta.blockhash.as_str()).into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?,
+                block_height,
+                last_valid_block_height: block_height + Self::MAXIMUM_PROCESSING_AGE,
+                receiving_instant: instant,
+            },
+        );
+        Ok(())
+    }
+    // None if nothing has been received yet or the cache is stale at the 'instant'.
+    pub fn get<'a>(&'a self, instant: Instant) -> Result<Option<CachedBlockhash>, Error> {
+        let cached_blockhash = match *self.lock()? {
+            Some(cached_blockhash_) => cached_blockhash_,
+            None => {
+                return Ok(None);
+            }
+        };
+        if instant.saturating_duration_since(cached_blockhash.receiving_instant) > Duration::from_secs(Self::MAXIMUM_STALENESS_SECONDS) {
+            return Ok(None);
+        }
+        Ok(Some(cached_blockhash))
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, Option<CachedBlockhash>>, Error> {
+        self.cached_blockhash.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+#[derive(Clone, Copy)]
+pub struct CachedBlockhash {
+    pub blockhash: Hash,
+    // The height of the block the blockhash belongs to, it is the latest known height of the chain.
+    pub block_height: u64,
+    // The transaction signed with the blockhash has expired once the chain is higher than this.
+    pub last_valid_block_height: u64,
+    receiving_instant: Instant,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yellowstone_grpc_proto::solana::storage::confirmed_block::BlockHeight;
+    #[test]
+    fn keep_highest_block() -> Result<(), Error> {
+        let blockhash_cache = BlockhashCache::new();
+        let now = Instant::now();
+        assert!(blockhash_cache.get(now)?.is_none());
+        let blockhash = Hash::new_unique();
+        blockhash_cache.observe(
+            &SubscribeUpdateBlockMeta {
+                blockhash: blockhash.to_string(),
+                block_height: Some(
+                    BlockHeight {
+                        block_height: 1000,
+                    },
+                ),
+                ..Default::default()
+            },
+            now,
+        )?;
+        // The block meta of the older block may arrive later, it does not replace the newer blockhash.
+        blockhash_cache.observe(
+            &SubscribeUpdateBlockMeta {
+                blockhash: Hash::new_unique().to_string(),
+                block_height: Some(
+                    BlockHeight {
+                        block_height: 999,
+                    },
+                ),
+                ..Default::default()
+            },
+            now,
+        )?;
+        let cached_blockhash = blockhash_cache.get(now)?.into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        assert_eq!(cached_blockhash.blockhash, blockhash);
+        assert_eq!(cached_blockhash.block_height, 1000);
+        assert_eq!(cached_blockhash.last_valid_block_height, 1150);
+        Ok(())
+    }
+    #[test]
+    fn expire_stale_blockhash() -> Result<(), Error> {
+        let blockhash_cache = BlockhashCache::new();
+        let now = Instant::now();
+        blockhash_cache.observe(
+            &SubscribeUpdateBlockMeta {
+                blockhash: Hash::new_unique().to_string(),
+                block_height: Some(
+                    BlockHeight {
+                        block_height: 1000,
+                    },
+                ),
+                ..Default::default()
+            },
+            now,
+        )?;
+        assert!(blockhash_cache.get(now + Duration::from_secs(BlockhashCache::MAXIMUM_STALENESS_SECONDS))?.is_some());
+        assert!(blockhash_cache.get(now + Duration::from_secs(BlockhashCache::MAXIMUM_STALENESS_SECONDS + 1))?.is_none());
+        Ok(())
+    }
+}
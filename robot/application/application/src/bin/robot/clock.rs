@@ -0,0 +1,141 @@
+use super::capture::Capture;
+use super::error::{
+    Backtrace,
+    Error,
+    LockResultConverter,
+};
+use super::unix_time::UnixTime;
+use std::future::Future;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+use tokio::sync::Notify;
+pub static CLOCK: Clock = Clock::new();
+// The trading decisions take the time from here. In the live run it is the system time. In the replay it is the receive
+// time of the last replayed record, so the same record gives the same decision whatever the pace of the replay is, and
+// the sleeps end when the record that is not earlier than their deadline is replayed.
+pub struct Clock {
+    replayed_moment: Mutex<Option<ReplayedMoment>>,
+    notify: Notify,
+}
+impl Clock {
+    const fn new() -> Self {
+        Self {
+            replayed_moment: Mutex::new(None),
+            notify: Notify::const_new(),
+        }
+    }
+    // Switches the clock to the replayed time. The first replayed record is mapped to the current instant, the rest are
+    // shifted from it by the difference of the receive timestamps. The time never goes backwards.
+    pub fn replay<'a>(&'a self, receive_timestamp_microseconds: u64) -> Result<(), Error> {
+        {
+            let mut replayed_moment = self.lock()?;
+            match *replayed_moment {
+                Some(ref mut replayed_moment_) => {
+                    if receive_timestamp_microseconds > replayed_moment_.receive_timestamp_microseconds {
+                        replayed_moment_.receive_timestamp_microseconds = receive_timestamp_microseconds;
+                    }
+                }
+                None => {
+                    *replayed_moment = Some(
+                        ReplayedMoment {
+                            first_receive_timestamp_microseconds: receive_timestamp_microseconds,
+                            first_instant: Instant::now(),
+                            receive_timestamp_microseconds,
+                        },
+                    );
+                }
+            }
+        }
+        self.notify.notify_waiters();
+        Ok(())
+    }
+    pub fn get<'a>(&'a self) -> Result<Duration, Error> {
+        match *self.lock()? {
+            Some(ref replayed_moment) => Ok(Duration::from_micros(replayed_moment.receive_timestamp_microseconds)),
+            None => UnixTime::get(),
+        }
+    }
+    pub fn get_timestamp<'a>(&'a self) -> Result<u64, Error> {
+        Ok(self.get()?.as_secs())
+    }
+    pub fn get_instant<'a>(&'a self) -> Result<Instant, Error> {
+        match *self.lock()? {
+            Some(ref replayed_moment) => Ok(replayed_moment.get_instant()),
+            None => Ok(Instant::now()),
+        }
+    }
+    pub fn sleep_until<'a>(&'a self, instant: Instant) -> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            '_a: loop {
+                // The waiting is registered before the time is read, so the replay of the next record can not be missed.
+                let mut notified = std::pin::pin!(self.notify.notified());
+                notified.as_mut().enable();
+                // The lock is released before the sleep.
+                let replayed_instant = self.lock()?.as_ref().map(ReplayedMoment::get_instant);
+                let replayed_instant = match replayed_instant {
+                    Some(replayed_instant_) => replayed_instant_,
+                    None => {
+                        tokio::time::sleep_until(instant.into()).await;
+                        return Ok(());
+                    }
+                };
+                if replayed_instant >= instant {
+                    return Ok(());
+                }
+                notified.await;
+            }
+        }
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, Option<ReplayedMoment>>, Error> {
+        self.replayed_moment.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct ReplayedMoment {
+    first_receive_timestamp_microseconds: u64,
+    first_instant: Instant,
+    receive_timestamp_microseconds: u64,
+}
+impl ReplayedMoment {
+    fn get_instant<'a>(&'a self) -> Instant {
+        self.first_instant + Duration::from_micros(self.receive_timestamp_microseconds - self.first_receive_timestamp_microseconds)
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::error::ResultConverter;
+    #[tokio::test]
+    async fn sleep_until_replayed_time() -> Result<(), Error> {
+        static CLOCK_: Clock = Clock::new();
+        CLOCK_.replay(1732807457000000)?;
+        let first_instant = CLOCK_.get_instant()?;
+        assert_eq!(CLOCK_.get_timestamp()?, 1732807457);
+        let sleep = tokio::spawn(CLOCK_.sleep_until(first_instant + Duration::from_secs(2)));
+        CLOCK_.replay(1732807458000000)?;
+        tokio::task::yield_now().await;
+        assert!(!sleep.is_finished());
+        // The record received earlier than the last one does not move the time backwards.
+        CLOCK_.replay(1732807456000000)?;
+        assert_eq!(CLOCK_.get_instant()?, first_instant + Duration::from_secs(1));
+        CLOCK_.replay(1732807459000000)?;
+        sleep.await.into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )??;
+        assert_eq!(CLOCK_.get_timestamp()?, 1732807459);
+        Ok(())
+    }
+}
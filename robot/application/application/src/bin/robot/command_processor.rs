@@ -48,7 +48,12 @@ impl CommandProcessor<Trade> {
             _worker_guard = initialize_stdout_logger();
         }
         let runtime = Self::initialize_runtime(environment_configuration)?;
-        runtime.block_on(Robot::start(environment_configuration))?;
+        runtime.block_on(
+            Robot::start(
+                environment_configuration,
+                None,
+            ),
+        )?;
         Ok(())
     }
     fn initialize_environment<'a>(environment_configuration_file_path: &'a str) -> Result<&'static EnvironmentConfiguration<Trade_>, Error> {
@@ -116,6 +121,62 @@ impl CommandProcessor<Trade> {
             )
     }
 }
+// Trades the same way the 'Trade' does, recording the Geyser stream into the file.
+pub struct Record;
+impl CommandProcessor<Record> {
+    pub fn process<'a>(environment_configuration_file_path: &'a str, geyser_record_file_path: &'a str) -> Result<(), Error> {
+        let _worker_guard;
+        let environment_configuration = CommandProcessor::<Trade>::initialize_environment(environment_configuration_file_path)?;
+        #[cfg(feature = "logging_to_file")]
+        {
+            _worker_guard = CommandProcessor::<Trade>::initialize_logging_to_fileger(environment_configuration)?;
+        }
+        #[cfg(not(feature = "logging_to_file"))]
+        {
+            _worker_guard = initialize_stdout_logger();
+        }
+        let runtime = CommandProcessor::<Trade>::initialize_runtime(environment_configuration)?;
+        runtime.block_on(
+            Robot::start(
+                environment_configuration,
+                Some(geyser_record_file_path.to_string()),
+            ),
+        )?;
+        Ok(())
+    }
+}
+pub struct Replay;
+impl CommandProcessor<Replay> {
+    pub fn process<'a>(
+        environment_configuration_file_path: &'a str,
+        geyser_record_file_path: &'a str,
+        is_original_pace: bool,
+    ) -> Result<(), Error> {
+        let environment_configuration = CommandProcessor::<Trade>::initialize_environment(environment_configuration_file_path)?;
+        let _worker_guard = initialize_stdout_logger();
+        let runtime = Self::initialize_runtime()?;
+        runtime.block_on(
+            Robot::replay(
+                environment_configuration,
+                geyser_record_file_path.to_string(),
+                is_original_pace,
+            ),
+        )?;
+        Ok(())
+    }
+    // The records are replayed on the single thread, so the order the tasks run in does not depend on the scheduling.
+    fn initialize_runtime() -> Result<Runtime, Error> {
+        RuntimeBuilder::new_current_thread()
+            .enable_all()
+            .build()
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+    }
+}
 pub struct ParseTransaction;
 impl CommandProcessor<ParseTransaction> {
     pub fn process<'a>(environment_configuration_file_path: &'a str) -> Result<(), Error> {
@@ -0,0 +1,142 @@
+use super::capture::Capture;
+use super::clock::CLOCK;
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+use std::time::Duration;
+use tokio::sync::oneshot::{
+    Receiver as OneshotReceiver,
+    Sender as OneshotSender,
+};
+pub static ENTRY_SCHEDULER: EntryScheduler = EntryScheduler::new();
+// The pools that can not be swapped yet are held here until their open time. The trading task keeps tracking the
+// accounts of the pool while it waits, the graceful shutdown cancels all the waiting entries.
+pub struct EntryScheduler {
+    scheduled_entry_registry: Mutex<BTreeMap<Pubkey, ScheduledEntry>>,
+}
+impl EntryScheduler {
+    const fn new() -> Self {
+        Self {
+            scheduled_entry_registry: Mutex::new(BTreeMap::new()),
+        }
+    }
+    pub fn schedule<'a>(&'static self, amm_market_pubkey: &'a Pubkey) -> Result<ScheduledEntryGuard, Error> {
+        let (
+            cancellation_sender,
+            cancellation_receiver,
+        ) = tokio::sync::oneshot::channel::<()>();
+        let mut scheduled_entry_registry = self.lock()?;
+        if scheduled_entry_registry.contains_key(amm_market_pubkey) {
+            return Err(
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        let _ = scheduled_entry_registry.insert(
+            *amm_market_pubkey,
+            ScheduledEntry {
+                cancellation_sender: Some(cancellation_sender),
+            },
+        );
+        Ok(
+            ScheduledEntryGuard {
+                entry_scheduler: self,
+                amm_market_pubkey: *amm_market_pubkey,
+                cancellation_receiver,
+            },
+        )
+    }
+    pub fn get_scheduled_entries_quantity<'a>(&'a self) -> Result<usize, Error> {
+        Ok(self.lock()?.len())
+    }
+    // Returns the quantity of the entries that have been cancelled.
+    pub fn cancel_all<'a>(&'a self) -> Result<usize, Error> {
+        let mut cancelled_entries_quantity: usize = 0;
+        '_a: for scheduled_entry in self.lock()?.values_mut() {
+            if let Some(cancellation_sender) = scheduled_entry.cancellation_sender.take() {
+                if cancellation_sender.send(()).is_ok() {
+                    cancelled_entries_quantity += 1;
+                }
+            }
+        }
+        Ok(cancelled_entries_quantity)
+    }
+    // The time left until the 'open_time', zero if it has already come.
+    pub fn get_open_delay(open_time: u64) -> Result<Duration, Error> {
+        Ok(Duration::from_secs(open_time).saturating_sub(CLOCK.get()?))
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, BTreeMap<Pubkey, ScheduledEntry>>, Error> {
+        self.scheduled_entry_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct ScheduledEntry {
+    cancellation_sender: Option<OneshotSender<()>>,
+}
+// The entry stays in the scheduler until the trading task stops waiting for the open time, the scheduler counts it until
+// then. The drop removes it when the open time has come and when the entry has been cancelled alike.
+pub struct ScheduledEntryGuard {
+    entry_scheduler: &'static EntryScheduler,
+    amm_market_pubkey: Pubkey,
+    cancellation_receiver: OneshotReceiver<()>,
+}
+impl ScheduledEntryGuard {
+    // Completes once the graceful shutdown has cancelled the entry. The cancellation waits in the receiver, so the future
+    // can lose the race to the open time in the 'select!' and be awaited again. It must not be awaited after completing.
+    pub fn wait_for_cancellation<'a>(&'a mut self) -> impl Future<Output = ()> + Send + Capture<&'a ()> {
+        async move {
+            // The scheduler drops the sender unsent only with the entry, and the entry is removed only by the drop of
+            // this guard, so the error is not expected here.
+            let _ = (&mut self.cancellation_receiver).await;
+        }
+    }
+}
+impl Drop for ScheduledEntryGuard {
+    fn drop(&mut self) {
+        if let Ok(mut scheduled_entry_registry) = self.entry_scheduler.lock() {
+            let _ = scheduled_entry_registry.remove(&self.amm_market_pubkey);
+        }
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[tokio::test]
+    async fn cancel_scheduled_entries() -> Result<(), Error> {
+        static ENTRY_SCHEDULER_: EntryScheduler = EntryScheduler::new();
+        let amm_market_pubkey = Pubkey::new_unique();
+        let mut scheduled_entry_guard = ENTRY_SCHEDULER_.schedule(&amm_market_pubkey)?;
+        // The pool is scheduled once.
+        assert!(ENTRY_SCHEDULER_.schedule(&amm_market_pubkey).is_err());
+        let scheduled_entry_guard_ = ENTRY_SCHEDULER_.schedule(&Pubkey::new_unique())?;
+        assert_eq!(ENTRY_SCHEDULER_.get_scheduled_entries_quantity()?, 2);
+        drop(scheduled_entry_guard_);
+        assert_eq!(ENTRY_SCHEDULER_.get_scheduled_entries_quantity()?, 1);
+        assert_eq!(ENTRY_SCHEDULER_.cancel_all()?, 1);
+        // The entry is cancelled once.
+        assert_eq!(ENTRY_SCHEDULER_.cancel_all()?, 0);
+        scheduled_entry_guard.wait_for_cancellation().await;
+        drop(scheduled_entry_guard);
+        assert_eq!(ENTRY_SCHEDULER_.get_scheduled_entries_quantity()?, 0);
+        Ok(())
+    }
+}
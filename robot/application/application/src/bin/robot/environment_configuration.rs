@@ -3,6 +3,7 @@ use self::environment_configuration_file::{
     ParseTransaction as ParseTransaction_,
     Trade as Trade_,
 };
+use self::environment_configuration_file::trade::TransactionSenderBackend as TransactionSenderBackend_;
 use std::path::Path;
 use super::error::{
     Backtrace,
@@ -11,21 +12,42 @@ use super::error::{
 };
 use serde::de::DeserializeOwned;
 use solana_sdk::signature::Keypair;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
 use self::trade::{
     Geyser,
     Logging,
     TokioRuntime,
     HttpServer,
-    Trading,
+    SolanaRpc,
+    TradeJournal,
+    GracefulShutdown,
 };
 use std::net::ToSocketAddrs;
-pub use self::trade::Trade;
+use std::time::Duration;
+pub use self::trade::{
+    GracefulShutdownPolicy,
+    HttpServerAuthentication,
+    HttpServerTls,
+    PriorityFee,
+    QuoteMint,
+    Risk,
+    TokenScreening,
+    Trade,
+    Trading,
+    TransactionSenderBackend,
+    TransactionSending,
+};
 pub use self::parse_transaction::ParseTransaction;
 use std::marker::PhantomData;
 use super::command_processor::{
     ParseTransaction as ParseTransaction__,
     Trade as Trade__,
 };
+use super::workflow_data::{
+    Calculation,
+    WorkflowData,
+};
 pub struct EnvironmentConfiguration<T> {
     pub subject: T,
 }
@@ -33,13 +55,21 @@ mod environment_configuration_file {
     pub use self::trade::Trade;
     pub use self::parse_transaction::ParseTransaction;
     pub mod trade {
-        use super::Value;
+        use super::{
+            Value,
+            ValueExist,
+        };
         #[derive(serde::Deserialize)]
         pub struct Trade {
             pub tokio_runtime: TokioRuntime,
             pub http_server: HttpServer,
             pub logging: Logging,
             pub geyser: Geyser,
+            pub solana_rpc: SolanaRpc,
+            pub trade_journal: TradeJournal,
+            pub graceful_shutdown: GracefulShutdown,
+            pub risk: Risk,
+            pub transaction_sending: TransactionSending,
             pub traiding: Trading,
         }
         #[derive(serde::Deserialize)]
@@ -51,6 +81,16 @@ mod environment_configuration_file {
         #[derive(serde::Deserialize)]
         pub struct HttpServer {
             pub tcp_socket_address: Value<String>,
+            pub authentication_method: Value<String>,
+            pub authentication_secret: Value<String>,
+            pub audit_log_file_path: Value<String>,
+            pub maximum_request_body_size: Value<usize>,
+            pub tls: ValueExist<HttpServerTls>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct HttpServerTls {
+            pub certificate_file_path: String,
+            pub private_key_file_path: String,
         }
         #[derive(serde::Deserialize)]
         pub struct Logging {
@@ -59,12 +99,85 @@ mod environment_configuration_file {
         }
         #[derive(serde::Deserialize)]
         pub struct Geyser {
-            pub grpc_url: Value<String>,
+            pub grpc_url_registry: Value<Vec<String>>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct SolanaRpc {
+            pub url: Value<String>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct TradeJournal {
+            pub database_file_path: Value<String>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct GracefulShutdown {
+            pub policy: Value<String>,
+            pub deadline_seconds: Value<u64>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct Risk {
+            pub maximum_concurrent_positions_quantity: Value<usize>,
+            pub creator_cooldown_seconds: Value<u64>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct TransactionSending {
+            pub backend_registry: Value<Vec<TransactionSenderBackend>>,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(tag = "kind", rename_all = "snake_case")]
+        pub enum TransactionSenderBackend {
+            Rpc {
+                url: String,
+                maximum_attempts_quantity: usize,
+                retry_delay_milliseconds: u64,
+            },
+            Tpu {
+                websocket_url: String,
+                fanout_slots: u64,
+            },
+            Bundle {
+                url: String,
+                tip_account_pubkey: String,
+                tip_lamports: u64,
+            },
+            Mock {
+                is_landing_simulated: bool,
+            },
         }
         #[derive(serde::Deserialize)]
         pub struct Trading {
             pub private_key: Value<Vec<u8>>,
-            pub initial_pc_amount: Value<u64>,
+            pub quote_mint_registry: Value<Vec<QuoteMint>>,
+            pub take_profit_basis_points: Value<u64>,
+            pub stop_loss_basis_points: Value<u64>,
+            pub trailing_stop_basis_points: Value<u64>,
+            pub maximum_holding_duration_seconds: Value<u64>,
+            pub is_paper_trading: Value<bool>,
+            pub maximum_open_time_delay_seconds: Value<u64>,
+            pub is_mint_authority_check_enabled: Value<bool>,
+            pub is_freeze_authority_check_enabled: Value<bool>,
+            pub is_token_2022_extension_check_enabled: Value<bool>,
+            pub maximum_creator_supply_basis_points: Value<u64>,
+            pub compute_unit_limit: Value<u32>,
+            pub priority_fee_percentile: Value<u8>,
+            pub priority_fee_window_transactions_quantity: Value<usize>,
+            pub minimum_compute_unit_price_micro_lamports: Value<u64>,
+            pub maximum_compute_unit_price_micro_lamports: Value<u64>,
+            pub maximum_trade_priority_fee_lamports: Value<u64>,
+            pub is_preflight_simulation_enabled: Value<bool>,
+            pub entry_slippage_basis_points: Value<u64>,
+            pub exit_slippage_basis_points: Value<u64>,
+            pub maximum_entry_price_impact_basis_points: Value<u64>,
+        }
+        #[derive(serde::Deserialize)]
+        pub struct QuoteMint {
+            pub mint_pubkey: String,
+            pub initial_pc_amount: u64,
+            pub maximum_exposure_pc_amount: u64,
+            pub daily_realized_loss_limit_pc_amount: u64,
+            pub minimum_initial_liquidity_pc_amount: u64,
+            pub maximum_initial_liquidity_pc_amount: u64,
+            pub position_size_basis_points: u64,
         }
     }
     pub mod parse_transaction {
@@ -86,12 +199,19 @@ mod environment_configuration_file {
 }
 mod trade {
     use std::net::SocketAddr;
+    use std::time::Duration;
     use solana_sdk::signer::keypair::Keypair;
+    use solana_sdk::pubkey::Pubkey;
     pub struct Trade {
         pub tokio_runtime: TokioRuntime,
         pub http_server: HttpServer,
         pub logging: Logging,
         pub geyser: Geyser,
+        pub solana_rpc: SolanaRpc,
+        pub trade_journal: TradeJournal,
+        pub graceful_shutdown: GracefulShutdown,
+        pub risk: Risk,
+        pub transaction_sending: TransactionSending,
         pub trading: Trading,
     }
     pub struct TokioRuntime {
@@ -101,17 +221,168 @@ mod trade {
     }
     pub struct HttpServer {
         pub tcp_socket_address: SocketAddr,
+        pub authentication: HttpServerAuthentication,
+        // Every accepted control command is appended as the JSON line.
+        pub audit_log_file_path: String,
+        // In bytes. The bigger body is not read to the end, the request is rejected.
+        pub maximum_request_body_size: usize,
+        // The plain TCP is served if it does not exist.
+        pub tls: Option<HttpServerTls>,
+    }
+    pub enum HttpServerAuthentication {
+        // The 'Authorization: Bearer <token>' header.
+        Bearer {
+            token: String,
+        },
+        // The HMAC-SHA256 of the request signed by the shared secret.
+        Hmac {
+            secret: Vec<u8>,
+        },
+    }
+    pub struct HttpServerTls {
+        // PEM, the chain of certificates.
+        pub certificate_file_path: String,
+        // PEM, the PKCS#8, PKCS#1 or SEC1 private key.
+        pub private_key_file_path: String,
     }
     pub struct Logging {
         pub directory_path: String,
         pub file_name_prefix: String,
     }
     pub struct Geyser {
-        pub grpc_url: String,
+        // Every endpoint is subscribed to concurrently, the first copy of an update wins.
+        pub grpc_url_registry: Vec<String>,
+    }
+    pub struct SolanaRpc {
+        pub url: String,
+    }
+    pub struct TradeJournal {
+        // The SQLite database, it is created and migrated on the start.
+        pub database_file_path: String,
+    }
+    pub struct GracefulShutdown {
+        pub policy: GracefulShutdownPolicy,
+    }
+    pub enum GracefulShutdownPolicy {
+        // The process ends after all trading tasks have been completed.
+        Wait,
+        // The process ends after the deadline anyway, the open positions are resumed from the trade journal on the next start.
+        WaitWithDeadline {
+            deadline_duration: Duration,
+        },
+        // The open positions are sold at the market after the deadline, then the process ends after the sells have landed.
+        Liquidate {
+            deadline_duration: Duration,
+        },
+    }
+    // The limits that are not bound to a quote mint, the rest are set per quote mint.
+    pub struct Risk {
+        // The positions that are still being bought are counted. Zero disables the rule.
+        pub maximum_concurrent_positions_quantity: usize,
+        // The pools of the same creator are entered not more often than this. Zero disables the rule.
+        pub creator_cooldown_duration: Duration,
+    }
+    pub struct TransactionSending {
+        // Every transaction is sent through all the backends at once.
+        pub backend_registry: Vec<TransactionSenderBackend>,
+    }
+    pub enum TransactionSenderBackend {
+        // The 'sendTransaction' of the RPC node, the failed request is repeated.
+        Rpc {
+            url: String,
+            maximum_attempts_quantity: usize,
+            retry_delay: Duration,
+        },
+        // The QUIC connections to the TPU of the upcoming leaders, the leader schedule is followed through the websocket of
+        // the 'solana_rpc' node.
+        Tpu {
+            websocket_url: String,
+            fanout_slots: u64,
+        },
+        // The 'sendBundle' of the block engine. The transfer of the tip is added to the end of every swap if the tip is not
+        // zero, so it is paid through whichever backend the swap lands.
+        Bundle {
+            url: String,
+            tip_account_pubkey: Pubkey,
+            tip_lamports: u64,
+        },
+        // Nothing is sent, the transactions are recorded in the memory.
+        Mock {
+            // The transaction is reported as landed at once, as if its status has been received from the Geyser.
+            is_landing_simulated: bool,
+        },
     }
     pub struct Trading {
         pub private_key: Keypair,
+        // The allow-list of the mints the robot buys with. A pool is traded only if one of its sides is in the list.
+        pub quote_mint_registry: Vec<QuoteMint>,
+        // Basis points of the 'initial_pc_amount' the position value has to reach. Zero disables the rule.
+        pub take_profit_basis_points: u64,
+        // Basis points of the 'initial_pc_amount' the position value has to fall to. Zero disables the rule.
+        pub stop_loss_basis_points: u64,
+        // Basis points of the maximum position value the position value has to fall by. Zero disables the rule.
+        pub trailing_stop_basis_points: u64,
+        // Zero disables the rule.
+        pub maximum_holding_duration_seconds: u64,
+        // Positions are opened and closed virtually against the live reserves, nothing is signed or sent.
+        pub is_paper_trading: bool,
+        // The pools that open later than this after the detection are not traded, the rest are bought at the open time.
+        // Zero disables the rule.
+        pub maximum_open_time_delay_seconds: u64,
+        pub token_screening: TokenScreening,
+        pub priority_fee: PriorityFee,
+        // Every swap is simulated before it is sent, the failed one is created again or is not sent depending on the reason.
+        pub is_preflight_simulation_enabled: bool,
+        // Basis points of the amount out the buy may lose against the quote at the latest reserves.
+        pub entry_slippage_basis_points: u64,
+        // Basis points of the amount out the sell may lose against the quote at the latest reserves.
+        pub exit_slippage_basis_points: u64,
+        // The pool is not bought if the buy moves the price by more than this. The sell is not limited, so the position
+        // can always be closed. Zero disables the rule.
+        pub maximum_entry_price_impact_basis_points: u64,
+    }
+    // The checks of the base mint that are made before the buy.
+    pub struct TokenScreening {
+        // The pool is rejected if the supply can still be increased.
+        pub is_mint_authority_check_enabled: bool,
+        // The pool is rejected if the token accounts can be frozen.
+        pub is_freeze_authority_check_enabled: bool,
+        // The pool is rejected if the Token-2022 mint has the transfer fee, the transfer hook or the permanent delegate.
+        pub is_token_2022_extension_check_enabled: bool,
+        // Basis points of the supply the creator may hold. Zero disables the rule.
+        pub maximum_creator_supply_basis_points: u64,
+    }
+    // The compute budget every swap is sent with.
+    pub struct PriorityFee {
+        // The compute units the swap requests, the priority fee is paid for all of them.
+        pub compute_unit_limit: u32,
+        // The percentile of the prices the recent Raydium AMM v4 transactions have paid, from 1 to 100.
+        pub percentile: u8,
+        // The quantity of the recent transactions the percentile is taken over.
+        pub window_transactions_quantity: usize,
+        // In micro-lamports per compute unit.
+        pub minimum_compute_unit_price: u64,
+        // In micro-lamports per compute unit. Zero disables the bound.
+        pub maximum_compute_unit_price: u64,
+        // The priority fee all the swaps of a trade may spend together, the swaps are sent without the priority fee once it
+        // has been spent. Zero disables the rule.
+        pub maximum_trade_priority_fee_lamports: u64,
+    }
+    pub struct QuoteMint {
+        pub mint_pubkey: Pubkey,
+        // The amount of the quote mint spent on a buy, in the smallest units of the mint.
         pub initial_pc_amount: u64,
+        // The total amount spent on the positions quoted in this mint. Zero disables the rule.
+        pub maximum_exposure_pc_amount: u64,
+        // All entries are stopped until the end of the UTC day once the realized loss in this mint reaches it. Zero
+        // disables the rule.
+        pub daily_realized_loss_limit_pc_amount: u64,
+        // The bounds of the initial reserve of this mint in the pool. Zero disables the bound.
+        pub minimum_initial_liquidity_pc_amount: u64,
+        pub maximum_initial_liquidity_pc_amount: u64,
+        // The buy is this share of the initial reserve of this mint, but not more than the 'initial_pc_amount'. Zero
+        // disables the sizing.
+        pub position_size_basis_points: u64,
     }
 }
 mod parse_transaction {
@@ -124,6 +395,9 @@ pub struct Loader<S> {
     _subject: PhantomData<S>,
 }
 impl Loader<Trade__> {
+    const AUTHENTICATION_SECRET_MINIMUM_LENGTH: usize = 32;
+    // https://github.com/anza-xyz/agave/blob/v2.0.18/program-runtime/src/compute_budget_processor.rs
+    const MAXIMUM_COMPUTE_UNIT_LIMIT: u32 = 1400000;
     pub fn load<'a>(environment_configuration_file_path: &'a str) -> Result<EnvironmentConfiguration<Trade>, Error> {
         let environment_configuration_file = load::<Trade_>(environment_configuration_file_path)?;
         Result::Ok(
@@ -153,13 +427,214 @@ impl Loader<Trade__> {
                                 file!(),
                             ),
                         )?,
+                        authentication: {
+                            // The secret is brute-forced offline from a single signed request if it is short.
+                            if environment_configuration_file.http_server.authentication_secret.value.len() < Self::AUTHENTICATION_SECRET_MINIMUM_LENGTH {
+                                return Result::Err(
+                                    Error::new(
+                                        "The HTTP server authentication secret is too short.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            match environment_configuration_file.http_server.authentication_method.value.as_str() {
+                                "bearer" => HttpServerAuthentication::Bearer {
+                                    token: environment_configuration_file.http_server.authentication_secret.value,
+                                },
+                                "hmac" => HttpServerAuthentication::Hmac {
+                                    secret: environment_configuration_file.http_server.authentication_secret.value.into_bytes(),
+                                },
+                                _ => {
+                                    return Result::Err(
+                                        Error::new(
+                                            "Invalid HTTP server authentication method.".into(),
+                                            Backtrace::new(
+                                                line!(),
+                                                file!(),
+                                            ),
+                                        ),
+                                    );
+                                }
+                            }
+                        },
+                        audit_log_file_path: environment_configuration_file.http_server.audit_log_file_path.value,
+                        maximum_request_body_size: {
+                            if environment_configuration_file.http_server.maximum_request_body_size.value == 0 {
+                                return Result::Err(
+                                    Error::new(
+                                        "The HTTP server maximum request body size is zero.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            environment_configuration_file.http_server.maximum_request_body_size.value
+                        },
+                        tls: if environment_configuration_file.http_server.tls.is_exist {
+                            Some(
+                                HttpServerTls {
+                                    certificate_file_path: environment_configuration_file.http_server.tls.value.certificate_file_path,
+                                    private_key_file_path: environment_configuration_file.http_server.tls.value.private_key_file_path,
+                                },
+                            )
+                        } else {
+                            None
+                        },
                     },
                     logging: Logging {
                         directory_path: environment_configuration_file.logging.directory_path.value,
                         file_name_prefix: environment_configuration_file.logging.file_name_prefix.value,
                     },
                     geyser: Geyser {
-                        grpc_url: environment_configuration_file.geyser.grpc_url.value,
+                        grpc_url_registry: {
+                            if environment_configuration_file.geyser.grpc_url_registry.value.is_empty() {
+                                return Result::Err(
+                                    Error::new(
+                                        "The Geyser gRPC URL registry is empty.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            environment_configuration_file.geyser.grpc_url_registry.value
+                        },
+                    },
+                    solana_rpc: SolanaRpc {
+                        url: environment_configuration_file.solana_rpc.url.value,
+                    },
+                    trade_journal: TradeJournal {
+                        database_file_path: environment_configuration_file.trade_journal.database_file_path.value,
+                    },
+                    graceful_shutdown: GracefulShutdown {
+                        policy: {
+                            let deadline_duration = Duration::from_secs(environment_configuration_file.graceful_shutdown.deadline_seconds.value);
+                            match environment_configuration_file.graceful_shutdown.policy.value.as_str() {
+                                "wait" => GracefulShutdownPolicy::Wait,
+                                "wait_with_deadline" => GracefulShutdownPolicy::WaitWithDeadline {
+                                    deadline_duration,
+                                },
+                                "liquidate" => GracefulShutdownPolicy::Liquidate {
+                                    deadline_duration,
+                                },
+                                _ => {
+                                    return Result::Err(
+                                        Error::new(
+                                            "Invalid graceful shutdown policy.".into(),
+                                            Backtrace::new(
+                                                line!(),
+                                                file!(),
+                                            ),
+                                        ),
+                                    );
+                                }
+                            }
+                        },
+                    },
+                    risk: Risk {
+                        maximum_concurrent_positions_quantity: environment_configuration_file.risk.maximum_concurrent_positions_quantity.value,
+                        creator_cooldown_duration: Duration::from_secs(environment_configuration_file.risk.creator_cooldown_seconds.value),
+                    },
+                    transaction_sending: TransactionSending {
+                        backend_registry: {
+                            if environment_configuration_file.transaction_sending.backend_registry.value.is_empty() {
+                                return Result::Err(
+                                    Error::new(
+                                        "The transaction sender backend registry is empty.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            let mut backend_registry = Vec::<TransactionSenderBackend>::with_capacity(
+                                environment_configuration_file.transaction_sending.backend_registry.value.len(),
+                            );
+                            '_a: for backend in environment_configuration_file.transaction_sending.backend_registry.value.into_iter() {
+                                backend_registry.push(
+                                    match backend {
+                                        TransactionSenderBackend_::Rpc {
+                                            url,
+                                            maximum_attempts_quantity,
+                                            retry_delay_milliseconds,
+                                        } => {
+                                            if maximum_attempts_quantity == 0 {
+                                                return Result::Err(
+                                                    Error::new(
+                                                        "The RPC transaction sender needs at least one attempt.".into(),
+                                                        Backtrace::new(
+                                                            line!(),
+                                                            file!(),
+                                                        ),
+                                                    ),
+                                                );
+                                            }
+                                            TransactionSenderBackend::Rpc {
+                                                url,
+                                                maximum_attempts_quantity,
+                                                retry_delay: Duration::from_millis(retry_delay_milliseconds),
+                                            }
+                                        }
+                                        TransactionSenderBackend_::Tpu {
+                                            websocket_url,
+                                            fanout_slots,
+                                        } => {
+                                            TransactionSenderBackend::Tpu {
+                                                websocket_url,
+                                                fanout_slots,
+                                            }
+                                        }
+                                        TransactionSenderBackend_::Bundle {
+                                            url,
+                                            tip_account_pubkey,
+                                            tip_lamports,
+                                        } => {
+                                            // The swap holds the single tip.
+                                            if backend_registry.iter().any(
+                                                |backend_: _| -> _ {
+                                                    matches!(backend_, TransactionSenderBackend::Bundle { .. })
+                                                }
+                                            ) {
+                                                return Result::Err(
+                                                    Error::new(
+                                                        "The bundle transaction sender backend can be only one.".into(),
+                                                        Backtrace::new(
+                                                            line!(),
+                                                            file!(),
+                                                        ),
+                                                    ),
+                                                );
+                                            }
+                                            TransactionSenderBackend::Bundle {
+                                                url,
+                                                tip_account_pubkey: Pubkey::from_str(tip_account_pubkey.as_str()).into_(
+                                                    Backtrace::new(
+                                                        line!(),
+                                                        file!(),
+                                                    ),
+                                                )?,
+                                                tip_lamports,
+                                            }
+                                        }
+                                        TransactionSenderBackend_::Mock {
+                                            is_landing_simulated,
+                                        } => {
+                                            TransactionSenderBackend::Mock {
+                                                is_landing_simulated,
+                                            }
+                                        }
+                                    },
+                                );
+                            }
+                            backend_registry
+                        },
                     },
                     trading: Trading {
                         private_key: Keypair::from_bytes(
@@ -171,7 +646,177 @@ impl Loader<Trade__> {
                                 file!(),
                             ),
                         )?,
-                        initial_pc_amount: environment_configuration_file.traiding.initial_pc_amount.value,
+                        quote_mint_registry: {
+                            let mut quote_mint_registry = Vec::<QuoteMint>::with_capacity(
+                                environment_configuration_file.traiding.quote_mint_registry.value.len(),
+                            );
+                            '_a: for quote_mint in environment_configuration_file.traiding.quote_mint_registry.value.iter() {
+                                if quote_mint.position_size_basis_points > WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE {
+                                    return Result::Err(
+                                        Error::new(
+                                            "The position size is more than the whole pool.".into(),
+                                            Backtrace::new(
+                                                line!(),
+                                                file!(),
+                                            ),
+                                        ),
+                                    );
+                                }
+                                if quote_mint.maximum_initial_liquidity_pc_amount != 0 && quote_mint.minimum_initial_liquidity_pc_amount > quote_mint.maximum_initial_liquidity_pc_amount {
+                                    return Result::Err(
+                                        Error::new(
+                                            "The minimum initial liquidity is more than the maximum one.".into(),
+                                            Backtrace::new(
+                                                line!(),
+                                                file!(),
+                                            ),
+                                        ),
+                                    );
+                                }
+                                quote_mint_registry.push(
+                                    QuoteMint {
+                                        mint_pubkey: Pubkey::from_str(quote_mint.mint_pubkey.as_str()).into_(
+                                            Backtrace::new(
+                                                line!(),
+                                                file!(),
+                                            ),
+                                        )?,
+                                        initial_pc_amount: quote_mint.initial_pc_amount,
+                                        maximum_exposure_pc_amount: quote_mint.maximum_exposure_pc_amount,
+                                        daily_realized_loss_limit_pc_amount: quote_mint.daily_realized_loss_limit_pc_amount,
+                                        minimum_initial_liquidity_pc_amount: quote_mint.minimum_initial_liquidity_pc_amount,
+                                        maximum_initial_liquidity_pc_amount: quote_mint.maximum_initial_liquidity_pc_amount,
+                                        position_size_basis_points: quote_mint.position_size_basis_points,
+                                    },
+                                );
+                            }
+                            quote_mint_registry
+                        },
+                        take_profit_basis_points: environment_configuration_file.traiding.take_profit_basis_points.value,
+                        stop_loss_basis_points: environment_configuration_file.traiding.stop_loss_basis_points.value,
+                        trailing_stop_basis_points: {
+                            if environment_configuration_file.traiding.trailing_stop_basis_points.value > WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE {
+                                return Result::Err(
+                                    Error::new(
+                                        "The trailing stop is more than the whole amount.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            environment_configuration_file.traiding.trailing_stop_basis_points.value
+                        },
+                        maximum_holding_duration_seconds: environment_configuration_file.traiding.maximum_holding_duration_seconds.value,
+                        is_paper_trading: environment_configuration_file.traiding.is_paper_trading.value,
+                        maximum_open_time_delay_seconds: environment_configuration_file.traiding.maximum_open_time_delay_seconds.value,
+                        token_screening: {
+                            if environment_configuration_file.traiding.maximum_creator_supply_basis_points.value > WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE {
+                                return Result::Err(
+                                    Error::new(
+                                        "The maximum creator supply is more than the whole supply.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            TokenScreening {
+                                is_mint_authority_check_enabled: environment_configuration_file.traiding.is_mint_authority_check_enabled.value,
+                                is_freeze_authority_check_enabled: environment_configuration_file.traiding.is_freeze_authority_check_enabled.value,
+                                is_token_2022_extension_check_enabled: environment_configuration_file.traiding.is_token_2022_extension_check_enabled.value,
+                                maximum_creator_supply_basis_points: environment_configuration_file.traiding.maximum_creator_supply_basis_points.value,
+                            }
+                        },
+                        priority_fee: {
+                            if environment_configuration_file.traiding.compute_unit_limit.value == 0
+                            || environment_configuration_file.traiding.compute_unit_limit.value > Self::MAXIMUM_COMPUTE_UNIT_LIMIT {
+                                return Result::Err(
+                                    Error::new(
+                                        "The compute unit limit is out of the range the runtime allows.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            if environment_configuration_file.traiding.priority_fee_percentile.value == 0
+                            || environment_configuration_file.traiding.priority_fee_percentile.value > 100 {
+                                return Result::Err(
+                                    Error::new(
+                                        "The priority fee percentile is not from 1 to 100.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            if environment_configuration_file.traiding.priority_fee_window_transactions_quantity.value == 0 {
+                                return Result::Err(
+                                    Error::new(
+                                        "The priority fee window is empty.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            if environment_configuration_file.traiding.maximum_compute_unit_price_micro_lamports.value != 0
+                            && environment_configuration_file.traiding.minimum_compute_unit_price_micro_lamports.value > environment_configuration_file.traiding.maximum_compute_unit_price_micro_lamports.value {
+                                return Result::Err(
+                                    Error::new(
+                                        "The minimum compute unit price is more than the maximum one.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            PriorityFee {
+                                compute_unit_limit: environment_configuration_file.traiding.compute_unit_limit.value,
+                                percentile: environment_configuration_file.traiding.priority_fee_percentile.value,
+                                window_transactions_quantity: environment_configuration_file.traiding.priority_fee_window_transactions_quantity.value,
+                                minimum_compute_unit_price: environment_configuration_file.traiding.minimum_compute_unit_price_micro_lamports.value,
+                                maximum_compute_unit_price: environment_configuration_file.traiding.maximum_compute_unit_price_micro_lamports.value,
+                                maximum_trade_priority_fee_lamports: environment_configuration_file.traiding.maximum_trade_priority_fee_lamports.value,
+                            }
+                        },
+                        is_preflight_simulation_enabled: environment_configuration_file.traiding.is_preflight_simulation_enabled.value,
+                        entry_slippage_basis_points: {
+                            if environment_configuration_file.traiding.entry_slippage_basis_points.value > WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE {
+                                return Result::Err(
+                                    Error::new(
+                                        "The entry slippage is more than the whole amount.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            environment_configuration_file.traiding.entry_slippage_basis_points.value
+                        },
+                        exit_slippage_basis_points: {
+                            if environment_configuration_file.traiding.exit_slippage_basis_points.value > WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE {
+                                return Result::Err(
+                                    Error::new(
+                                        "The exit slippage is more than the whole amount.".into(),
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                            environment_configuration_file.traiding.exit_slippage_basis_points.value
+                        },
+                        maximum_entry_price_impact_basis_points: environment_configuration_file.traiding.maximum_entry_price_impact_basis_points.value,
                     }
                 },
             },
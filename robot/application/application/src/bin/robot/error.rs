@@ -6,6 +6,7 @@ use std::{
         Error as FmtError,
         Formatter,
     },
+    sync::LockResult,
 };
 #[derive(Debug)]
 pub struct Error(pub Auditor<Box<dyn StdError + Send + Sync + 'static>>);
@@ -106,6 +107,22 @@ impl<T> OptionConverter<T> for Option<T> {
         )
     }
 }
+// The lock is poisoned only if some thread has panicked while holding it, which is not expected to happen.
+pub trait LockResultConverter<T> {
+    fn into_unreachable_state(self, backtrace: Backtrace) -> Result<T, Error>;
+}
+impl<T> LockResultConverter<T> for LockResult<T> {
+    fn into_unreachable_state(self, backtrace: Backtrace) -> Result<T, Error> {
+        self.map_err(
+            move |_: _| -> _ {
+                return Error::new_(
+                    Common::UnreachableState,
+                    backtrace,
+                );
+            },
+        )
+    }
+}
 #[derive(Debug)]
 pub enum Common {
     OutOfRange,
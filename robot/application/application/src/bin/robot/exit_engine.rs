@@ -0,0 +1,279 @@
+use super::environment_configuration::Trading;
+use super::error::{
+    Backtrace,
+    Error,
+    LockResultConverter,
+    OptionConverter,
+};
+use std::sync::RwLock;
+use super::extern_source::U128;
+use super::workflow_data::{
+    Calculation,
+    WorkflowData,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+pub static EXIT_STRATEGY_HOLDER: ExitStrategyHolder = ExitStrategyHolder::new();
+// The exit rules the positions are opened with. They are replaced at runtime by the HTTP API, the open positions keep
+// the rules they have been opened with.
+pub struct ExitStrategyHolder {
+    exit_strategy: RwLock<Option<ExitStrategy>>,
+}
+impl ExitStrategyHolder {
+    const fn new() -> Self {
+        Self {
+            exit_strategy: RwLock::new(None),
+        }
+    }
+    pub fn get<'a>(&'a self) -> Result<ExitStrategy, Error> {
+        let exit_strategy = *self.exit_strategy.read().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        exit_strategy.into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    pub fn set<'a>(&'a self, exit_strategy: ExitStrategy) -> Result<(), Error> {
+        *self.exit_strategy.write().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )? = Some(exit_strategy);
+        Ok(())
+    }
+}
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct ExitStrategy {
+    pub take_profit_basis_points: u64,
+    pub stop_loss_basis_points: u64,
+    pub trailing_stop_basis_points: u64,
+    pub maximum_holding_duration_seconds: u64,
+}
+impl<'a> From<&'a Trading> for ExitStrategy {
+    fn from(trading: &'a Trading) -> Self {
+        Self {
+            take_profit_basis_points: trading.take_profit_basis_points,
+            stop_loss_basis_points: trading.stop_loss_basis_points,
+            trailing_stop_basis_points: trading.trailing_stop_basis_points,
+            maximum_holding_duration_seconds: trading.maximum_holding_duration_seconds,
+        }
+    }
+}
+pub struct ExitEngine {
+    take_profit_pc_amount: Option<U128>,
+    stop_loss_pc_amount: Option<U128>,
+    trailing_stop_basis_points: u64,
+    maximum_position_pc_amount: U128,
+    deadline: Option<Instant>,
+}
+impl ExitEngine {
+    pub fn new<'a>(exit_strategy: &'a ExitStrategy, entry_pc_amount: u64, opening_instant: Instant) -> Result<Self, Error> {
+        let take_profit_pc_amount = if exit_strategy.take_profit_basis_points != 0 {
+            Some(Self::apply_basis_points(U128::from(entry_pc_amount), exit_strategy.take_profit_basis_points)?)
+        } else {
+            None
+        };
+        let stop_loss_pc_amount = if exit_strategy.stop_loss_basis_points != 0 {
+            Some(Self::apply_basis_points(U128::from(entry_pc_amount), exit_strategy.stop_loss_basis_points)?)
+        } else {
+            None
+        };
+        let deadline = if exit_strategy.maximum_holding_duration_seconds != 0 {
+            Some(
+                opening_instant
+                .checked_add(Duration::from_secs(exit_strategy.maximum_holding_duration_seconds))
+                .into_out_of_range(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?,
+            )
+        } else {
+            None
+        };
+        Ok(
+            Self {
+                take_profit_pc_amount,
+                stop_loss_pc_amount,
+                trailing_stop_basis_points: exit_strategy.trailing_stop_basis_points,
+                maximum_position_pc_amount: U128::from(entry_pc_amount),
+                deadline,
+            },
+        )
+    }
+    pub fn get_deadline<'a>(&'a self) -> Option<Instant> {
+        self.deadline
+    }
+    // The 'position_pc_amount' is the PC amount the whole position would be sold for at the current reserves. The
+    // trailing stop is expected to be not more than the whole amount, it is checked where the strategy is received.
+    pub fn evaluate<'a>(&'a mut self, position_pc_amount: U128, now: Instant) -> Result<Option<ExitReason>, Error> {
+        if position_pc_amount > self.maximum_position_pc_amount {
+            self.maximum_position_pc_amount = position_pc_amount;
+        }
+        if let Some(take_profit_pc_amount) = self.take_profit_pc_amount {
+            if position_pc_amount >= take_profit_pc_amount {
+                return Ok(Some(ExitReason::TakeProfit));
+            }
+        }
+        if let Some(stop_loss_pc_amount) = self.stop_loss_pc_amount {
+            if position_pc_amount <= stop_loss_pc_amount {
+                return Ok(Some(ExitReason::StopLoss));
+            }
+        }
+        if self.trailing_stop_basis_points != 0 {
+            let trailing_stop_pc_amount = Self::apply_basis_points(
+                self.maximum_position_pc_amount,
+                WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE - self.trailing_stop_basis_points,
+            )?;
+            if position_pc_amount <= trailing_stop_pc_amount {
+                return Ok(Some(ExitReason::TrailingStop));
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if now >= deadline {
+                return Ok(Some(ExitReason::MaximumHoldingDuration));
+            }
+        }
+        Ok(None)
+    }
+    fn apply_basis_points(amount: U128, basis_points: u64) -> Result<U128, Error> {
+        amount
+        .checked_mul(U128::from(basis_points))
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .checked_div(U128::from(WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE))
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+    TrailingStop,
+    MaximumHoldingDuration,
+    // The position is sold at the market by the graceful shutdown.
+    Liquidation,
+    // The position is sold at the market by the HTTP API.
+    ForcedSell,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn exit_by_take_profit() -> Result<(), Error> {
+        let mut exit_engine = ExitEngine::new(
+            &ExitStrategy {
+                take_profit_basis_points: 15000,
+                stop_loss_basis_points: 0,
+                trailing_stop_basis_points: 0,
+                maximum_holding_duration_seconds: 0,
+            },
+            1000,
+            Instant::now(),
+        )?;
+        let now = Instant::now();
+        assert!(exit_engine.evaluate(U128::from(1499), now)?.is_none());
+        assert!(matches!(exit_engine.evaluate(U128::from(1500), now)?, Some(ExitReason::TakeProfit)));
+        Ok(())
+    }
+    #[test]
+    fn exit_by_stop_loss() -> Result<(), Error> {
+        let mut exit_engine = ExitEngine::new(
+            &ExitStrategy {
+                take_profit_basis_points: 0,
+                stop_loss_basis_points: 8000,
+                trailing_stop_basis_points: 0,
+                maximum_holding_duration_seconds: 0,
+            },
+            1000,
+            Instant::now(),
+        )?;
+        let now = Instant::now();
+        // Without the take profit the growth of the position does not close it.
+        assert!(exit_engine.evaluate(U128::from(1000000), now)?.is_none());
+        assert!(exit_engine.evaluate(U128::from(801), now)?.is_none());
+        assert!(matches!(exit_engine.evaluate(U128::from(800), now)?, Some(ExitReason::StopLoss)));
+        Ok(())
+    }
+    #[test]
+    fn exit_by_trailing_stop_from_peak() -> Result<(), Error> {
+        let mut exit_engine = ExitEngine::new(
+            &ExitStrategy {
+                take_profit_basis_points: 0,
+                stop_loss_basis_points: 0,
+                trailing_stop_basis_points: 2000,
+                maximum_holding_duration_seconds: 0,
+            },
+            1000,
+            Instant::now(),
+        )?;
+        let now = Instant::now();
+        // The peak starts from the entry amount.
+        assert!(exit_engine.evaluate(U128::from(801), now)?.is_none());
+        assert!(exit_engine.evaluate(U128::from(1500), now)?.is_none());
+        // The drop is counted from the peak, not from the last evaluated amount.
+        assert!(exit_engine.evaluate(U128::from(1300), now)?.is_none());
+        assert!(exit_engine.evaluate(U128::from(1201), now)?.is_none());
+        assert!(matches!(exit_engine.evaluate(U128::from(1200), now)?, Some(ExitReason::TrailingStop)));
+        Ok(())
+    }
+    #[test]
+    fn exit_by_deadline() -> Result<(), Error> {
+        let opening_instant = Instant::now();
+        let mut exit_engine = ExitEngine::new(
+            &ExitStrategy {
+                take_profit_basis_points: 0,
+                stop_loss_basis_points: 0,
+                trailing_stop_basis_points: 0,
+                maximum_holding_duration_seconds: 60,
+            },
+            1000,
+            opening_instant,
+        )?;
+        assert_eq!(exit_engine.get_deadline(), Some(opening_instant + Duration::from_secs(60)));
+        assert!(exit_engine.evaluate(U128::from(1000), opening_instant + Duration::from_secs(59))?.is_none());
+        assert!(
+            matches!(
+                exit_engine.evaluate(U128::from(1000), opening_instant + Duration::from_secs(60))?,
+                Some(ExitReason::MaximumHoldingDuration),
+            ),
+        );
+        // The price rules are checked before the deadline.
+        assert!(
+            matches!(
+                ExitEngine::new(
+                    &ExitStrategy {
+                        take_profit_basis_points: 0,
+                        stop_loss_basis_points: 9000,
+                        trailing_stop_basis_points: 0,
+                        maximum_holding_duration_seconds: 60,
+                    },
+                    1000,
+                    opening_instant,
+                )?
+                .evaluate(U128::from(900), opening_instant + Duration::from_secs(60))?,
+                Some(ExitReason::StopLoss),
+            ),
+        );
+        Ok(())
+    }
+}
@@ -3,11 +3,26 @@ use super::error::{
     Error,
     Common,
     OptionConverter,
+    ResultConverter,
+};
+use solana_sdk::{
+    instruction::{
+        AccountMeta,
+        Instruction,
+    },
+    pubkey::Pubkey,
 };
 use uint::construct_uint;
+use super::workflow_data::{
+    Calculation,
+    WorkflowData,
+};
 construct_uint! {
     pub struct U128(2);
 }
+construct_uint! {
+    pub struct U256(4);
+}
 // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/instruction.rs#L29
 #[repr(C)]
 #[derive(Debug)]
@@ -109,6 +124,252 @@ impl RaydiumAmmInitializeInstruction2 {
         }
     }
 }
+// https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/instruction.rs
+#[derive(Debug)]
+pub struct RaydiumAmmSwapBaseInInstruction {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+}
+impl RaydiumAmmSwapBaseInInstruction {
+    const TAG: u8 = 9;
+    // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/instruction.rs
+    pub fn pack<'a>(&'a self) -> Vec<u8> {
+        let mut data = Vec::<u8>::with_capacity(17);
+        data.push(Self::TAG);
+        data.extend_from_slice(self.amount_in.to_le_bytes().as_slice());
+        data.extend_from_slice(self.minimum_amount_out.to_le_bytes().as_slice());
+        data
+    }
+    // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/instruction.rs
+    pub fn create_instruction<'a>(&'a self, program_id: &'a Pubkey, raydium_amm_swap_accounts: &'a RaydiumAmmSwapAccounts) -> Instruction {
+        Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(raydium_amm_swap_accounts.token_program, false),
+                AccountMeta::new(raydium_amm_swap_accounts.amm, false),
+                AccountMeta::new_readonly(raydium_amm_swap_accounts.amm_authority, false),
+                AccountMeta::new(raydium_amm_swap_accounts.amm_open_orders, false),
+                AccountMeta::new(raydium_amm_swap_accounts.amm_target_orders, false),
+                AccountMeta::new(raydium_amm_swap_accounts.amm_coin_vault, false),
+                AccountMeta::new(raydium_amm_swap_accounts.amm_pc_vault, false),
+                AccountMeta::new_readonly(raydium_amm_swap_accounts.serum_program, false),
+                AccountMeta::new(raydium_amm_swap_accounts.serum_market, false),
+                AccountMeta::new(raydium_amm_swap_accounts.serum_bids, false),
+                AccountMeta::new(raydium_amm_swap_accounts.serum_asks, false),
+                AccountMeta::new(raydium_amm_swap_accounts.serum_event_queue, false),
+                AccountMeta::new(raydium_amm_swap_accounts.serum_coin_vault, false),
+                AccountMeta::new(raydium_amm_swap_accounts.serum_pc_vault, false),
+                AccountMeta::new_readonly(raydium_amm_swap_accounts.serum_vault_signer, false),
+                AccountMeta::new(raydium_amm_swap_accounts.user_source_token_account, false),
+                AccountMeta::new(raydium_amm_swap_accounts.user_destination_token_account, false),
+                AccountMeta::new_readonly(raydium_amm_swap_accounts.user_owner, true),
+            ],
+            data: self.pack(),
+        }
+    }
+}
+pub struct RaydiumAmmSwapAccounts {
+    pub token_program: Pubkey,
+    pub amm: Pubkey,
+    pub amm_authority: Pubkey,
+    pub amm_open_orders: Pubkey,
+    pub amm_target_orders: Pubkey,
+    pub amm_coin_vault: Pubkey,
+    pub amm_pc_vault: Pubkey,
+    pub serum_program: Pubkey,
+    pub serum_market: Pubkey,
+    pub serum_bids: Pubkey,
+    pub serum_asks: Pubkey,
+    pub serum_event_queue: Pubkey,
+    pub serum_coin_vault: Pubkey,
+    pub serum_pc_vault: Pubkey,
+    pub serum_vault_signer: Pubkey,
+    pub user_source_token_account: Pubkey,
+    pub user_destination_token_account: Pubkey,
+    pub user_owner: Pubkey,
+}
+// https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/state.rs
+#[derive(Debug)]
+pub struct RaydiumAmmInfo {
+    pub status: u64,
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub pool_open_time: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+}
+impl RaydiumAmmInfo {
+    const LENGTH: usize = 752;
+    const STATUS_OFFSET: usize = 0;
+    const NEED_TAKE_PNL_COIN_OFFSET: usize = 192;
+    const NEED_TAKE_PNL_PC_OFFSET: usize = 200;
+    const POOL_OPEN_TIME_OFFSET: usize = 224;
+    const COIN_VAULT_OFFSET: usize = 336;
+    const PC_VAULT_OFFSET: usize = 368;
+    const COIN_VAULT_MINT_OFFSET: usize = 400;
+    const PC_VAULT_MINT_OFFSET: usize = 432;
+    const OPEN_ORDERS_OFFSET: usize = 496;
+    const MARKET_OFFSET: usize = 528;
+    const MARKET_PROGRAM_OFFSET: usize = 560;
+    const TARGET_ORDERS_OFFSET: usize = 592;
+    // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/state.rs
+    const STATUS_INITIALIZED: u64 = 1;
+    const STATUS_ORDER_BOOK_ONLY: u64 = 5;
+    pub fn unpack<'a>(input: &'a [u8]) -> Result<Self, Error> {
+        AccountDataReader::check_length(input, Self::LENGTH)?;
+        Ok(
+            Self {
+                status: AccountDataReader::read_u64(input, Self::STATUS_OFFSET)?,
+                need_take_pnl_coin: AccountDataReader::read_u64(input, Self::NEED_TAKE_PNL_COIN_OFFSET)?,
+                need_take_pnl_pc: AccountDataReader::read_u64(input, Self::NEED_TAKE_PNL_PC_OFFSET)?,
+                pool_open_time: AccountDataReader::read_u64(input, Self::POOL_OPEN_TIME_OFFSET)?,
+                coin_vault: AccountDataReader::read_pubkey(input, Self::COIN_VAULT_OFFSET)?,
+                pc_vault: AccountDataReader::read_pubkey(input, Self::PC_VAULT_OFFSET)?,
+                coin_vault_mint: AccountDataReader::read_pubkey(input, Self::COIN_VAULT_MINT_OFFSET)?,
+                pc_vault_mint: AccountDataReader::read_pubkey(input, Self::PC_VAULT_MINT_OFFSET)?,
+                open_orders: AccountDataReader::read_pubkey(input, Self::OPEN_ORDERS_OFFSET)?,
+                market: AccountDataReader::read_pubkey(input, Self::MARKET_OFFSET)?,
+                market_program: AccountDataReader::read_pubkey(input, Self::MARKET_PROGRAM_OFFSET)?,
+                target_orders: AccountDataReader::read_pubkey(input, Self::TARGET_ORDERS_OFFSET)?,
+            },
+        )
+    }
+    // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/state.rs
+    pub fn is_order_book_enabled<'a>(&'a self) -> bool {
+        self.status == Self::STATUS_INITIALIZED || self.status == Self::STATUS_ORDER_BOOK_ONLY
+    }
+}
+// https://github.com/openbook-dex/program/blob/master/dex/src/state.rs
+#[derive(Debug)]
+pub struct SerumMarketState {
+    pub vault_signer_nonce: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub event_queue: Pubkey,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+}
+impl SerumMarketState {
+    // The account data is surrounded by the 5 bytes of the 'serum' head padding and the 7 bytes of the tail padding.
+    const LENGTH: usize = 388;
+    const VAULT_SIGNER_NONCE_OFFSET: usize = 45;
+    const COIN_VAULT_OFFSET: usize = 117;
+    const PC_VAULT_OFFSET: usize = 165;
+    const EVENT_QUEUE_OFFSET: usize = 253;
+    const BIDS_OFFSET: usize = 285;
+    const ASKS_OFFSET: usize = 317;
+    pub fn unpack<'a>(input: &'a [u8]) -> Result<Self, Error> {
+        AccountDataReader::check_length(input, Self::LENGTH)?;
+        Ok(
+            Self {
+                vault_signer_nonce: AccountDataReader::read_u64(input, Self::VAULT_SIGNER_NONCE_OFFSET)?,
+                coin_vault: AccountDataReader::read_pubkey(input, Self::COIN_VAULT_OFFSET)?,
+                pc_vault: AccountDataReader::read_pubkey(input, Self::PC_VAULT_OFFSET)?,
+                event_queue: AccountDataReader::read_pubkey(input, Self::EVENT_QUEUE_OFFSET)?,
+                bids: AccountDataReader::read_pubkey(input, Self::BIDS_OFFSET)?,
+                asks: AccountDataReader::read_pubkey(input, Self::ASKS_OFFSET)?,
+            },
+        )
+    }
+    // https://github.com/openbook-dex/program/blob/master/dex/src/state.rs
+    pub fn get_vault_signer<'a>(&'a self, market: &'a Pubkey, market_program: &'a Pubkey) -> Result<Pubkey, Error> {
+        Pubkey::create_program_address(
+            &[
+                market.as_ref(),
+                self.vault_signer_nonce.to_le_bytes().as_slice(),
+            ],
+            market_program,
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+// https://github.com/openbook-dex/program/blob/master/dex/src/state.rs
+#[derive(Debug)]
+pub struct SerumOpenOrders {
+    pub native_coin_total: u64,
+    pub native_pc_total: u64,
+}
+impl SerumOpenOrders {
+    // The account data is surrounded by the 5 bytes of the 'serum' head padding and the 7 bytes of the tail padding.
+    const LENGTH: usize = 3228;
+    const NATIVE_COIN_TOTAL_OFFSET: usize = 85;
+    const NATIVE_PC_TOTAL_OFFSET: usize = 101;
+    pub fn unpack<'a>(input: &'a [u8]) -> Result<Self, Error> {
+        AccountDataReader::check_length(input, Self::LENGTH)?;
+        Ok(
+            Self {
+                native_coin_total: AccountDataReader::read_u64(input, Self::NATIVE_COIN_TOTAL_OFFSET)?,
+                native_pc_total: AccountDataReader::read_u64(input, Self::NATIVE_PC_TOTAL_OFFSET)?,
+            },
+        )
+    }
+}
+struct AccountDataReader;
+impl AccountDataReader {
+    fn check_length<'a>(input: &'a [u8], length: usize) -> Result<(), Error> {
+        if input.len() != length {
+            return Err(
+                Error::new_(
+                    Common::OutOfRange,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        Ok(())
+    }
+    fn read_u64<'a>(input: &'a [u8], offset: usize) -> Result<u64, Error> {
+        input
+        .get(offset..offset + 8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn read_pubkey<'a>(input: &'a [u8], offset: usize) -> Result<Pubkey, Error> {
+        input
+        .get(offset..offset + 32)
+        .and_then(|slice| <[u8; 32]>::try_from(slice).ok())
+        .map(Pubkey::new_from_array)
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+// https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/math.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapDirection {
+    PcToCoin,
+    CoinToPc,
+}
+impl SwapDirection {
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::PcToCoin => Self::CoinToPc,
+            Self::CoinToPc => Self::PcToCoin,
+        }
+    }
+}
 pub struct Calcaulator;
 impl Calcaulator {
     // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/state.rs#L518
@@ -120,9 +381,142 @@ impl Calcaulator {
         pc_amount: u64,
         total_pc_amount_without_take_pnl: u64,
         total_coin_amount_without_take_pnl: u64,
+    ) -> Result<U128, Error> {
+        Self::swap_base_in(
+            pc_amount,
+            total_pc_amount_without_take_pnl,
+            total_coin_amount_without_take_pnl,
+        )
+    }
+    pub fn get_pc_amount_from_coin_amount(
+        coin_amount: u64,
+        total_pc_amount_without_take_pnl: u64,
+        total_coin_amount_without_take_pnl: u64,
+    ) -> Result<U128, Error> {
+        Self::swap_base_in(
+            coin_amount,
+            total_coin_amount_without_take_pnl,
+            total_pc_amount_without_take_pnl,
+        )
+    }
+    pub fn get_amount_out(
+        amount_in: u64,
+        total_pc_amount_without_take_pnl: u64,
+        total_coin_amount_without_take_pnl: u64,
+        swap_direction: SwapDirection,
+    ) -> Result<U128, Error> {
+        match swap_direction {
+            SwapDirection::PcToCoin => Self::get_coin_amount_from_pc_amount(
+                amount_in,
+                total_pc_amount_without_take_pnl,
+                total_coin_amount_without_take_pnl,
+            ),
+            SwapDirection::CoinToPc => Self::get_pc_amount_from_coin_amount(
+                amount_in,
+                total_pc_amount_without_take_pnl,
+                total_coin_amount_without_take_pnl,
+            ),
+        }
+    }
+    pub fn get_required_pc_amount_for_coin_amount(
+        coin_amount: u64,
+        total_pc_amount_without_take_pnl: u64,
+        total_coin_amount_without_take_pnl: u64,
+    ) -> Result<U128, Error> {
+        Self::swap_base_out(
+            coin_amount,
+            total_pc_amount_without_take_pnl,
+            total_coin_amount_without_take_pnl,
+        )
+    }
+    pub fn get_required_coin_amount_for_pc_amount(
+        pc_amount: u64,
+        total_pc_amount_without_take_pnl: u64,
+        total_coin_amount_without_take_pnl: u64,
+    ) -> Result<U128, Error> {
+        Self::swap_base_out(
+            pc_amount,
+            total_coin_amount_without_take_pnl,
+            total_pc_amount_without_take_pnl,
+        )
+    }
+    // The difference between the spot price of the pool and the execution price of the swap, the swap fee included.
+    pub fn get_price_impact_basis_points(
+        amount_in: u64,
+        amount_out: u64,
+        total_amount_in_without_take_pnl: u64,
+        total_amount_out_without_take_pnl: u64,
+    ) -> Result<u64, Error> {
+        let spot_amount_out = U256::from(amount_in)
+        .checked_mul(total_amount_out_without_take_pnl.into())
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let execution_basis_points = U256::from(amount_out)
+        .checked_mul(total_amount_in_without_take_pnl.into())
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .checked_mul(WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE.into())
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .checked_div(spot_amount_out)
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        if execution_basis_points >= U256::from(WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE) {
+            return Ok(0);
+        }
+        Ok(WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE - execution_basis_points.low_u64())
+    }
+    // The expected amount out reduced by the slippage tolerance, rounded down. The swap instruction fails if it gives less.
+    pub fn get_minimum_amount_out(amount_out: U128, slippage_basis_points: u64) -> Result<u64, Error> {
+        let minimum_amount_out = amount_out
+        .checked_mul(
+            WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE
+            .checked_sub(slippage_basis_points)
+            .into_out_of_range(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .into(),
+        )
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        / U128::from(WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE);
+        u64::try_from(minimum_amount_out).ok().into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn swap_base_in(
+        amount_in: u64,
+        total_amount_in_without_take_pnl: u64,
+        total_amount_out_without_take_pnl: u64,
     ) -> Result<U128, Error> {
         // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/processor.rs#L2393
-        let swap_fee_pc_amount = U128::from(pc_amount)
+        let swap_fee_amount = U128::from(amount_in)
         .checked_mul(Self::AMM_SWAP_FEE.numerator.into())
         .into_out_of_range(
             Backtrace::new(
@@ -138,8 +532,8 @@ impl Calcaulator {
             ),
         )?
         .0;
-        let pc_amount_after_taking_swap_fee = U128::from(pc_amount)
-        .checked_sub(swap_fee_pc_amount)
+        let amount_in_after_taking_swap_fee = U128::from(amount_in)
+        .checked_sub(swap_fee_amount)
         .into_out_of_range(
             Backtrace::new(
                 line!(),
@@ -147,16 +541,16 @@ impl Calcaulator {
             ),
         )?;
         // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/math.rs#L401
-        let denominator = U128::from(total_pc_amount_without_take_pnl)
-        .checked_add(pc_amount_after_taking_swap_fee)
+        let denominator = U128::from(total_amount_in_without_take_pnl)
+        .checked_add(amount_in_after_taking_swap_fee)
         .into_out_of_range(
             Backtrace::new(
                 line!(),
                 file!(),
             ),
         )?;
-        U128::from(total_coin_amount_without_take_pnl)
-        .checked_mul(pc_amount_after_taking_swap_fee)
+        U128::from(total_amount_out_without_take_pnl)
+        .checked_mul(amount_in_after_taking_swap_fee)
         .into_out_of_range(
             Backtrace::new(
                 line!(),
@@ -171,6 +565,60 @@ impl Calcaulator {
             ),
         )
     }
+    fn swap_base_out(
+        amount_out: u64,
+        total_amount_in_without_take_pnl: u64,
+        total_amount_out_without_take_pnl: u64,
+    ) -> Result<U128, Error> {
+        // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/math.rs
+        let denominator = U128::from(total_amount_out_without_take_pnl)
+        .checked_sub(amount_out.into())
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let amount_in_before_adding_swap_fee = U128::from(total_amount_in_without_take_pnl)
+        .checked_mul(amount_out.into())
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .checked_ceil_div(denominator)
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .0;
+        // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/processor.rs
+        amount_in_before_adding_swap_fee
+        .checked_mul(Self::AMM_SWAP_FEE.denominator.into())
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .checked_ceil_div(
+            (Self::AMM_SWAP_FEE.denominator - Self::AMM_SWAP_FEE.numerator).into(),
+        )
+        .map(
+            |ceil_div: _| -> _ {
+                ceil_div.0
+            }
+        )
+        .into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
 }
 struct AmmSwapFee {
     numerator: u64,
@@ -212,4 +660,160 @@ impl CheckedCeilDiv for U128 {
         }
         Some((quotient, rhs))
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    // The reserves right after the pool initialization in the '2gMuTdGx6RaQKSrUqGib2kkNQ7XD71eMvA3fm8h5MY8qFSLoALQrnxiWo3YzCdaTSEstGd751HwD3LqVaxjX268t' transaction.
+    const TOTAL_PC_AMOUNT: u64 = 763000000000;
+    const TOTAL_COIN_AMOUNT: u64 = 206900000000000000;
+    const ITERATIONS_QUANTITY: usize = 100000;
+    struct Xorshift(u64);
+    impl Xorshift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn next_in_range(&mut self, minimum: u64, maximum: u64) -> u64 {
+            minimum + self.next() % (maximum - minimum + 1)
+        }
+    }
+    #[test]
+    fn get_coin_amount_from_pc_amount() -> Result<(), Error> {
+        assert_eq!(Calcaulator::get_coin_amount_from_pc_amount(1000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(270135373479625_u64));
+        assert_eq!(Calcaulator::get_coin_amount_from_pc_amount(5000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(1343659564771562_u64));
+        assert_eq!(Calcaulator::get_coin_amount_from_pc_amount(12345, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(3339143589_u64));
+        Ok(())
+    }
+    #[test]
+    fn get_pc_amount_from_coin_amount() -> Result<(), Error> {
+        assert_eq!(Calcaulator::get_pc_amount_from_coin_amount(1000000000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(3660902608_u64));
+        assert_eq!(Calcaulator::get_pc_amount_from_coin_amount(270000000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(991917963_u64));
+        Ok(())
+    }
+    #[test]
+    fn get_required_amount() -> Result<(), Error> {
+        assert_eq!(Calcaulator::get_required_pc_amount_for_coin_amount(1000000000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(3714969796_u64));
+        assert_eq!(Calcaulator::get_required_coin_amount_for_pc_amount(1000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(272202816753170_u64));
+        assert!(Calcaulator::get_required_pc_amount_for_coin_amount(TOTAL_COIN_AMOUNT, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT).is_err());
+        Ok(())
+    }
+    #[test]
+    fn checked_ceil_div() -> Result<(), Error> {
+        // The divisor is reduced to the smallest one that gives the same ceiled quotient.
+        assert_eq!(U128::from(7).checked_ceil_div(U128::from(2)), Some((U128::from(4), U128::from(2))));
+        assert_eq!(U128::from(10).checked_ceil_div(U128::from(5)), Some((U128::from(2), U128::from(5))));
+        // The quotient less than one half is zero, not one.
+        assert_eq!(U128::from(1).checked_ceil_div(U128::from(3)), Some((U128::from(0), U128::from(0))));
+        assert_eq!(U128::from(2).checked_ceil_div(U128::from(3)), Some((U128::from(1), U128::from(0))));
+        assert_eq!(U128::from(7).checked_ceil_div(U128::from(0)), None);
+        Ok(())
+    }
+    #[test]
+    fn round_like_raydium_amm() -> Result<(), Error> {
+        // The swap fee of 199 is less than one half and is not taken, so the swap of 199 gives as much as the swap of 200.
+        assert_eq!(Calcaulator::get_coin_amount_from_pc_amount(199, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(53962123_u64));
+        assert_eq!(Calcaulator::get_coin_amount_from_pc_amount(200, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(53962123_u64));
+        assert_eq!(Calcaulator::get_coin_amount_from_pc_amount(201, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(54233289_u64));
+        // The same applies to the required amount of the swap base out.
+        assert_eq!(Calcaulator::get_required_pc_amount_for_coin_amount(135583, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(0_u64));
+        assert_eq!(Calcaulator::get_required_pc_amount_for_coin_amount(135584, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, U128::from(2_u64));
+        Ok(())
+    }
+    #[test]
+    fn get_price_impact_basis_points() -> Result<(), Error> {
+        assert_eq!(Calcaulator::get_price_impact_basis_points(1000000000, 270135373479625, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, 39);
+        assert_eq!(Calcaulator::get_price_impact_basis_points(50000000000, 12694617868675995, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?, 638);
+        Ok(())
+    }
+    #[test]
+    fn get_minimum_amount_out() -> Result<(), Error> {
+        assert_eq!(Calcaulator::get_minimum_amount_out(U128::from(270135373479625_u64), 100)?, 267434019744828);
+        assert_eq!(Calcaulator::get_minimum_amount_out(U128::from(3339143589_u64), 0)?, 3339143589);
+        assert_eq!(Calcaulator::get_minimum_amount_out(U128::from(3339143589_u64), 10000)?, 0);
+        assert!(Calcaulator::get_minimum_amount_out(U128::from(3339143589_u64), 10001).is_err());
+        assert!(Calcaulator::get_minimum_amount_out(U128::from(u64::MAX) + U128::from(1_u64), 0).is_err());
+        Ok(())
+    }
+    #[test]
+    fn swap_base_out_amount_is_enough_for_swap_base_in() -> Result<(), Error> {
+        let mut xorshift = Xorshift(0x2545F4914F6CDD1D);
+        '_a: for _ in 0..ITERATIONS_QUANTITY {
+            let total_pc_amount = xorshift.next_in_range(1, u64::MAX >> 1);
+            let total_coin_amount = xorshift.next_in_range(2, u64::MAX >> 1);
+            let coin_amount = xorshift.next_in_range(1, total_coin_amount - 1);
+            // Raydium's ceiling division rounds a quotient less than one half down to zero.
+            if (total_pc_amount as u128) * (coin_amount as u128) < (total_coin_amount - coin_amount) as u128 {
+                continue '_a;
+            }
+            let required_pc_amount = Calcaulator::get_required_pc_amount_for_coin_amount(coin_amount, total_pc_amount, total_coin_amount)?;
+            if required_pc_amount > U128::from(u64::MAX) {
+                continue '_a;
+            }
+            let received_coin_amount = Calcaulator::get_coin_amount_from_pc_amount(required_pc_amount.low_u64(), total_pc_amount, total_coin_amount)?;
+            assert!(received_coin_amount >= U128::from(coin_amount));
+        }
+        Ok(())
+    }
+    #[test]
+    fn swap_base_in_is_monotonic_and_bounded() -> Result<(), Error> {
+        let mut xorshift = Xorshift(0x9E3779B97F4A7C15);
+        '_a: for _ in 0..ITERATIONS_QUANTITY {
+            let total_pc_amount = xorshift.next_in_range(1, u64::MAX >> 1);
+            let total_coin_amount = xorshift.next_in_range(1, u64::MAX >> 1);
+            let pc_amount = xorshift.next_in_range(0, u64::MAX >> 2);
+            let pc_amount_increment = xorshift.next_in_range(0, u64::MAX >> 2);
+            let coin_amount = Calcaulator::get_coin_amount_from_pc_amount(pc_amount, total_pc_amount, total_coin_amount)?;
+            let coin_amount_ = Calcaulator::get_coin_amount_from_pc_amount(pc_amount + pc_amount_increment, total_pc_amount, total_coin_amount)?;
+            assert!(coin_amount <= coin_amount_);
+            assert!(coin_amount_ < U128::from(total_coin_amount));
+        }
+        Ok(())
+    }
+    #[test]
+    fn price_impact_includes_swap_fee() -> Result<(), Error> {
+        let mut xorshift = Xorshift(0xD1B54A32D192ED03);
+        '_a: for _ in 0..ITERATIONS_QUANTITY {
+            let total_coin_amount = xorshift.next_in_range(1000000, u64::MAX >> 1);
+            let total_pc_amount = xorshift.next_in_range(1000000, u64::MAX >> 1);
+            let coin_amount = xorshift.next_in_range(10000, u64::MAX >> 1);
+            let pc_amount = Calcaulator::get_pc_amount_from_coin_amount(coin_amount, total_pc_amount, total_coin_amount)?;
+            if pc_amount.is_zero() {
+                continue '_a;
+            }
+            let price_impact_basis_points = Calcaulator::get_price_impact_basis_points(coin_amount, pc_amount.low_u64(), total_coin_amount, total_pc_amount)?;
+            assert!(price_impact_basis_points >= Calcaulator::AMM_SWAP_FEE.numerator);
+            assert!(price_impact_basis_points <= WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE);
+        }
+        Ok(())
+    }
+    #[test]
+    fn unpack_raydium_amm_info() -> Result<(), Error> {
+        let mut data = vec![0_u8; RaydiumAmmInfo::LENGTH];
+        data[RaydiumAmmInfo::STATUS_OFFSET..RaydiumAmmInfo::STATUS_OFFSET + 8].copy_from_slice(6_u64.to_le_bytes().as_slice());
+        data[RaydiumAmmInfo::NEED_TAKE_PNL_COIN_OFFSET..RaydiumAmmInfo::NEED_TAKE_PNL_COIN_OFFSET + 8].copy_from_slice(11_u64.to_le_bytes().as_slice());
+        data[RaydiumAmmInfo::NEED_TAKE_PNL_PC_OFFSET..RaydiumAmmInfo::NEED_TAKE_PNL_PC_OFFSET + 8].copy_from_slice(22_u64.to_le_bytes().as_slice());
+        data[RaydiumAmmInfo::TARGET_ORDERS_OFFSET..RaydiumAmmInfo::TARGET_ORDERS_OFFSET + 32].copy_from_slice([7_u8; 32].as_slice());
+        let raydium_amm_info = RaydiumAmmInfo::unpack(data.as_slice())?;
+        assert_eq!(raydium_amm_info.need_take_pnl_coin, 11);
+        assert_eq!(raydium_amm_info.need_take_pnl_pc, 22);
+        assert_eq!(raydium_amm_info.target_orders, Pubkey::new_from_array([7_u8; 32]));
+        assert!(!raydium_amm_info.is_order_book_enabled());
+        assert!(RaydiumAmmInfo::unpack(&data[..RaydiumAmmInfo::LENGTH - 1]).is_err());
+        Ok(())
+    }
+    #[test]
+    fn get_amount_out() -> Result<(), Error> {
+        assert_eq!(
+            Calcaulator::get_amount_out(1000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT, SwapDirection::PcToCoin)?,
+            Calcaulator::get_coin_amount_from_pc_amount(1000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?,
+        );
+        assert_eq!(
+            Calcaulator::get_amount_out(1000000000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT, SwapDirection::PcToCoin.reverse())?,
+            Calcaulator::get_pc_amount_from_coin_amount(1000000000000000, TOTAL_PC_AMOUNT, TOTAL_COIN_AMOUNT)?,
+        );
+        Ok(())
+    }
+}
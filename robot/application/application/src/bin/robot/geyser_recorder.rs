@@ -0,0 +1,294 @@
+use super::error::{
+    Backtrace,
+    Error,
+    ResultConverter,
+};
+use std::fs::File;
+use std::io::{
+    BufReader,
+    BufWriter,
+    ErrorKind,
+    Read,
+    Write,
+};
+use std::sync::mpsc::{
+    Receiver,
+    RecvTimeoutError,
+    SyncSender,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+use yellowstone_grpc_proto::{
+    prelude::SubscribeUpdate,
+    prost::{
+        decode_length_delimiter,
+        Message,
+    },
+};
+// The record file is a sequence of frames. Every frame is the receive timestamp in microseconds since the Unix epoch
+// as the little endian u64, followed by the length-delimited protobuf 'SubscribeUpdate'.
+pub struct GeyserRecorder;
+impl GeyserRecorder {
+    const BUFFER_SIZE: usize = 1024 * 1024;
+    const FLUSHING_INTERVAL_SECONDS: u64 = 1;
+    const CHANNEL_CAPACITY: usize = 100000;
+    // The file is written on its own thread, so the disk never stalls the runtime. The records are handed over with
+    // 'try_send', the ones the full channel does not take are dropped by the sender.
+    pub fn spawn(record_file_path: String) -> Result<SyncSender<GeyserRecord>, Error> {
+        let file = File::create(record_file_path.as_str()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let (
+            record_sender,
+            record_receiver,
+        ) = std::sync::mpsc::sync_channel::<GeyserRecord>(Self::CHANNEL_CAPACITY);
+        let _ = std::thread::Builder::new()
+            .name("geyser_recorder".to_string())
+            .spawn(
+                move || -> () {
+                    if let Err(error) = Self::record(
+                        file,
+                        &record_receiver,
+                    ) {
+                        tracing::error!("{}", &error);
+                    }
+                },
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        Ok(record_sender)
+    }
+    // Returns when all the senders are dropped and the rest of the records is written.
+    fn record<W>(writer: W, record_receiver: &Receiver<GeyserRecord>) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        let mut buf_writer = BufWriter::with_capacity(
+            Self::BUFFER_SIZE,
+            writer,
+        );
+        let flushing_interval = Duration::from_secs(Self::FLUSHING_INTERVAL_SECONDS);
+        let mut flushing_instant = Instant::now() + flushing_interval;
+        let mut buffer = Vec::<u8>::new();
+        '_a: loop {
+            match record_receiver.recv_timeout(flushing_instant.saturating_duration_since(Instant::now())) {
+                Ok(geyser_record) => {
+                    buffer.clear();
+                    buffer.extend_from_slice(geyser_record.receive_timestamp_microseconds.to_le_bytes().as_slice());
+                    geyser_record.subscribe_update.encode_length_delimited(&mut buffer).into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?;
+                    buf_writer.write_all(buffer.as_slice()).into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    break '_a;
+                }
+            }
+            if Instant::now() >= flushing_instant {
+                buf_writer.flush().into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?;
+                flushing_instant = Instant::now() + flushing_interval;
+            }
+        }
+        buf_writer.flush().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+pub struct GeyserRecordReader {
+    buf_reader: BufReader<File>,
+    buffer: Vec<u8>,
+}
+impl GeyserRecordReader {
+    // The varint of the u64 takes 10 bytes at most.
+    const LENGTH_DELIMITER_MAXIMUM_SIZE: usize = 10;
+    pub fn new<'a>(record_file_path: &'a str) -> Result<Self, Error> {
+        Ok(
+            Self {
+                buf_reader: BufReader::new(
+                    File::open(record_file_path).into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?,
+                ),
+                buffer: Vec::new(),
+            },
+        )
+    }
+    // Returns None at the end of the file.
+    pub fn read<'a>(&'a mut self) -> Result<Option<GeyserRecord>, Error> {
+        let mut receive_timestamp_microseconds = [0; 8];
+        if let Err(error) = self.buf_reader.read_exact(receive_timestamp_microseconds.as_mut_slice()) {
+            if error.kind() == ErrorKind::UnexpectedEof {
+                return Ok(None);
+            }
+            return Err(
+                Error::new(
+                    error.into(),
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        self.buffer.clear();
+        '_a: loop {
+            let mut byte = [0; 1];
+            self.buf_reader.read_exact(byte.as_mut_slice()).into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            self.buffer.push(byte[0]);
+            if byte[0] < 0x80 || self.buffer.len() == Self::LENGTH_DELIMITER_MAXIMUM_SIZE {
+                break '_a;
+            }
+        }
+        let length = decode_length_delimiter(self.buffer.as_slice()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        self.buffer.resize(length, 0);
+        self.buf_reader.read_exact(self.buffer.as_mut_slice()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let subscribe_update = SubscribeUpdate::decode(self.buffer.as_slice()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(
+            Some(
+                GeyserRecord {
+                    receive_timestamp_microseconds: u64::from_le_bytes(receive_timestamp_microseconds),
+                    subscribe_update,
+                },
+            ),
+        )
+    }
+}
+pub struct GeyserRecord {
+    pub receive_timestamp_microseconds: u64,
+    pub subscribe_update: SubscribeUpdate,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::error::OptionConverter;
+    use yellowstone_grpc_proto::prelude::{
+        subscribe_update::UpdateOneof,
+        SubscribeUpdatePing,
+        SubscribeUpdateSlot,
+    };
+    #[test]
+    fn write_and_read() -> Result<(), Error> {
+        let record_file_path = std::env::temp_dir().join(format!("geyser_record_{}.bin", std::process::id()));
+        let record_file_path_ = record_file_path.to_string_lossy().to_string();
+        let geyser_record_registry = [
+            GeyserRecord {
+                receive_timestamp_microseconds: 1732807457000000,
+                subscribe_update: SubscribeUpdate {
+                    filters: vec![],
+                    update_oneof: Some(
+                        UpdateOneof::Slot(
+                            SubscribeUpdateSlot {
+                                slot: 302307654,
+                                parent: Some(302307653),
+                                status: 0,
+                            },
+                        ),
+                    ),
+                },
+            },
+            GeyserRecord {
+                receive_timestamp_microseconds: 1732807457400000,
+                subscribe_update: SubscribeUpdate {
+                    filters: vec![],
+                    update_oneof: Some(UpdateOneof::Ping(SubscribeUpdatePing {})),
+                },
+            },
+        ];
+        let (
+            record_sender,
+            record_receiver,
+        ) = std::sync::mpsc::sync_channel::<GeyserRecord>(geyser_record_registry.len());
+        '_a: for geyser_record in geyser_record_registry.iter() {
+            record_sender.try_send(
+                GeyserRecord {
+                    receive_timestamp_microseconds: geyser_record.receive_timestamp_microseconds,
+                    subscribe_update: geyser_record.subscribe_update.clone(),
+                },
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        }
+        drop(record_sender);
+        GeyserRecorder::record(
+            File::create(record_file_path_.as_str()).into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            &record_receiver,
+        )?;
+        let mut geyser_record_reader = GeyserRecordReader::new(record_file_path_.as_str())?;
+        '_a: for geyser_record in geyser_record_registry.iter() {
+            let geyser_record_ = geyser_record_reader.read()?.into_value_does_not_exist(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            assert_eq!(geyser_record_.receive_timestamp_microseconds, geyser_record.receive_timestamp_microseconds);
+            assert_eq!(geyser_record_.subscribe_update, geyser_record.subscribe_update);
+        }
+        assert!(geyser_record_reader.read()?.is_none());
+        std::fs::remove_file(record_file_path.as_path()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+}
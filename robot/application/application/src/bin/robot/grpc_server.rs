@@ -13,8 +13,13 @@ use super::robot::{
     Robot,
     ForAccountProcessing,
     ForAccountTracking1,
+    ForTransactionStatusProcessing,
+    ForTransactionTracking,
 };
+use solana_sdk::signer::Signer;
 use super::environment_configuration::Trade;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use std::sync::Arc;
 use {
     futures::stream::StreamExt,
     yellowstone_grpc_client::GeyserGrpcClient,
@@ -22,56 +27,246 @@ use {
         subscribe_update::UpdateOneof,
         SubscribeRequest,
         SubscribeRequestFilterAccounts,
+        SubscribeRequestFilterBlocksMeta,
+        SubscribeRequestFilterSlots,
         SubscribeRequestFilterTransactions,
+        SubscribeRequestPing,
+        SubscribeUpdate,
     },
 };
+use futures::sink::SinkExt;
+use std::sync::atomic::{
+    AtomicBool,
+    AtomicU64,
+    Ordering,
+};
+use std::time::Duration;
+use tokio::time::Instant;
+use ahash::RandomState;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use super::error::{
+    Common,
+    OptionConverter,
+};
+use tokio::sync::mpsc::Receiver;
+use std::sync::mpsc::SyncSender;
+use tokio::sync::watch::Receiver as WatchReceiver;
+use super::workflow_data::WorkflowData;
+use super::capture::Capture;
+use super::geyser_recorder::GeyserRecord;
+use super::metrics::Metrics;
+use super::slot_clock::SLOT_CLOCK;
+use super::blockhash_cache::BLOCKHASH_CACHE;
+use super::unix_time::UnixTime;
+pub static GEYSER_RECONNECTIONS_QUANTITY: AtomicU64 = AtomicU64::new(0);
+pub static GEYSER_MISSED_SLOTS_QUANTITY: AtomicU64 = AtomicU64::new(0);
+pub static GEYSER_LAST_PROCESSED_SLOT: AtomicU64 = AtomicU64::new(0);
+pub static GEYSER_ENDPOINT_STATISTIC_REGISTRY: OnceLock<Vec<GeyserEndpointStatistic>> = OnceLock::new();
 pub struct GrpcServer;
 impl GrpcServer {
+    const RECONNECTION_INITIAL_DELAY_MILLISECONDS: u64 = 100;
+    const RECONNECTION_MAXIMUM_DELAY_MILLISECONDS: u64 = 10000;
+    const PING_INTERVAL_SECONDS: u64 = 5;
+    // Slot updates arrive several times per second, so no data for this long means the stream is stalled.
+    const STALL_TIMEOUT_SECONDS: u64 = 15;
+    // Two generations of this size are kept, which covers several slots of the subscribed updates.
+    const DEDUPLICATION_GENERATION_CAPACITY: usize = 100000;
+    const STATISTIC_LOGGING_INTERVAL_SECONDS: u64 = 60;
     pub fn run(
         environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: Arc<RpcClient>,
+        subscribe_request_receiver: WatchReceiver<SubscribeRequest>,
+        record_sender: Option<SyncSender<GeyserRecord>>,
         accumulate_trackable_account_sender: Sender<ForAccountTracking1>,
         remove_trackable_account_sender: Sender<ForAccountTracking2>,
         process_account_sender: Sender<ForAccountProcessing>,
+        track_transaction_sender: Sender<ForTransactionTracking>,
+        process_transaction_status_sender: Sender<ForTransactionStatusProcessing>,
     ) -> impl Future<Output = Result<(), Error>> + Send {
-        const CLIENT_NAME: &'static str = "simo_robot";
         async move {
-            let mut subscribe_request_filter_transactions_map = HashMap::<String, SubscribeRequestFilterTransactions>::new();
-            let _ = subscribe_request_filter_transactions_map.insert(
-                CLIENT_NAME.to_string(),
-                SubscribeRequestFilterTransactions {
-                    vote: None,
-                    failed: None,
-                    signature: None,
-                    account_include: vec![],
-                    account_exclude: vec![],
-                    account_required: vec![],
-                },
-            );
-            let mut subscribe_request_filter_accounts_map = HashMap::<String, SubscribeRequestFilterAccounts>::new();
+            GEYSER_ENDPOINT_STATISTIC_REGISTRY.set(
+                environment_configuration.subject.geyser.grpc_url_registry.iter().map(
+                    |grpc_url: _| -> _ {
+                        GeyserEndpointStatistic {
+                            grpc_url: grpc_url.clone(),
+                            is_subscribed: AtomicBool::new(false),
+                            won_races_quantity: AtomicU64::new(0),
+                        }
+                    }
+                )
+                .collect::<Vec<GeyserEndpointStatistic>>(),
+            )
+            .map_err(
+                |_: _| -> _ {
+                    Error::new_(
+                        Common::UnreachableState,
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )
+                }
+            )?;
+            let (
+                deduplicate_sender,
+                mut deduplicate_receiver,
+            ) = tokio::sync::mpsc::channel::<ForDeduplication>(100000);
+            '_a: for (endpoint_index, grpc_url) in environment_configuration.subject.geyser.grpc_url_registry.iter().enumerate() {
+                Spawner::spawn_tokio_non_blocking_task_into_background(
+                    Self::subscribe(
+                        endpoint_index,
+                        grpc_url,
+                        subscribe_request_receiver.clone(),
+                        deduplicate_sender.clone(),
+                    ),
+                );
+            }
+            Self::deduplicate(
+                environment_configuration,
+                rpc_client,
+                &mut deduplicate_receiver,
+                record_sender,
+                accumulate_trackable_account_sender,
+                remove_trackable_account_sender,
+                process_account_sender,
+                track_transaction_sender,
+                process_transaction_status_sender,
+            )
+            .await
+        }
+    }
+    // Transactions are received for the Raydium program only, accounts are received for the tracked pools only. The block
+    // metas feed the blockhash cache. The failed transactions are received for the trading account only, with the logs
+    // the failure is decoded from.
+    pub fn create_subscribe_request(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        trackable_account_pubkey_registry: Vec<String>,
+    ) -> SubscribeRequest {
+        const CLIENT_NAME: &'static str = "simo_robot";
+        const FAILED_TRANSACTION_FILTER_NAME: &'static str = "simo_robot_failed";
+        let mut subscribe_request_filter_transactions_map = HashMap::<String, SubscribeRequestFilterTransactions>::new();
+        let _ = subscribe_request_filter_transactions_map.insert(
+            CLIENT_NAME.to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(false),
+                signature: None,
+                account_include: vec![
+                    WorkflowData::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY.to_string(),
+                ],
+                account_exclude: vec![],
+                account_required: vec![],
+            },
+        );
+        let _ = subscribe_request_filter_transactions_map.insert(
+            FAILED_TRANSACTION_FILTER_NAME.to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(true),
+                signature: None,
+                account_include: vec![],
+                account_exclude: vec![],
+                account_required: vec![
+                    environment_configuration.subject.trading.private_key.pubkey().to_string(),
+                ],
+            },
+        );
+        let mut subscribe_request_filter_accounts_map = HashMap::<String, SubscribeRequestFilterAccounts>::new();
+        // The filter without accounts matches every account of the chain, so it is not sent until something is tracked.
+        if !trackable_account_pubkey_registry.is_empty() {
             let _ = subscribe_request_filter_accounts_map.insert(
                 CLIENT_NAME.to_string(),
                 SubscribeRequestFilterAccounts {
-                    account: vec![],
+                    account: trackable_account_pubkey_registry,
                     owner: vec![],
                     filters: vec![],
                     nonempty_txn_signature: None,
                 }
             );
-            let subscribe_request = SubscribeRequest {
-                accounts: subscribe_request_filter_accounts_map,
-                slots: HashMap::new(),
-                transactions: subscribe_request_filter_transactions_map,
-                transactions_status: HashMap::new(),
-                blocks: HashMap::new(),
-                blocks_meta: HashMap::new(),
-                entry: HashMap::new(),
-                commitment: None,    // TODO TODO Сразу принимать confirmed?
-                accounts_data_slice: vec![],
-                ping: None,
-            };
+        }
+        let mut subscribe_request_filter_transactions_status_map = HashMap::<String, SubscribeRequestFilterTransactions>::new();
+        let _ = subscribe_request_filter_transactions_status_map.insert(
+            CLIENT_NAME.to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: None,
+                signature: None,
+                account_include: vec![
+                    environment_configuration.subject.trading.private_key.pubkey().to_string(),
+                ],
+                account_exclude: vec![],
+                account_required: vec![],
+            },
+        );
+        let mut subscribe_request_filter_slots_map = HashMap::<String, SubscribeRequestFilterSlots>::new();
+        let _ = subscribe_request_filter_slots_map.insert(
+            CLIENT_NAME.to_string(),
+            SubscribeRequestFilterSlots {
+                filter_by_commitment: Some(true),
+            },
+        );
+        let mut subscribe_request_filter_blocks_meta_map = HashMap::<String, SubscribeRequestFilterBlocksMeta>::new();
+        let _ = subscribe_request_filter_blocks_meta_map.insert(
+            CLIENT_NAME.to_string(),
+            SubscribeRequestFilterBlocksMeta {},
+        );
+        SubscribeRequest {
+            accounts: subscribe_request_filter_accounts_map,
+            slots: subscribe_request_filter_slots_map,
+            transactions: subscribe_request_filter_transactions_map,
+            transactions_status: subscribe_request_filter_transactions_status_map,
+            blocks: HashMap::new(),
+            blocks_meta: subscribe_request_filter_blocks_meta_map,
+            entry: HashMap::new(),
+            commitment: None,    // TODO TODO Сразу принимать confirmed?
+            accounts_data_slice: vec![],
+            ping: None,
+        }
+    }
+    fn subscribe(
+        endpoint_index: usize,
+        grpc_url: &'static String,
+        mut subscribe_request_receiver: WatchReceiver<SubscribeRequest>,
+        deduplicate_sender: Sender<ForDeduplication>,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async move {
+            let geyser_endpoint_statistic = GEYSER_ENDPOINT_STATISTIC_REGISTRY
+            .get()
+            .into_unreachable_state(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .get(endpoint_index)
+            .into_unreachable_state(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let metrics = Metrics::get()?;
+            // Zero means the first connection, which is not delayed.
+            let mut reconnection_attempt: u32 = 0;
+            // The slot is kept between the reconnections, so the updates missed while reconnecting are detected.
+            let mut last_processed_slot: u64 = 0;
             'a: loop {
-                let mut client = GeyserGrpcClient::build_from_shared(
-                    environment_configuration.subject.geyser.grpc_url.as_str()
+                geyser_endpoint_statistic.is_subscribed.store(false, Ordering::Relaxed);
+                if reconnection_attempt != 0 {
+                    let reconnection_delay = Self::get_reconnection_delay(reconnection_attempt);
+                    tracing::warn!(
+                        "Reconnecting to the Geyser {} in {} ms, attempt {}.",
+                        grpc_url.as_str(),
+                        reconnection_delay.as_millis(),
+                        reconnection_attempt,
+                    );
+                    tokio::time::sleep(reconnection_delay).await;
+                    let _ = GEYSER_RECONNECTIONS_QUANTITY.fetch_add(1, Ordering::Relaxed);
+                }
+                reconnection_attempt = reconnection_attempt.saturating_add(1);
+                let mut client = match GeyserGrpcClient::build_from_shared(
+                    grpc_url.as_str()
                 )
                 .into_(
                     Backtrace::new(
@@ -86,121 +281,154 @@ impl GrpcServer {
                         line!(),
                         file!(),
                     ),
-                )?;
-                let mut subscribe_update = client.subscribe_once(subscribe_request.clone()).await.into_(
+                ) {
+                    Ok(client_) => client_,
+                    Err(error) => {
+                        tracing::error!("{}", &error);
+                        continue 'a;
+                    }
+                };
+                let subscribe_request = subscribe_request_receiver.borrow_and_update().clone();
+                let (
+                    mut subscribe_request_sink,
+                    subscribe_update_stream,
+                ) = match client.subscribe_with_request(Some(subscribe_request)).await.into_(
                     Backtrace::new(
                         line!(),
                         file!(),
                     ),
-                )?;
+                ) {
+                    Ok(subscription) => subscription,
+                    Err(error) => {
+                        tracing::error!("{}", &error);
+                        continue 'a;
+                    }
+                };
+                geyser_endpoint_statistic.is_subscribed.store(true, Ordering::Relaxed);
+                let mut subscribe_update_stream_ = std::pin::pin!(subscribe_update_stream);
+                let mut ping_interval = tokio::time::interval(Duration::from_secs(Self::PING_INTERVAL_SECONDS));
+                let mut ping_id: i32 = 0;
+                let stall_timeout = Duration::from_secs(Self::STALL_TIMEOUT_SECONDS);
+                let mut stall_sleep = std::pin::pin!(tokio::time::sleep(stall_timeout));
+                let mut is_first_slot_received = false;
                 '_b: loop {
-                    match subscribe_update.next().await {
-                        Some(subscribe_update_) => {
-                            match subscribe_update_ {
-                                Ok(subscribe_update__) => {
-                                    match subscribe_update__.update_oneof {
-                                        Some(update_oneof) => {
-                                            match update_oneof {
-                                                UpdateOneof::Account(subscribe_update_account) => {
-                                                    let process_account_sender_ = process_account_sender.clone();
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async move {
-                                                            Robot::process_account(
-                                                                &subscribe_update_account,
-                                                                &process_account_sender_,
-                                                            )
-                                                            .await
-                                                        },
-                                                    );
-                                                }
-                                                UpdateOneof::Slot(subscribe_update_slot) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: Slot");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                                UpdateOneof::Transaction(subscribe_update_transaction) => {
-                                                    let accumulate_trackable_account_sender_ = accumulate_trackable_account_sender.clone();
-                                                    let remove_trackable_account_sender_ = remove_trackable_account_sender.clone();
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async move {
-                                                            Robot::process_transaction(
-                                                                environment_configuration,
-                                                                &subscribe_update_transaction,
-                                                                &accumulate_trackable_account_sender_,
-                                                                remove_trackable_account_sender_,
-                                                            )
-                                                            .await
-                                                        },
-                                                    );
-                                                }
-                                                UpdateOneof::TransactionStatus(subscribe_update_transaction_status) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: TransactionStatus");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                                UpdateOneof::Entry(subscribe_update_entry) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: Entry");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                                UpdateOneof::BlockMeta(subscribe_update_block_meta) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: BlockMeta");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                                UpdateOneof::Block(subscribe_update_block) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: Block");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                                UpdateOneof::Ping(subscribe_update_ping) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: Ping");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                                UpdateOneof::Pong(subscribe_update_pong) => {
-                                                    Spawner::spawn_tokio_non_blocking_task_into_background(
-                                                        async {
-                                                            tracing::warn!("Unexpected request: Pong");
-                                                            Ok(())
-                                                        }
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            continue 'a; // TODO TODO Нужно ли делать реконнект, если получена ошибка на приеме данных? Или же можно продолжать прием на том же коннетке.
-                                            todo!()
-                                        }
-                                    }
+                    let subscribe_update = tokio::select! {
+                        subscribe_update = subscribe_update_stream_.next() => subscribe_update,
+                        _ = ping_interval.tick() => {
+                            ping_id = ping_id.wrapping_add(1);
+                            // The request with the ping only is answered with the pong and does not replace the subscription filters.
+                            if let Err(error) = subscribe_request_sink.send(
+                                SubscribeRequest {
+                                    ping: Some(
+                                        SubscribeRequestPing {
+                                            id: ping_id,
+                                        },
+                                    ),
+                                    ..Default::default()
+                                },
+                            )
+                            .await {
+                                tracing::warn!("Geyser {} ping has not been sent: {}.", grpc_url.as_str(), &error);
+                                continue 'a;
+                            }
+                            continue '_b;
+                        }
+                        subscribe_request_changing = subscribe_request_receiver.changed() => {
+                            subscribe_request_changing.map_err(
+                                |_: _| -> _ {
+                                    Error::new_(
+                                        Common::UnreachableState,
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    )
                                 }
-                                Err(status) => {
-                                    continue 'a; // TODO TODO Нужно ли делать реконнект, если получена ошибка на приеме данных? Или же можно продолжать прием на том же коннетке.
-                                    todo!()
+                            )?;
+                            // The request with the filters replaces all the filters of the subscription.
+                            let subscribe_request = subscribe_request_receiver.borrow_and_update().clone();
+                            if let Err(error) = subscribe_request_sink.send(subscribe_request).await {
+                                tracing::warn!("Geyser {} subscription has not been updated: {}.", grpc_url.as_str(), &error);
+                                continue 'a;
+                            }
+                            continue '_b;
+                        }
+                        _ = stall_sleep.as_mut() => {
+                            tracing::warn!("Geyser {} stream has stalled for {} seconds.", grpc_url.as_str(), Self::STALL_TIMEOUT_SECONDS);
+                            continue 'a;
+                        }
+                    };
+                    let geyser_receiving_instant = Instant::now();
+                    let update_oneof = match subscribe_update {
+                        Some(Ok(subscribe_update_)) => {
+                            match subscribe_update_.update_oneof {
+                                Some(update_oneof_) => update_oneof_,
+                                None => {
+                                    continue '_b;
                                 }
                             }
                         }
+                        Some(Err(status)) => {
+                            tracing::warn!("Geyser {} stream has failed: {}.", grpc_url.as_str(), &status);
+                            continue 'a;
+                        }
                         None => {
-                            continue 'a; // TODO TODO Нужно ли делать реконнект, если получена ошибка на приеме данных? Или же можно продолжать прием на том же коннетке.
-                            todo!()
+                            tracing::warn!("Geyser {} stream has been closed.", grpc_url.as_str());
+                            continue 'a;
+                        }
+                    };
+                    metrics.increment_geyser_updates(&update_oneof);
+                    match update_oneof {
+                        UpdateOneof::Ping(_) | UpdateOneof::Pong(_) => {
+                            continue '_b;
+                        }
+                        _ => {
+                            stall_sleep.as_mut().reset(Instant::now() + stall_timeout);
+                            // The connection is healthy, so the next reconnection starts from the initial delay.
+                            reconnection_attempt = 1;
+                        }
+                    }
+                    match update_oneof {
+                        UpdateOneof::Slot(subscribe_update_slot) => {
+                            if !is_first_slot_received {
+                                is_first_slot_received = true;
+                                // Some of the slots in the gap may have been skipped by the cluster, so the gap is an upper bound.
+                                if last_processed_slot != 0 && subscribe_update_slot.slot > last_processed_slot + 1 {
+                                    let missed_slots_quantity = subscribe_update_slot.slot - last_processed_slot - 1;
+                                    let _ = GEYSER_MISSED_SLOTS_QUANTITY.fetch_add(missed_slots_quantity, Ordering::Relaxed);
+                                    tracing::warn!(
+                                        "Geyser {} stream has missed up to {} slots: from {} to {}.",
+                                        grpc_url.as_str(),
+                                        missed_slots_quantity,
+                                        last_processed_slot + 1,
+                                        subscribe_update_slot.slot - 1,
+                                    );
+                                }
+                            }
+                            if subscribe_update_slot.slot > last_processed_slot {
+                                last_processed_slot = subscribe_update_slot.slot;
+                                let _ = GEYSER_LAST_PROCESSED_SLOT.fetch_max(last_processed_slot, Ordering::Relaxed);
+                                SLOT_CLOCK.observe(
+                                    last_processed_slot,
+                                    geyser_receiving_instant.into_std(),
+                                )?;
+                            }
+                        }
+                        update_oneof_ => {
+                            deduplicate_sender.send(
+                                ForDeduplication {
+                                    endpoint_index,
+                                    update_oneof: update_oneof_,
+                                    geyser_receiving_instant,
+                                },
+                            )
+                            .await
+                            .into_(
+                                Backtrace::new(
+                                    line!(),
+                                    file!(),
+                                ),
+                            )?;
                         }
                     }
                 }
@@ -208,4 +436,233 @@ impl GrpcServer {
             Ok(())
         }
     }
+    // Every endpoint delivers its own copy of an update, only the first one is processed.
+    fn deduplicate<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: Arc<RpcClient>,
+        deduplicate_receiver: &'a mut Receiver<ForDeduplication>,
+        record_sender: Option<SyncSender<GeyserRecord>>,
+        accumulate_trackable_account_sender: Sender<ForAccountTracking1>,
+        remove_trackable_account_sender: Sender<ForAccountTracking2>,
+        process_account_sender: Sender<ForAccountProcessing>,
+        track_transaction_sender: Sender<ForTransactionTracking>,
+        process_transaction_status_sender: Sender<ForTransactionStatusProcessing>,
+    ) -> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let geyser_endpoint_statistic_registry = GEYSER_ENDPOINT_STATISTIC_REGISTRY.get().into_unreachable_state(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let mut transaction_deduplication_registry = DeduplicationRegistry::<Vec<u8>>::new(Self::DEDUPLICATION_GENERATION_CAPACITY);
+            let mut transaction_status_deduplication_registry = DeduplicationRegistry::<Vec<u8>>::new(Self::DEDUPLICATION_GENERATION_CAPACITY);
+            let mut account_deduplication_registry = DeduplicationRegistry::<(Vec<u8>, u64)>::new(Self::DEDUPLICATION_GENERATION_CAPACITY);
+            let mut block_meta_deduplication_registry = DeduplicationRegistry::<u64>::new(Self::DEDUPLICATION_GENERATION_CAPACITY);
+            let mut statistic_logging_interval = tokio::time::interval(Duration::from_secs(Self::STATISTIC_LOGGING_INTERVAL_SECONDS));
+            '_a: loop {
+                let for_deduplication = tokio::select! {
+                    for_deduplication_ = deduplicate_receiver.recv() => {
+                        for_deduplication_.into_unreachable_state(
+                            Backtrace::new(
+                                line!(),
+                                file!(),
+                            ),
+                        )?
+                    }
+                    _ = statistic_logging_interval.tick() => {
+                        '_b: for geyser_endpoint_statistic in geyser_endpoint_statistic_registry.iter() {
+                            tracing::info!(
+                                "Geyser {} has won {} races.",
+                                geyser_endpoint_statistic.grpc_url.as_str(),
+                                geyser_endpoint_statistic.won_races_quantity.load(Ordering::Relaxed),
+                            );
+                        }
+                        continue '_a;
+                    }
+                };
+                let is_first_copy = match for_deduplication.update_oneof {
+                    UpdateOneof::Account(ref subscribe_update_account) => {
+                        match subscribe_update_account.account {
+                            Some(ref subscribe_update_account_info) => account_deduplication_registry.insert(
+                                (
+                                    subscribe_update_account_info.pubkey.clone(),
+                                    subscribe_update_account_info.write_version,
+                                ),
+                            ),
+                            None => true,
+                        }
+                    }
+                    UpdateOneof::Transaction(ref subscribe_update_transaction) => {
+                        match subscribe_update_transaction.transaction {
+                            Some(ref subscribe_update_transaction_info) => transaction_deduplication_registry.insert(
+                                subscribe_update_transaction_info.signature.clone(),
+                            ),
+                            None => true,
+                        }
+                    }
+                    UpdateOneof::TransactionStatus(ref subscribe_update_transaction_status) => {
+                        transaction_status_deduplication_registry.insert(
+                            subscribe_update_transaction_status.signature.clone(),
+                        )
+                    }
+                    UpdateOneof::BlockMeta(ref subscribe_update_block_meta) => {
+                        block_meta_deduplication_registry.insert(subscribe_update_block_meta.slot)
+                    }
+                    _ => true,
+                };
+                if !is_first_copy {
+                    continue '_a;
+                }
+                if let Some(geyser_endpoint_statistic) = geyser_endpoint_statistic_registry.get(for_deduplication.endpoint_index) {
+                    let _ = geyser_endpoint_statistic.won_races_quantity.fetch_add(1, Ordering::Relaxed);
+                }
+                // The deduplicated stream is recorded, so the replay sees exactly what has been processed. The processing
+                // does not wait for the recorder, the update the recorder can not take is dropped from the record.
+                if let Some(ref record_sender_) = record_sender {
+                    if record_sender_.try_send(
+                        GeyserRecord {
+                            receive_timestamp_microseconds: UnixTime::get_timestamp_microseconds()?,
+                            subscribe_update: SubscribeUpdate {
+                                filters: vec![],
+                                update_oneof: Some(for_deduplication.update_oneof.clone()),
+                            },
+                        },
+                    )
+                    .is_err() {
+                        Metrics::get()?.increment_geyser_dropped_records();
+                    }
+                }
+                let update_oneof = for_deduplication.update_oneof;
+                let geyser_receiving_instant = for_deduplication.geyser_receiving_instant.into_std();
+                match update_oneof {
+                    UpdateOneof::Account(subscribe_update_account) => {
+                        let process_account_sender_ = process_account_sender.clone();
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async move {
+                                Robot::process_account(
+                                    &subscribe_update_account,
+                                    &process_account_sender_,
+                                )
+                                .await
+                            },
+                        );
+                    }
+                    UpdateOneof::Transaction(subscribe_update_transaction) => {
+                        let rpc_client_ = rpc_client.clone();
+                        let accumulate_trackable_account_sender_ = accumulate_trackable_account_sender.clone();
+                        let remove_trackable_account_sender_ = remove_trackable_account_sender.clone();
+                        let track_transaction_sender_ = track_transaction_sender.clone();
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async move {
+                                Robot::process_transaction(
+                                    environment_configuration,
+                                    rpc_client_,
+                                    &subscribe_update_transaction,
+                                    geyser_receiving_instant,
+                                    false,
+                                    &accumulate_trackable_account_sender_,
+                                    remove_trackable_account_sender_,
+                                    track_transaction_sender_,
+                                )
+                                .await
+                            },
+                        );
+                    }
+                    UpdateOneof::TransactionStatus(subscribe_update_transaction_status) => {
+                        let process_transaction_status_sender_ = process_transaction_status_sender.clone();
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async move {
+                                Robot::process_transaction_status(
+                                    &subscribe_update_transaction_status,
+                                    &process_transaction_status_sender_,
+                                )
+                                .await
+                            },
+                        );
+                    }
+                    UpdateOneof::Entry(subscribe_update_entry) => {
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async {
+                                tracing::warn!("Unexpected request: Entry");
+                                Ok(())
+                            }
+                        );
+                    }
+                    UpdateOneof::BlockMeta(subscribe_update_block_meta) => {
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async move {
+                                BLOCKHASH_CACHE.observe(
+                                    &subscribe_update_block_meta,
+                                    geyser_receiving_instant,
+                                )
+                            }
+                        );
+                    }
+                    UpdateOneof::Block(subscribe_update_block) => {
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async {
+                                tracing::warn!("Unexpected request: Block");
+                                Ok(())
+                            }
+                        );
+                    }
+                    UpdateOneof::Slot(_) | UpdateOneof::Ping(_) | UpdateOneof::Pong(_) => {}
+                }
+            }
+            Ok(())
+        }
+    }
+    // The exponential backoff with the jitter in the upper half of the delay, so the reconnecting clients do not synchronize.
+    fn get_reconnection_delay(reconnection_attempt: u32) -> Duration {
+        let maximum_delay_milliseconds = Self::RECONNECTION_INITIAL_DELAY_MILLISECONDS
+            .saturating_mul(1_u64 << reconnection_attempt.saturating_sub(1).min(16))
+            .min(Self::RECONNECTION_MAXIMUM_DELAY_MILLISECONDS);
+        let jitter_milliseconds = RandomState::new().hash_one(reconnection_attempt) % (maximum_delay_milliseconds / 2 + 1);
+        Duration::from_millis(maximum_delay_milliseconds - jitter_milliseconds)
+    }
+}
+pub struct GeyserEndpointStatistic {
+    pub grpc_url: String,
+    pub is_subscribed: AtomicBool,
+    // The number of updates this endpoint has delivered before the others.
+    pub won_races_quantity: AtomicU64,
+}
+struct ForDeduplication {
+    endpoint_index: usize,
+    update_oneof: UpdateOneof,
+    // The moment the update has been received from the endpoint, before the deduplication.
+    geyser_receiving_instant: Instant,
+}
+// Keys are forgotten a generation at a time, so the memory is bounded and a recent key is always remembered.
+struct DeduplicationRegistry<K> {
+    current_generation: HashSet<K, RandomState>,
+    previous_generation: HashSet<K, RandomState>,
+    generation_capacity: usize,
+}
+impl<K> DeduplicationRegistry<K>
+where
+    K: std::hash::Hash + Eq,
+{
+    fn new(generation_capacity: usize) -> Self {
+        Self {
+            current_generation: HashSet::with_capacity_and_hasher(generation_capacity, RandomState::new()),
+            previous_generation: HashSet::with_capacity_and_hasher(generation_capacity, RandomState::new()),
+            generation_capacity,
+        }
+    }
+    // Returns false if the key has already been inserted.
+    fn insert(&mut self, key: K) -> bool {
+        if self.previous_generation.contains(&key) || self.current_generation.contains(&key) {
+            return false;
+        }
+        if self.current_generation.len() >= self.generation_capacity {
+            self.previous_generation = std::mem::replace(
+                &mut self.current_generation,
+                HashSet::with_capacity_and_hasher(self.generation_capacity, RandomState::new()),
+            );
+        }
+        let _ = self.current_generation.insert(key);
+        true
+    }
 }
\ No newline at end of file
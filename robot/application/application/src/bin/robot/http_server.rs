@@ -4,24 +4,42 @@ use std::sync::atomic::{
     AtomicBool,
     Ordering,
 };
-use std::sync::Arc;
+use std::sync::{
+    Arc,
+    Mutex,
+    MutexGuard,
+};
+use std::collections::BTreeMap;
 use super::error::{
+    Common,
     Error,
+    LockResultConverter,
+    OptionConverter,
     ResultConverter,
     Backtrace,
 };
 use super::spawner::Spawner;
-use bytes::{
-    Buf,
-    Bytes,
+use super::capture::Capture;
+use bytes::Bytes;
+use http_body_util::{
+    Full,
+    LengthLimitError,
+    Limited,
 };
-use http_body_util::Full;
 use hyper::{
-    body::Incoming,
+    body::{
+        Body,
+        Incoming,
+    },
     Request,
     Response,
 };
-use super::environment_configuration::Trade;
+use std::error::Error as StdError;
+use super::environment_configuration::{
+    HttpServerAuthentication,
+    HttpServerTls,
+    Trade,
+};
 use hyper::{
     server::conn::http2::Builder,
     Method,
@@ -34,6 +52,7 @@ use matchit::Router;
 use tokio::net::TcpListener;
 use http::{
     header,
+    request::Parts,
     HeaderMap,
     HeaderValue,
     StatusCode,
@@ -41,14 +60,65 @@ use http::{
 };
 use std::convert::From;
 use http_body_util::BodyExt;
+use super::paper_trader::PAPER_TRADING_JOURNAL;
+use super::trade_task_registry::TRADE_TASK_REGISTRY;
+use super::trade_journal::TradeJournal;
+use super::exit_engine::{
+    ExitReason,
+    ExitStrategy,
+    EXIT_STRATEGY_HOLDER,
+};
+use super::workflow_data::{
+    Calculation,
+    WorkflowData,
+};
+use super::grpc_server::{
+    GEYSER_ENDPOINT_STATISTIC_REGISTRY,
+    GEYSER_LAST_PROCESSED_SLOT,
+    GEYSER_MISSED_SLOTS_QUANTITY,
+    GEYSER_RECONNECTIONS_QUANTITY,
+};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::time::Instant;
+use super::audit_log::AuditLog;
+use super::unix_time::UnixTime;
+use super::metrics::Metrics;
+use std::net::SocketAddr;
+use std::fs::File;
+use std::io::BufReader;
+use tokio_rustls::{
+    rustls::{
+        self,
+        pki_types::CertificateDer,
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+use hmac::{
+    Hmac,
+    Mac,
+};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
 pub struct HttpServer;
 impl HttpServer {
+    const TRADE_HISTORY_DEFAULT_LIMIT: usize = 100;
+    const TRADE_HISTORY_MAXIMUM_LIMIT: usize = 1000;
+    const OPENAPI_DESCRIPTION: &'static str = include_str!("./../../../../openapi/openapi.json");
     pub fn run(
         environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        starting_instant: Instant,
         is_graceful_shutdown_command_received: &'static AtomicBool,
+        is_entry_paused: &'static AtomicBool,
     ) -> impl Future<Output = Result<(), Error>> + Send {
         async move {
             let router = Arc::new(Self::create_router()?);
+            let tls_acceptor = match environment_configuration.subject.http_server.tls.as_ref() {
+                Some(http_server_tls) => Some(TlsAcceptor::from(Arc::new(Self::create_tls_server_config(http_server_tls)?))),
+                None => None,
+            };
             let tcp_listener = TcpListener::bind(&environment_configuration.subject.http_server.tcp_socket_address)
             .await
             .into_(
@@ -59,8 +129,8 @@ impl HttpServer {
             )?;
             let builder = Builder::new(TokioExecutor::new());
             'a: loop {
-                let tcp_stream = match tcp_listener.accept().await {
-                    Ok((tcp_stream_, _)) => tcp_stream_,
+                let (tcp_stream, remote_socket_address) = match tcp_listener.accept().await {
+                    Ok(accepted) => accepted,
                     Err(_) => {
                         continue 'a;
                     }
@@ -71,8 +141,13 @@ impl HttpServer {
                         let router__ = router_.clone();
                         return async move {
                             let response = Self::process_request(
-                                environment_configuration,
+                                &environment_configuration.subject.http_server.authentication,
+                                environment_configuration.subject.http_server.maximum_request_body_size,
+                                environment_configuration.subject.trading.is_paper_trading,
+                                starting_instant,
                                 is_graceful_shutdown_command_received,
+                                is_entry_paused,
+                                remote_socket_address,
                                 request,
                                 router__,
                             )
@@ -81,83 +156,702 @@ impl HttpServer {
                         };
                     },
                 );
-                let serve_connection_future = builder.serve_connection(
-                    TokioIo::new(tcp_stream),
-                    service_fn,
-                );
-                Spawner::spawn_tokio_non_blocking_task_into_background(
-                    async move {
-                        serve_connection_future.await.into_(
-                            Backtrace::new(
-                                line!(),
-                                file!(),
-                            ),
-                        )
+                match tls_acceptor.as_ref() {
+                    Some(tls_acceptor_) => {
+                        let tls_acceptor__ = tls_acceptor_.clone();
+                        let builder_ = builder.clone();
+                        // The handshake is done in the connection task, so a slow client does not block the accepting.
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async move {
+                                let tls_stream = tls_acceptor__.accept(tcp_stream).await.into_(
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )?;
+                                builder_.serve_connection(
+                                    TokioIo::new(tls_stream),
+                                    service_fn,
+                                )
+                                .await
+                                .into_(
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )
+                            }
+                        );
                     }
-                );
+                    None => {
+                        let serve_connection_future = builder.serve_connection(
+                            TokioIo::new(tcp_stream),
+                            service_fn,
+                        );
+                        Spawner::spawn_tokio_non_blocking_task_into_background(
+                            async move {
+                                serve_connection_future.await.into_(
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )
+                            }
+                        );
+                    }
+                }
             }
             Ok(())
         }
     }
-    fn create_router() -> Result<Router<()>, Error> {
-        let mut router = Router::<()>::new();
-        router.insert("/robot", ())
+    fn create_router() -> Result<Router<Route>, Error> {
+        let mut router = Router::<Route>::new();
+        let route_registry = [
+            ("/robot", Route::Command),
+            ("/robot/status", Route::Status),
+            ("/robot/positions", Route::Positions),
+            ("/robot/positions/{amm_market_pubkey}/sell", Route::PositionSelling),
+            ("/robot/trades", Route::Trades),
+            ("/robot/entries/pause", Route::EntriesPausing),
+            ("/robot/entries/resume", Route::EntriesResuming),
+            ("/robot/strategy", Route::Strategy),
+            ("/robot/openapi.json", Route::OpenApi),
+            ("/metrics", Route::Metrics),
+        ];
+        '_a: for (path, route) in route_registry.into_iter() {
+            router.insert(path, route)
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        }
+        Ok(router)
+    }
+    fn create_tls_server_config<'a>(http_server_tls: &'a HttpServerTls) -> Result<ServerConfig, Error> {
+        let certificate_registry = rustls_pemfile::certs(
+            &mut BufReader::new(
+                File::open(http_server_tls.certificate_file_path.as_str()).into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?,
+            ),
+        )
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
         .into_(
             Backtrace::new(
                 line!(),
                 file!(),
             ),
         )?;
-        Ok(router)
+        let private_key = rustls_pemfile::private_key(
+            &mut BufReader::new(
+                File::open(http_server_tls.private_key_file_path.as_str()).into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?,
+            ),
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut server_config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .with_no_client_auth()
+        .with_single_cert(
+            certificate_registry,
+            private_key,
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        // The server speaks HTTP/2 only.
+        server_config.alpn_protocols = vec![b"h2".to_vec()];
+        Ok(server_config)
     }
-    fn process_request(
-        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+    fn process_request<B>(
+        http_server_authentication: &'static HttpServerAuthentication,
+        maximum_request_body_size: usize,
+        is_paper_trading: bool,
+        starting_instant: Instant,
         is_graceful_shutdown_command_received: &'static AtomicBool,
-        request: Request<Incoming>,
-        router: Arc<Router<()>>,
-    ) -> impl Future<Output = Response<Full<Bytes>>> + Send {
+        is_entry_paused: &'static AtomicBool,
+        remote_socket_address: SocketAddr,
+        request: Request<B>,
+        router: Arc<Router<Route>>,
+    ) -> impl Future<Output = Response<Full<Bytes>>> + Send
+    where
+        B: Body + Send,
+        B::Data: Send,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
         return async move {
-            let (parts, mut incoming) = request.into_parts();
-            if let Err(_) = router.at(parts.uri.path()) {
-                return ResponseCreator::create_not_found();
+            let (
+                parts,
+                body,
+            ) = match Self::receive(
+                http_server_authentication,
+                maximum_request_body_size,
+                router.as_ref(),
+                remote_socket_address,
+                request,
+            )
+            .await {
+                Ok(received) => received,
+                Err(api_error) => {
+                    return ResponseCreator::create_error(api_error);
+                }
+            };
+            let response = Self::route(
+                is_paper_trading,
+                starting_instant,
+                is_graceful_shutdown_command_received,
+                is_entry_paused,
+                &parts,
+                body.as_ref(),
+                router.as_ref(),
+            );
+            // Every request that is not a read is a control command. The entry is flushed on the blocking thread, and the
+            // response waits for it, so the command is never answered before it has been logged.
+            if parts.method != Method::GET {
+                let method = parts.method.clone();
+                let path = parts.uri.path().to_string();
+                let body_ = body.clone();
+                let status_code = response.status().as_u16();
+                let audit_log_writing_result = Spawner::spawn_tokio_blocking_task_processed(
+                    move || -> Result<(), Error> {
+                        AuditLog::get()?.write(
+                            &remote_socket_address,
+                            method.as_str(),
+                            path.as_str(),
+                            body_.as_ref(),
+                            status_code,
+                        )
+                    },
+                )
+                .await
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                );
+                if let Err(error) = audit_log_writing_result.and_then(
+                    |audit_log_writing_result_: _| -> _ {
+                        audit_log_writing_result_
+                    }
+                ) {
+                    tracing::error!("{}", &error);
+                }
             }
-            if let Method::POST = parts.method {
-                let collected = match incoming.collect().await {
-                    Ok(collected_) => collected_,
-                    Err(_) => {
-                        return ResponseCreator::create_internal_server_error();
+            return response;
+        };
+    }
+    // The headers are authenticated before the body is read, so the client without the credentials can not make the
+    // server receive anything more. The HMAC signature covers the body, so it is verified after the body is read.
+    fn receive<'a, B>(
+        http_server_authentication: &'a HttpServerAuthentication,
+        maximum_request_body_size: usize,
+        router: &'a Router<Route>,
+        remote_socket_address: SocketAddr,
+        request: Request<B>,
+    ) -> impl Future<Output = Result<(Parts, Bytes), ApiError>> + Send + Capture<&'a ()>
+    where
+        B: Body + Send + 'a,
+        B::Data: Send,
+        B::Error: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        return async move {
+            let (parts, incoming) = request.into_parts();
+            let now = UnixTime::get_timestamp().map_err(ApiError::Internal)?;
+            let is_public = match router.at(parts.uri.path()) {
+                Ok(match_) => parts.method == Method::GET && match_.value.is_public(),
+                Err(_) => false,
+            };
+            let signed_request = if is_public {
+                None
+            } else {
+                match Authenticator::authenticate_headers(
+                    http_server_authentication,
+                    &parts,
+                    now,
+                ) {
+                    Ok(signed_request_) => signed_request_,
+                    Err(api_error) => {
+                        Self::warn_about_unauthenticated_request(&parts, &remote_socket_address);
+                        return Err(api_error);
                     }
-                };
-                let command = match serde_json::de::from_slice::<'_, Command>(collected.aggregate().chunk()) {
-                    Ok(command_) => command_,
-                    Err(_) => {
-                        return ResponseCreator::create_bad_request();
+                }
+            };
+            let body = match Limited::new(incoming, maximum_request_body_size).collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(error) => {
+                    if error.is::<LengthLimitError>() {
+                        return Err(ApiError::PayloadTooLarge);
                     }
+                    return Err(ApiError::BadRequest("The request body has not been received."));
+                }
+            };
+            if let Some(signed_request_) = signed_request {
+                if let Err(api_error) = AUTHENTICATOR.verify(&signed_request_, &parts, body.as_ref(), now) {
+                    Self::warn_about_unauthenticated_request(&parts, &remote_socket_address);
+                    return Err(api_error);
+                }
+            }
+            Ok((parts, body))
+        };
+    }
+    fn warn_about_unauthenticated_request<'a>(parts: &'a Parts, remote_socket_address: &'a SocketAddr) {
+        tracing::warn!(
+            "The HTTP request {} {} from {} has not been authenticated.",
+            parts.method.as_str(),
+            parts.uri.path(),
+            remote_socket_address,
+        );
+    }
+    fn route<'a>(
+        is_paper_trading: bool,
+        starting_instant: Instant,
+        is_graceful_shutdown_command_received: &'static AtomicBool,
+        is_entry_paused: &'static AtomicBool,
+        parts: &'a Parts,
+        body: &'a [u8],
+        router: &'a Router<Route>,
+    ) -> Response<Full<Bytes>> {
+        let (route, amm_market_pubkey) = match router.at(parts.uri.path()) {
+            Ok(match_) => {
+                (
+                    *match_.value,
+                    match_.params.get("amm_market_pubkey").map(
+                        |amm_market_pubkey_: _| -> _ {
+                            amm_market_pubkey_.to_string()
+                        }
+                    ),
+                )
+            }
+            Err(_) => {
+                return ResponseCreator::create_error(ApiError::NotFound);
+            }
+        };
+        let result = match (route, &parts.method) {
+            (Route::Command, &Method::POST) => {
+                Self::process_command(
+                    is_graceful_shutdown_command_received,
+                    body,
+                )
+            }
+            (Route::Status, &Method::GET) => {
+                Self::get_status(
+                    is_paper_trading,
+                    starting_instant,
+                    is_graceful_shutdown_command_received,
+                    is_entry_paused,
+                )
+            }
+            (Route::Positions, &Method::GET) => {
+                TRADE_TASK_REGISTRY.get_open_position_registry()
+                .map_err(ApiError::Internal)
+                .and_then(
+                    |open_position_registry: _| -> _ {
+                        ResponseCreator::serialize(&open_position_registry)
+                    }
+                )
+            }
+            (Route::PositionSelling, &Method::POST) => {
+                Self::sell_position(amm_market_pubkey)
+            }
+            (Route::Trades, &Method::GET) => {
+                Self::get_trade_history(parts.uri.query())
+            }
+            (Route::EntriesPausing, &Method::POST) => {
+                is_entry_paused.store(true, Ordering::Relaxed);
+                ResponseCreator::serialize(
+                    &EntriesState {
+                        is_entry_paused: true,
+                    },
+                )
+            }
+            (Route::EntriesResuming, &Method::POST) => {
+                is_entry_paused.store(false, Ordering::Relaxed);
+                ResponseCreator::serialize(
+                    &EntriesState {
+                        is_entry_paused: false,
+                    },
+                )
+            }
+            (Route::Strategy, &Method::GET) => {
+                EXIT_STRATEGY_HOLDER.get()
+                .map_err(ApiError::Internal)
+                .and_then(
+                    |exit_strategy: _| -> _ {
+                        ResponseCreator::serialize(&exit_strategy)
+                    }
+                )
+            }
+            (Route::Strategy, &Method::PUT) => {
+                Self::update_strategy(body)
+            }
+            (Route::OpenApi, &Method::GET) => {
+                Ok(Self::OPENAPI_DESCRIPTION.as_bytes().to_vec())
+            }
+            // The only response that is not JSON.
+            (Route::Metrics, &Method::GET) => {
+                return match Metrics::get().and_then(
+                    |metrics: _| -> _ {
+                        metrics.render()
+                    }
+                ) {
+                    Ok(data) => ResponseCreator::create_text(data),
+                    Err(error) => ResponseCreator::create_error(ApiError::Internal(error)),
+                };
+            }
+            _ => {
+                Err(ApiError::MethodNotAllowed)
+            }
+        };
+        return match result {
+            Ok(data) => ResponseCreator::create_ok(data),
+            Err(api_error) => ResponseCreator::create_error(api_error),
+        };
+    }
+    fn process_command<'a>(
+        is_graceful_shutdown_command_received: &'static AtomicBool,
+        body: &'a [u8],
+    ) -> Result<Vec<u8>, ApiError> {
+        let command = Self::deserialize::<Command>(body)?;
+        match command {
+            Command::GracefulShutdown => {
+                let message = if !is_graceful_shutdown_command_received.load(Ordering::Relaxed) {
+                    is_graceful_shutdown_command_received.store(true, Ordering::Relaxed);
+                    "The process will not create new trading tasks and will end according to the graceful shutdown policy."
+                } else {
+                    "The command has already been received. The process is completing previous traiding tasks according to the graceful shutdown policy."
                 };
-                let data = match command {
-                    Command::GracefulShutdown => {
-                        if !is_graceful_shutdown_command_received.load(Ordering::Relaxed) {
-                            is_graceful_shutdown_command_received.store(true, Ordering::Relaxed);
-                            b"The process will not create new trading tasks and will end after all previous tasks have been completed.".to_vec()
-                        } else {
-                            b"The command has already been received. The process is waiting for previous traiding tasks to complete.".to_vec()
+                ResponseCreator::serialize(
+                    &GracefulShutdownState {
+                        message,
+                        trading_tasks_quantity: TRADE_TASK_REGISTRY.get_trading_tasks_quantity(),
+                        open_positions_quantity: TRADE_TASK_REGISTRY.get_open_positions_quantity().map_err(ApiError::Internal)?,
+                    },
+                )
+            }
+            Command::GetPaperTradingReport => PAPER_TRADING_JOURNAL.create_report().map_err(ApiError::Internal),
+        }
+    }
+    fn get_status(
+        is_paper_trading: bool,
+        starting_instant: Instant,
+        is_graceful_shutdown_command_received: &'static AtomicBool,
+        is_entry_paused: &'static AtomicBool,
+    ) -> Result<Vec<u8>, ApiError> {
+        let geyser_endpoint_state_registry = match GEYSER_ENDPOINT_STATISTIC_REGISTRY.get() {
+            Some(geyser_endpoint_statistic_registry) => {
+                geyser_endpoint_statistic_registry.iter().map(
+                    |geyser_endpoint_statistic: _| -> _ {
+                        GeyserEndpointState {
+                            grpc_url: geyser_endpoint_statistic.grpc_url.as_str(),
+                            is_subscribed: geyser_endpoint_statistic.is_subscribed.load(Ordering::Relaxed),
+                            won_races_quantity: geyser_endpoint_statistic.won_races_quantity.load(Ordering::Relaxed),
+                        }
+                    }
+                )
+                .collect::<Vec<GeyserEndpointState<'_>>>()
+            }
+            None => vec![],
+        };
+        ResponseCreator::serialize(
+            &Status {
+                uptime_seconds: starting_instant.elapsed().as_secs(),
+                is_paper_trading,
+                is_entry_paused: is_entry_paused.load(Ordering::Relaxed),
+                is_graceful_shutdown_command_received: is_graceful_shutdown_command_received.load(Ordering::Relaxed),
+                trading_tasks_quantity: TRADE_TASK_REGISTRY.get_trading_tasks_quantity(),
+                open_positions_quantity: TRADE_TASK_REGISTRY.get_open_positions_quantity().map_err(ApiError::Internal)?,
+                geyser: GeyserState {
+                    is_connected: geyser_endpoint_state_registry.iter().any(
+                        |geyser_endpoint_state: _| -> _ {
+                            geyser_endpoint_state.is_subscribed
+                        }
+                    ),
+                    last_processed_slot: GEYSER_LAST_PROCESSED_SLOT.load(Ordering::Relaxed),
+                    reconnections_quantity: GEYSER_RECONNECTIONS_QUANTITY.load(Ordering::Relaxed),
+                    missed_slots_quantity: GEYSER_MISSED_SLOTS_QUANTITY.load(Ordering::Relaxed),
+                    endpoint_registry: geyser_endpoint_state_registry,
+                },
+            },
+        )
+    }
+    fn sell_position(amm_market_pubkey: Option<String>) -> Result<Vec<u8>, ApiError> {
+        let amm_market_pubkey_ = match amm_market_pubkey {
+            Some(amm_market_pubkey__) => {
+                Pubkey::from_str(amm_market_pubkey__.as_str()).map_err(
+                    |_: _| -> _ {
+                        ApiError::BadRequest("The pool id is not a valid pubkey.")
+                    }
+                )?
+            }
+            None => {
+                return Err(ApiError::NotFound);
+            }
+        };
+        if !TRADE_TASK_REGISTRY.request_exit(
+            &amm_market_pubkey_,
+            ExitReason::ForcedSell,
+        )
+        .map_err(ApiError::Internal)? {
+            return Err(ApiError::Conflict("The position does not exist or its selling has already been requested."));
+        }
+        ResponseCreator::serialize(
+            &PositionSelling {
+                amm_market_pubkey: amm_market_pubkey_.to_string(),
+                is_exit_requested: true,
+            },
+        )
+    }
+    fn get_trade_history<'a>(query: Option<&'a str>) -> Result<Vec<u8>, ApiError> {
+        let mut limit = Self::TRADE_HISTORY_DEFAULT_LIMIT;
+        if let Some(query_) = query {
+            '_a: for query_parameter in query_.split('&') {
+                if let Some(limit_) = query_parameter.strip_prefix("limit=") {
+                    limit = limit_.parse::<usize>().map_err(
+                        |_: _| -> _ {
+                            ApiError::BadRequest("The limit is not a valid number.")
                         }
+                    )?;
+                }
+            }
+        }
+        if limit == 0 || limit > Self::TRADE_HISTORY_MAXIMUM_LIMIT {
+            return Err(ApiError::BadRequest("The limit is out of range."));
+        }
+        let journaled_trade_registry = TradeJournal::get()
+        .and_then(
+            |trade_journal: _| -> _ {
+                trade_journal.load_trade_history(limit)
+            }
+        )
+        .map_err(ApiError::Internal)?;
+        ResponseCreator::serialize(&journaled_trade_registry)
+    }
+    // The new rules are applied to the positions opened after the update.
+    fn update_strategy<'a>(body: &'a [u8]) -> Result<Vec<u8>, ApiError> {
+        let exit_strategy = Self::deserialize::<ExitStrategy>(body)?;
+        if exit_strategy.trailing_stop_basis_points > WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE {
+            return Err(ApiError::BadRequest("The trailing stop is more than the whole amount."));
+        }
+        EXIT_STRATEGY_HOLDER.set(exit_strategy).map_err(ApiError::Internal)?;
+        ResponseCreator::serialize(&exit_strategy)
+    }
+    fn deserialize<'a, T>(body: &'a [u8]) -> Result<T, ApiError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::de::from_slice::<'_, T>(body).map_err(
+            |_: _| -> _ {
+                ApiError::BadRequest("The request body is not valid.")
+            }
+        )
+    }
+}
+static AUTHENTICATOR: Authenticator = Authenticator::new();
+// The signed request is accepted once. Its nonce is kept while the timestamp is in the window, after that the timestamp
+// rejects the request itself.
+struct Authenticator {
+    // The request timestamp by the nonce.
+    used_nonce_registry: Mutex<BTreeMap<String, u64>>,
+}
+impl Authenticator {
+    const HEADER_NAME_TIMESTAMP: &'static str = "x-robot-timestamp";
+    const HEADER_NAME_NONCE: &'static str = "x-robot-nonce";
+    const HEADER_NAME_SIGNATURE: &'static str = "x-robot-signature";
+    const BEARER_PREFIX: &'static [u8] = b"Bearer ";
+    // The signed request is accepted for this long around its timestamp, so the captured one can not be replayed later.
+    const TIMESTAMP_TOLERANCE_SECONDS: u64 = 30;
+    const NONCE_MAXIMUM_LENGTH: usize = 64;
+    const fn new() -> Self {
+        Self {
+            used_nonce_registry: Mutex::new(BTreeMap::new()),
+        }
+    }
+    // The 'now' is the Unix time in seconds. The bearer token is checked completely, the signed request is returned to be
+    // verified with the body.
+    fn authenticate_headers<'a>(http_server_authentication: &'a HttpServerAuthentication, parts: &'a Parts, now: u64) -> Result<Option<SignedRequest<'a>>, ApiError> {
+        match http_server_authentication {
+            HttpServerAuthentication::Bearer {
+                token,
+            } => {
+                let token_ = parts
+                .headers
+                .get(header::AUTHORIZATION)
+                .and_then(
+                    |header_value: _| -> _ {
+                        header_value.as_bytes().strip_prefix(Self::BEARER_PREFIX)
+                    }
+                )
+                .ok_or(ApiError::Unauthorized("The bearer token is missing."))?;
+                if !bool::from(token_.ct_eq(token.as_bytes())) {
+                    return Err(ApiError::Unauthorized("The bearer token is not valid."));
+                }
+                Ok(None)
+            }
+            HttpServerAuthentication::Hmac {
+                secret,
+            } => {
+                let timestamp = parts
+                .headers
+                .get(Self::HEADER_NAME_TIMESTAMP)
+                .and_then(
+                    |header_value: _| -> _ {
+                        header_value.to_str().ok()
+                    }
+                )
+                .ok_or(ApiError::Unauthorized("The timestamp is missing."))?;
+                let timestamp_ = match timestamp.parse::<u64>() {
+                    Ok(timestamp__) if now.abs_diff(timestamp__) <= Self::TIMESTAMP_TOLERANCE_SECONDS => timestamp__,
+                    _ => {
+                        return Err(ApiError::Unauthorized("The timestamp is not valid."));
                     }
                 };
-                return ResponseCreator::create_ok(data);
+                let nonce = parts
+                .headers
+                .get(Self::HEADER_NAME_NONCE)
+                .and_then(
+                    |header_value: _| -> _ {
+                        header_value.to_str().ok()
+                    }
+                )
+                .ok_or(ApiError::Unauthorized("The nonce is missing."))?;
+                if nonce.is_empty() || nonce.len() > Self::NONCE_MAXIMUM_LENGTH {
+                    return Err(ApiError::Unauthorized("The nonce is not valid."));
+                }
+                let signature = parts
+                .headers
+                .get(Self::HEADER_NAME_SIGNATURE)
+                .and_then(
+                    |header_value: _| -> _ {
+                        hex::decode(header_value.as_bytes()).ok()
+                    }
+                )
+                .ok_or(ApiError::Unauthorized("The signature is missing."))?;
+                Ok(
+                    Some(
+                        SignedRequest {
+                            secret: secret.as_slice(),
+                            timestamp,
+                            timestamp_,
+                            nonce,
+                            signature,
+                        },
+                    ),
+                )
+            }
+        }
+    }
+    // The signature is checked first, so only the nonces of the authentic requests are kept.
+    fn verify<'a>(&'a self, signed_request: &'a SignedRequest<'a>, parts: &'a Parts, body: &'a [u8], now: u64) -> Result<(), ApiError> {
+        signed_request.verify(parts, body)?;
+        let mut used_nonce_registry = self.lock().map_err(ApiError::Internal)?;
+        used_nonce_registry.retain(
+            |_: _, timestamp: _| -> _ {
+                now.abs_diff(*timestamp) <= Self::TIMESTAMP_TOLERANCE_SECONDS
             }
-            return ResponseCreator::create_not_found();
+        );
+        if used_nonce_registry.contains_key(signed_request.nonce) {
+            return Err(ApiError::Unauthorized("The nonce has already been used."));
+        }
+        let _ = used_nonce_registry.insert(
+            signed_request.nonce.to_string(),
+            signed_request.timestamp_,
+        );
+        Ok(())
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, BTreeMap<String, u64>>, Error> {
+        self.used_nonce_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct SignedRequest<'a> {
+    secret: &'a [u8],
+    // As it has been signed.
+    timestamp: &'a str,
+    timestamp_: u64,
+    nonce: &'a str,
+    signature: Vec<u8>,
+}
+impl<'a> SignedRequest<'a> {
+    fn verify<'b>(&'b self, parts: &'b Parts, body: &'b [u8]) -> Result<(), ApiError> {
+        // The HMAC accepts the key of any length.
+        let mut hmac = Hmac::<Sha256>::new_from_slice(self.secret).map_err(
+            |_: _| -> _ {
+                ApiError::Internal(
+                    Error::new_(
+                        Common::UnreachableState,
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    ),
+                )
+            }
+        )?;
+        let path_and_query = match parts.uri.path_and_query() {
+            Some(path_and_query_) => path_and_query_.as_str(),
+            None => parts.uri.path(),
         };
+        hmac.update(parts.method.as_str().as_bytes());
+        hmac.update(b"\n");
+        hmac.update(path_and_query.as_bytes());
+        hmac.update(b"\n");
+        hmac.update(self.timestamp.as_bytes());
+        hmac.update(b"\n");
+        hmac.update(self.nonce.as_bytes());
+        hmac.update(b"\n");
+        hmac.update(body);
+        // The comparison is constant-time.
+        hmac.verify_slice(self.signature.as_slice()).map_err(
+            |_: _| -> _ {
+                ApiError::Unauthorized("The signature is not valid.")
+            }
+        )
     }
 }
 struct ResponseCreator;
 impl ResponseCreator {
-    const HEADER_VALUE_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("application/octet-stream");
-    fn create(status_code: StatusCode, data: Option<Vec<u8>>) -> Response<Full<Bytes>> {
+    const HEADER_VALUE_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("application/json");
+    // The Prometheus text exposition format.
+    const HEADER_VALUE_CONTENT_TYPE_TEXT: HeaderValue = HeaderValue::from_static("text/plain; version=0.0.4");
+    fn create(status_code: StatusCode, header_value_content_type: HeaderValue, data: Option<Vec<u8>>) -> Response<Full<Bytes>> {
         let mut header_map = HeaderMap::new();
         header_map.append(
             header::CONTENT_TYPE,
-            Self::HEADER_VALUE_CONTENT_TYPE,
+            header_value_content_type,
         );
         let mut parts = Response::new(()).into_parts().0;
         parts.status = status_code;
@@ -172,32 +866,532 @@ impl ResponseCreator {
             Full::new(bytes),
         );
     }
-    fn create_bad_request() -> Response<Full<Bytes>> {
+    fn create_ok(data: Vec<u8>) -> Response<Full<Bytes>> {
         return Self::create(
-            StatusCode::BAD_REQUEST,
-            Option::None,
+            StatusCode::OK,
+            Self::HEADER_VALUE_CONTENT_TYPE,
+            Option::Some(data),
         );
     }
-    fn create_not_found() -> Response<Full<Bytes>> {
+    fn create_text(data: Vec<u8>) -> Response<Full<Bytes>> {
         return Self::create(
-            StatusCode::NOT_FOUND,
-            Option::None,
+            StatusCode::OK,
+            Self::HEADER_VALUE_CONTENT_TYPE_TEXT,
+            Option::Some(data),
         );
     }
-    fn create_internal_server_error() -> Response<Full<Bytes>> {
+    // Every error is answered with the same JSON object, so the client parses only the 'code' to branch on.
+    fn create_error(api_error: ApiError) -> Response<Full<Bytes>> {
+        let (status_code, code, message) = match api_error {
+            ApiError::BadRequest(message_) => (StatusCode::BAD_REQUEST, "bad_request", message_),
+            ApiError::Unauthorized(message_) => (StatusCode::UNAUTHORIZED, "unauthorized", message_),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not_found", "The resource does not exist."),
+            ApiError::MethodNotAllowed => (StatusCode::METHOD_NOT_ALLOWED, "method_not_allowed", "The method is not allowed for the resource."),
+            ApiError::Conflict(message_) => (StatusCode::CONFLICT, "conflict", message_),
+            ApiError::PayloadTooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", "The request body is too large."),
+            ApiError::Internal(error) => {
+                tracing::error!("{}", &error);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal", "The request has not been processed.")
+            }
+        };
+        let data = serde_json::to_vec(
+            &ErrorResponse {
+                error: ErrorBody {
+                    code,
+                    message,
+                },
+            },
+        )
+        .ok();
         return Self::create(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Option::None,
+            status_code,
+            Self::HEADER_VALUE_CONTENT_TYPE,
+            data,
         );
     }
-    fn create_ok(data: Vec<u8>) -> Response<Full<Bytes>> {
-        return Self::create(
-            StatusCode::OK,
-            Option::Some(data),
-        );
+    fn serialize<'a, T>(value: &'a T) -> Result<Vec<u8>, ApiError>
+    where
+        T: Serialize,
+    {
+        serde_json::to_vec(value).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+        .map_err(ApiError::Internal)
+    }
+}
+#[derive(Clone, Copy)]
+enum Route {
+    Command,
+    Status,
+    Positions,
+    PositionSelling,
+    Trades,
+    EntriesPausing,
+    EntriesResuming,
+    Strategy,
+    OpenApi,
+    Metrics,
+}
+impl Route {
+    // The Prometheus scrape job can neither sign the request nor send the nonce, and the metrics do not control anything.
+    fn is_public(self) -> bool {
+        matches!(self, Self::Metrics)
     }
 }
+enum ApiError {
+    BadRequest(&'static str),
+    Unauthorized(&'static str),
+    NotFound,
+    MethodNotAllowed,
+    Conflict(&'static str),
+    PayloadTooLarge,
+    Internal(Error),
+}
 #[derive(serde::Deserialize)]
 enum Command {
     GracefulShutdown,
-}
\ No newline at end of file
+    GetPaperTradingReport,
+}
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+#[derive(Serialize)]
+struct ErrorBody {
+    code: &'static str,
+    message: &'static str,
+}
+#[derive(Serialize)]
+struct Status<'a> {
+    uptime_seconds: u64,
+    is_paper_trading: bool,
+    is_entry_paused: bool,
+    is_graceful_shutdown_command_received: bool,
+    trading_tasks_quantity: usize,
+    open_positions_quantity: usize,
+    geyser: GeyserState<'a>,
+}
+#[derive(Serialize)]
+struct GeyserState<'a> {
+    // At least one of the endpoints is subscribed.
+    is_connected: bool,
+    last_processed_slot: u64,
+    reconnections_quantity: u64,
+    missed_slots_quantity: u64,
+    endpoint_registry: Vec<GeyserEndpointState<'a>>,
+}
+#[derive(Serialize)]
+struct GeyserEndpointState<'a> {
+    grpc_url: &'a str,
+    is_subscribed: bool,
+    won_races_quantity: u64,
+}
+#[derive(Serialize)]
+struct EntriesState {
+    is_entry_paused: bool,
+}
+#[derive(Serialize)]
+struct PositionSelling {
+    amm_market_pubkey: String,
+    is_exit_requested: bool,
+}
+#[derive(Serialize)]
+struct GracefulShutdownState {
+    message: &'static str,
+    trading_tasks_quantity: usize,
+    open_positions_quantity: usize,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::pin::Pin;
+    use std::task::{
+        Context,
+        Poll,
+    };
+    use hyper::body::Frame;
+    use std::convert::Infallible;
+    const NOW: u64 = 1732807457;
+    fn authenticate<'a>(
+        authenticator: &'a Authenticator,
+        http_server_authentication: &'a HttpServerAuthentication,
+        parts: &'a Parts,
+        body: &'a [u8],
+        now: u64,
+    ) -> Result<(), ApiError> {
+        if let Some(signed_request) = Authenticator::authenticate_headers(http_server_authentication, parts, now)? {
+            authenticator.verify(&signed_request, parts, body, now)?;
+        }
+        Ok(())
+    }
+    // The 'method_and_path_and_query' is the first two lines of the signed string.
+    fn create_signature<'a>(secret: &'a [u8], method_and_path_and_query: &'a str, timestamp: &'a str, nonce: &'a str, body: &'a [u8]) -> Result<String, Error> {
+        let mut hmac = Hmac::<Sha256>::new_from_slice(secret).ok().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        hmac.update(format!("{}\n{}\n{}\n", method_and_path_and_query, timestamp, nonce).as_bytes());
+        hmac.update(body);
+        Ok(hex::encode(hmac.finalize().into_bytes()))
+    }
+    // Only tells whether the body has been read.
+    struct ObservedBody {
+        is_read: &'static AtomicBool,
+    }
+    impl Body for ObservedBody {
+        type Data = Bytes;
+        type Error = Infallible;
+        fn poll_frame(self: Pin<&mut Self>, _context: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            self.is_read.store(true, Ordering::Relaxed);
+            Poll::Ready(None)
+        }
+    }
+    #[test]
+    fn authenticate_bearer_token() -> Result<(), Error> {
+        let http_server_authentication = HttpServerAuthentication::Bearer {
+            token: "2f8c1e0a9b7d".to_string(),
+        };
+        let mut parts = Request::builder()
+            .method(Method::POST)
+            .uri("/robot")
+            .header(header::AUTHORIZATION, "Bearer 2f8c1e0a9b7d")
+            .body(())
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .into_parts()
+            .0;
+        assert!(Authenticator::authenticate_headers(&http_server_authentication, &parts, NOW).is_ok());
+        // The token of the same length and the prefix of the token are rejected alike.
+        let _ = parts.headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer 2f8c1e0a9b7e"));
+        assert!(matches!(Authenticator::authenticate_headers(&http_server_authentication, &parts, NOW), Err(ApiError::Unauthorized(_))));
+        let _ = parts.headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer 2f8c1e"));
+        assert!(matches!(Authenticator::authenticate_headers(&http_server_authentication, &parts, NOW), Err(ApiError::Unauthorized(_))));
+        let _ = parts.headers.insert(header::AUTHORIZATION, HeaderValue::from_static("2f8c1e0a9b7d"));
+        assert!(matches!(Authenticator::authenticate_headers(&http_server_authentication, &parts, NOW), Err(ApiError::Unauthorized(_))));
+        let _ = parts.headers.remove(header::AUTHORIZATION);
+        assert!(matches!(Authenticator::authenticate_headers(&http_server_authentication, &parts, NOW), Err(ApiError::Unauthorized(_))));
+        Ok(())
+    }
+    #[test]
+    fn authenticate_hmac_signature() -> Result<(), Error> {
+        let secret = b"b1d4c7e2a9f03865".to_vec();
+        let http_server_authentication = HttpServerAuthentication::Hmac {
+            secret: secret.clone(),
+        };
+        let authenticator = Authenticator::new();
+        let body = br#"{"trailing_stop_basis_points":500}"#;
+        let timestamp = NOW.to_string();
+        let signature = create_signature(secret.as_slice(), "PUT\n/robot/strategy?dry_run=true", timestamp.as_str(), "6f1d0c2b", body.as_slice())?;
+        let mut parts = Request::builder()
+            .method(Method::PUT)
+            .uri("/robot/strategy?dry_run=true")
+            .header(Authenticator::HEADER_NAME_TIMESTAMP, timestamp.as_str())
+            .header(Authenticator::HEADER_NAME_NONCE, "6f1d0c2b")
+            .header(Authenticator::HEADER_NAME_SIGNATURE, signature.as_str())
+            .body(())
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .into_parts()
+            .0;
+        // The signature covers the body, the method and the query.
+        assert!(matches!(authenticate(&authenticator, &http_server_authentication, &parts, b"{}", NOW), Err(ApiError::Unauthorized(_))));
+        let mut parts_ = Request::builder()
+            .method(Method::POST)
+            .uri("/robot/strategy")
+            .body(())
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .into_parts()
+            .0;
+        parts_.headers = parts.headers.clone();
+        assert!(matches!(authenticate(&authenticator, &http_server_authentication, &parts_, body.as_slice(), NOW), Err(ApiError::Unauthorized(_))));
+        // The signature covers the nonce.
+        let _ = parts_.headers.insert(Authenticator::HEADER_NAME_NONCE, HeaderValue::from_static("6f1d0c2c"));
+        assert!(matches!(authenticate(&authenticator, &http_server_authentication, &parts_, body.as_slice(), NOW), Err(ApiError::Unauthorized(_))));
+        assert!(authenticate(&authenticator, &http_server_authentication, &parts, body.as_slice(), NOW).is_ok());
+        let _ = parts.headers.insert(Authenticator::HEADER_NAME_SIGNATURE, HeaderValue::from_static("not hex"));
+        assert!(matches!(authenticate(&authenticator, &http_server_authentication, &parts, body.as_slice(), NOW), Err(ApiError::Unauthorized(_))));
+        Ok(())
+    }
+    #[test]
+    fn authenticate_hmac_timestamp_window() -> Result<(), Error> {
+        let secret = b"b1d4c7e2a9f03865".to_vec();
+        let http_server_authentication = HttpServerAuthentication::Hmac {
+            secret: secret.clone(),
+        };
+        let authenticator = Authenticator::new();
+        // The signature is valid for its timestamp, so only the window decides.
+        '_a: for (timestamp, is_accepted) in [
+            (NOW - 30, true),
+            (NOW + 30, true),
+            (NOW - 31, false),
+            (NOW + 31, false),
+        ] {
+            let timestamp_ = timestamp.to_string();
+            let parts = Request::builder()
+                .method(Method::POST)
+                .uri("/robot/entries/pause")
+                .header(Authenticator::HEADER_NAME_TIMESTAMP, timestamp_.as_str())
+                .header(Authenticator::HEADER_NAME_NONCE, timestamp_.as_str())
+                .header(
+                    Authenticator::HEADER_NAME_SIGNATURE,
+                    create_signature(secret.as_slice(), "POST\n/robot/entries/pause", timestamp_.as_str(), timestamp_.as_str(), b"")?.as_str(),
+                )
+                .body(())
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?
+                .into_parts()
+                .0;
+            assert_eq!(authenticate(&authenticator, &http_server_authentication, &parts, b"", NOW).is_ok(), is_accepted);
+        }
+        Ok(())
+    }
+    #[test]
+    fn reject_replayed_hmac_request() -> Result<(), Error> {
+        let secret = b"b1d4c7e2a9f03865".to_vec();
+        let http_server_authentication = HttpServerAuthentication::Hmac {
+            secret: secret.clone(),
+        };
+        let authenticator = Authenticator::new();
+        let create_parts = |nonce: &'static str, timestamp: u64| -> Result<Parts, Error> {
+            let timestamp = timestamp.to_string();
+            Ok(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/robot/entries/pause")
+                    .header(Authenticator::HEADER_NAME_TIMESTAMP, timestamp.as_str())
+                    .header(Authenticator::HEADER_NAME_NONCE, nonce)
+                    .header(
+                        Authenticator::HEADER_NAME_SIGNATURE,
+                        create_signature(secret.as_slice(), "POST\n/robot/entries/pause", timestamp.as_str(), nonce, b"")?.as_str(),
+                    )
+                    .body(())
+                    .into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?
+                    .into_parts()
+                    .0,
+            )
+        };
+        let parts = create_parts("3a9e5b71", NOW)?;
+        assert!(authenticate(&authenticator, &http_server_authentication, &parts, b"", NOW).is_ok());
+        // The same signed request is accepted once within the window.
+        assert!(matches!(authenticate(&authenticator, &http_server_authentication, &parts, b"", NOW + 30), Err(ApiError::Unauthorized(_))));
+        assert!(authenticate(&authenticator, &http_server_authentication, &create_parts("3a9e5b72", NOW)?, b"", NOW).is_ok());
+        // The nonces are forgotten with the window, the request is rejected by its timestamp instead.
+        assert!(matches!(authenticate(&authenticator, &http_server_authentication, &parts, b"", NOW + 31), Err(ApiError::Unauthorized(_))));
+        assert!(authenticate(&authenticator, &http_server_authentication, &create_parts("3a9e5b71", NOW + 31)?, b"", NOW + 31).is_ok());
+        assert_eq!(authenticator.lock()?.len(), 1);
+        // The missing and the too long nonce are rejected before the signature.
+        let mut parts_ = create_parts("3a9e5b73", NOW)?;
+        let _ = parts_.headers.remove(Authenticator::HEADER_NAME_NONCE);
+        assert!(matches!(Authenticator::authenticate_headers(&http_server_authentication, &parts_, NOW), Err(ApiError::Unauthorized(_))));
+        let _ = parts_.headers.insert(Authenticator::HEADER_NAME_NONCE, HeaderValue::from_str("a".repeat(65).as_str()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?);
+        assert!(matches!(Authenticator::authenticate_headers(&http_server_authentication, &parts_, NOW), Err(ApiError::Unauthorized(_))));
+        Ok(())
+    }
+    #[tokio::test]
+    async fn limit_request_body() -> Result<(), Error> {
+        let http_server_authentication = HttpServerAuthentication::Bearer {
+            token: "2f8c1e0a9b7d".to_string(),
+        };
+        let router = HttpServer::create_router()?;
+        let remote_socket_address = SocketAddr::from(([127, 0, 0, 1], 50000));
+        let create_request = |body: &'static [u8]| -> _ {
+            Request::builder()
+                .method(Method::PUT)
+                .uri("/robot/strategy")
+                .header(header::AUTHORIZATION, "Bearer 2f8c1e0a9b7d")
+                .body(Full::new(Bytes::from_static(body)))
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+        };
+        let body = br#"{"trailing_stop_basis_points":500}"#;
+        match HttpServer::receive(&http_server_authentication, body.len(), &router, remote_socket_address, create_request(body)?).await {
+            Ok((_, received_body)) => assert_eq!(received_body.as_ref(), body.as_slice()),
+            Err(_) => panic!(),
+        }
+        assert!(
+            matches!(
+                HttpServer::receive(&http_server_authentication, body.len() - 1, &router, remote_socket_address, create_request(body)?).await,
+                Err(ApiError::PayloadTooLarge),
+            ),
+        );
+        // The body of the request without the valid token is not read at all.
+        static IS_READ: AtomicBool = AtomicBool::new(false);
+        let request = Request::builder()
+            .method(Method::PUT)
+            .uri("/robot/strategy")
+            .header(header::AUTHORIZATION, "Bearer 2f8c1e0a9b7e")
+            .body(
+                ObservedBody {
+                    is_read: &IS_READ,
+                },
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        assert!(
+            matches!(
+                HttpServer::receive(&http_server_authentication, body.len(), &router, remote_socket_address, request).await,
+                Err(ApiError::Unauthorized(_)),
+            ),
+        );
+        assert!(!IS_READ.load(Ordering::Relaxed));
+        Ok(())
+    }    #[tokio::test]
+    async fn scrape_metrics_without_credentials() -> Result<(), Error> {
+        let http_server_authentication = HttpServerAuthentication::Hmac {
+            secret: b"b1d4c7e2a9f03865".to_vec(),
+        };
+        let router = HttpServer::create_router()?;
+        let remote_socket_address = SocketAddr::from(([127, 0, 0, 1], 50000));
+        let create_request = |method: Method, path: &'static str| -> _ {
+            Request::builder()
+                .method(method)
+                .uri(path)
+                .body(Full::new(Bytes::new()))
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+        };
+        assert!(HttpServer::receive(&http_server_authentication, 16, &router, remote_socket_address, create_request(Method::GET, "/metrics")?).await.is_ok());
+        // Only the reading of the metrics is public.
+        assert!(
+            matches!(
+                HttpServer::receive(&http_server_authentication, 16, &router, remote_socket_address, create_request(Method::POST, "/metrics")?).await,
+                Err(ApiError::Unauthorized(_)),
+            ),
+        );
+        assert!(
+            matches!(
+                HttpServer::receive(&http_server_authentication, 16, &router, remote_socket_address, create_request(Method::GET, "/robot/status")?).await,
+                Err(ApiError::Unauthorized(_)),
+            ),
+        );
+        Ok(())
+    }    #[tokio::test]
+    async fn reject_unauthenticated_request_before_handler() -> Result<(), Error> {
+        static HTTP_SERVER_AUTHENTICATION: HttpServerAuthentication = HttpServerAuthentication::Hmac {
+            secret: Vec::new(),
+        };
+        static IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED: AtomicBool = AtomicBool::new(false);
+        static IS_ENTRY_PAUSED: AtomicBool = AtomicBool::new(false);
+        let audit_log_file_path = std::env::temp_dir().join(format!("audit_log_{}.log", std::process::id()));
+        // The file of the earlier run is appended to otherwise.
+        let _ = std::fs::remove_file(audit_log_file_path.as_path());
+        AuditLog::initialize(audit_log_file_path.to_string_lossy().as_ref())?;
+        let router = Arc::new(HttpServer::create_router()?);
+        let remote_socket_address = SocketAddr::from(([127, 0, 0, 1], 50000));
+        let process_request = |request: Request<Full<Bytes>>| -> _ {
+            HttpServer::process_request(
+                &HTTP_SERVER_AUTHENTICATION,
+                16384,
+                false,
+                Instant::now(),
+                &IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED,
+                &IS_ENTRY_PAUSED,
+                remote_socket_address,
+                request,
+                router.clone(),
+            )
+        };
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/robot/entries/pause")
+            .body(Full::new(Bytes::new()))
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        assert_eq!(process_request(request).await.status(), StatusCode::UNAUTHORIZED);
+        // Neither the handler has paused the entries nor the audit entry has been written.
+        assert!(!IS_ENTRY_PAUSED.load(Ordering::Relaxed));
+        assert!(
+            std::fs::read(audit_log_file_path.as_path())
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?
+                .is_empty(),
+        );
+        // The same request with the valid signature goes through both.
+        let timestamp = UnixTime::get_timestamp()?.to_string();
+        let request_ = Request::builder()
+            .method(Method::POST)
+            .uri("/robot/entries/pause")
+            .header(Authenticator::HEADER_NAME_TIMESTAMP, timestamp.as_str())
+            .header(Authenticator::HEADER_NAME_NONCE, "c4e8a1f0")
+            .header(
+                Authenticator::HEADER_NAME_SIGNATURE,
+                create_signature(b"", "POST\n/robot/entries/pause", timestamp.as_str(), "c4e8a1f0", b"")?.as_str(),
+            )
+            .body(Full::new(Bytes::new()))
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        assert_eq!(process_request(request_).await.status(), StatusCode::OK);
+        assert!(IS_ENTRY_PAUSED.load(Ordering::Relaxed));
+        let audit_log = std::fs::read_to_string(audit_log_file_path.as_path()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        std::fs::remove_file(audit_log_file_path.as_path()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        assert_eq!(audit_log.lines().count(), 1);
+        assert!(audit_log.contains("/robot/entries/pause"));
+        Ok(())
+    }
+}
@@ -1,16 +1,37 @@
+mod account_mailbox;
+mod audit_log;
+mod blockhash_cache;
 mod capture;
+mod clock;
 mod command_processor;
+mod entry_scheduler;
 mod environment_configuration;
 mod error;
+mod exit_engine;
 mod grpc_server;
 mod http_server;
 mod extern_source;
+mod geyser_recorder;
+mod metrics;
+mod paper_trader;
+mod pool_detector;
+mod priority_fee_estimator;
+mod risk_manager;
 mod robot;
+mod slot_clock;
 mod spawner;
+mod token_screener;
+mod trade_journal;
+mod trade_task_registry;
+mod transaction_creator;
+mod transaction_error_decoder;
 mod transaction_parser;
+mod transaction_sender;
+mod unix_time;
 mod workflow_data;
 use clap::{
     Arg,
+    ArgAction,
     Command,
 };
 use self::error::{
@@ -22,6 +43,8 @@ use self::error::{
 use self::command_processor::{
     CommandProcessor,
     ParseTransaction,
+    Record,
+    Replay,
     Trade,
 };
 fn main() -> Result<(), Error> {
@@ -32,13 +55,26 @@ impl Processor {
     fn process() -> Result<(), Error> {
         const COMMAND_TRADE: &'static str = "trade";
         const COMMAND_PARSE_TRANSACTION: &'static str = "parse_transaction";
+        const COMMAND_RECORD: &'static str = "record";
+        const COMMAND_REPLAY: &'static str = "replay";
         const ARGUMENT_ENVIRONMENT_FILE_PATH: &'static str = "environment_configuration_file_path";
+        const ARGUMENT_GEYSER_RECORD_FILE_PATH: &'static str = "geyser_record_file_path";
+        const ARGUMENT_IS_ORIGINAL_PACE: &'static str = "is_original_pace";
         let arg_matches = clap::command!()
             .arg_required_else_help(true)
             .arg(Arg::new(ARGUMENT_ENVIRONMENT_FILE_PATH).required(true).long(ARGUMENT_ENVIRONMENT_FILE_PATH))
             .subcommand_required(true)
             .subcommand(Command::new(COMMAND_TRADE))
             .subcommand(Command::new(COMMAND_PARSE_TRANSACTION))
+            .subcommand(
+                Command::new(COMMAND_RECORD)
+                .arg(Arg::new(ARGUMENT_GEYSER_RECORD_FILE_PATH).required(true).long(ARGUMENT_GEYSER_RECORD_FILE_PATH))
+            )
+            .subcommand(
+                Command::new(COMMAND_REPLAY)
+                .arg(Arg::new(ARGUMENT_GEYSER_RECORD_FILE_PATH).required(true).long(ARGUMENT_GEYSER_RECORD_FILE_PATH))
+                .arg(Arg::new(ARGUMENT_IS_ORIGINAL_PACE).long(ARGUMENT_IS_ORIGINAL_PACE).action(ArgAction::SetTrue))
+            )
             .get_matches();
         let environment_configuration_file_path = arg_matches.get_one::<String>(ARGUMENT_ENVIRONMENT_FILE_PATH).into_unreachable_state(
             Backtrace::new(
@@ -55,6 +91,31 @@ impl Processor {
         match subcommand_arg_matches {
             (COMMAND_TRADE, _) => CommandProcessor::<Trade>::process(environment_configuration_file_path.as_str()),
             (COMMAND_PARSE_TRANSACTION, _) => CommandProcessor::<ParseTransaction>::process(environment_configuration_file_path.as_str()),
+            (COMMAND_RECORD, subcommand_arg_matches_) => {
+                let geyser_record_file_path = subcommand_arg_matches_.get_one::<String>(ARGUMENT_GEYSER_RECORD_FILE_PATH).into_unreachable_state(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?;
+                CommandProcessor::<Record>::process(
+                    environment_configuration_file_path.as_str(),
+                    geyser_record_file_path.as_str(),
+                )
+            }
+            (COMMAND_REPLAY, subcommand_arg_matches_) => {
+                let geyser_record_file_path = subcommand_arg_matches_.get_one::<String>(ARGUMENT_GEYSER_RECORD_FILE_PATH).into_unreachable_state(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?;
+                CommandProcessor::<Replay>::process(
+                    environment_configuration_file_path.as_str(),
+                    geyser_record_file_path.as_str(),
+                    subcommand_arg_matches_.get_flag(ARGUMENT_IS_ORIGINAL_PACE),
+                )
+            }
             _ => {
                 Result::Err(
                     Error::new_(
@@ -0,0 +1,529 @@
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+    OptionConverter,
+    ResultConverter,
+};
+use super::entry_scheduler::ENTRY_SCHEDULER;
+use super::exit_engine::ExitReason;
+use super::risk_manager::RiskRejectionReason;
+use super::token_screener::TokenCheck;
+use super::transaction_error_decoder::TransactionFailureReason;
+use super::grpc_server::{
+    GEYSER_ENDPOINT_STATISTIC_REGISTRY,
+    GEYSER_LAST_PROCESSED_SLOT,
+    GEYSER_MISSED_SLOTS_QUANTITY,
+    GEYSER_RECONNECTIONS_QUANTITY,
+};
+use super::trade_task_registry::TRADE_TASK_REGISTRY;
+use prometheus::{
+    Encoder,
+    Histogram,
+    HistogramOpts,
+    IntCounter,
+    IntCounterVec,
+    IntGauge,
+    IntGaugeVec,
+    Opts,
+    Registry,
+    TextEncoder,
+};
+use std::sync::atomic::Ordering;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+    OnceLock,
+};
+use std::time::Instant;
+use tokio::sync::mpsc::Sender;
+use yellowstone_grpc_proto::prelude::subscribe_update::UpdateOneof;
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+// The events are counted where they happen, the state is read from the registries at the scrape time, so the scrape
+// always sees the current values.
+pub struct Metrics {
+    registry: Registry,
+    geyser_updates: IntCounterVec,
+    geyser_reconnections: IntCounter,
+    geyser_missed_slots: IntCounter,
+    geyser_dropped_records: IntCounter,
+    geyser_last_processed_slot: IntGauge,
+    geyser_endpoint_subscribed: IntGaugeVec,
+    geyser_endpoint_won_races: IntGaugeVec,
+    detected_pools: IntCounter,
+    rejected_pools: IntCounterVec,
+    opened_trades: IntCounter,
+    closed_trades: IntCounterVec,
+    failed_transactions: IntCounterVec,
+    geyser_receiving_to_sending_latency: Histogram,
+    trading_tasks: IntGauge,
+    scheduled_entries: IntGauge,
+    open_positions: IntGauge,
+    open_positions_entry_pc_amount: IntGaugeVec,
+    open_positions_unrealized_pnl_pc_amount: IntGaugeVec,
+    realized_pnl_pc_amount: IntGaugeVec,
+    channel_depth: IntGaugeVec,
+    channel_depth_observer_registry: Mutex<Vec<ChannelDepthObserver>>,
+}
+impl Metrics {
+    const NAMESPACE: &'static str = "robot";
+    // From the single milliseconds of the hot path to the seconds of the slow RPC.
+    const LATENCY_BUCKETS: [f64; 12] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+    pub fn initialize() -> Result<(), Error> {
+        let metrics = Self {
+            registry: Registry::new(),
+            geyser_updates: IntCounterVec::new(
+                Self::create_opts("geyser_updates_total", "The updates received from all the Geyser endpoints, the duplicates included."),
+                &["kind"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_reconnections: IntCounter::with_opts(
+                Self::create_opts("geyser_reconnections_total", "The reconnections to the Geyser endpoints."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_missed_slots: IntCounter::with_opts(
+                Self::create_opts("geyser_missed_slots_total", "The upper bound of the slots missed while reconnecting."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_dropped_records: IntCounter::with_opts(
+                Self::create_opts("geyser_dropped_records_total", "The updates left out of the record because the recorder has fallen behind."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_last_processed_slot: IntGauge::with_opts(
+                Self::create_opts("geyser_last_processed_slot", "The latest slot received from the Geyser."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_endpoint_subscribed: IntGaugeVec::new(
+                Self::create_opts("geyser_endpoint_subscribed", "1 if the endpoint is subscribed."),
+                &["endpoint_index"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_endpoint_won_races: IntGaugeVec::new(
+                Self::create_opts("geyser_endpoint_won_races", "The updates the endpoint has delivered before the others."),
+                &["endpoint_index"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            detected_pools: IntCounter::with_opts(
+                Self::create_opts("detected_pools_total", "The Raydium pools detected in the transaction stream."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            rejected_pools: IntCounterVec::new(
+                Self::create_opts("rejected_pools_total", "The detected pools that have not been traded."),
+                &["reason"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            opened_trades: IntCounter::with_opts(
+                Self::create_opts("opened_trades_total", "The positions that have been bought."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            closed_trades: IntCounterVec::new(
+                Self::create_opts("closed_trades_total", "The positions that have been sold."),
+                &["exit_reason"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            failed_transactions: IntCounterVec::new(
+                Self::create_opts("failed_transactions_total", "The swaps that have failed the simulation or have landed failed."),
+                &["stage", "reason"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            geyser_receiving_to_sending_latency: Histogram::with_opts(
+                HistogramOpts::new(
+                    "geyser_receiving_to_sending_latency_seconds",
+                    "The time from receiving the pool initialization from the Geyser to sending the buy transaction.",
+                )
+                .namespace(Self::NAMESPACE)
+                .buckets(Self::LATENCY_BUCKETS.to_vec()),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            trading_tasks: IntGauge::with_opts(
+                Self::create_opts("trading_tasks", "The trading tasks, the ones that are still buying included."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            scheduled_entries: IntGauge::with_opts(
+                Self::create_opts("scheduled_entries", "The trading tasks waiting for the open time of the pool."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            open_positions: IntGauge::with_opts(
+                Self::create_opts("open_positions", "The open positions."),
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            open_positions_entry_pc_amount: IntGaugeVec::new(
+                Self::create_opts("open_positions_entry_pc_amount", "The quote amount spent on the open positions."),
+                &["quote_mint"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            open_positions_unrealized_pnl_pc_amount: IntGaugeVec::new(
+                Self::create_opts("open_positions_unrealized_pnl_pc_amount", "The PnL of the open positions at the last known reserves."),
+                &["quote_mint"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            realized_pnl_pc_amount: IntGaugeVec::new(
+                Self::create_opts("realized_pnl_pc_amount", "The PnL of the closed positions at the reserves the sell has been sent at."),
+                &["quote_mint"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            channel_depth: IntGaugeVec::new(
+                Self::create_opts("channel_depth", "The messages waiting in the channel."),
+                &["channel"],
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            channel_depth_observer_registry: Mutex::new(vec![]),
+        };
+        metrics.register()?;
+        METRICS.set(metrics).map_err(
+            |_: _| -> _ {
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+            }
+        )
+    }
+    pub fn get() -> Result<&'static Self, Error> {
+        METRICS.get().into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    // The sender is kept for the whole life of the process, as the channels of the robot are.
+    pub fn observe_channel_depth<'a, T>(&'a self, channel_name: &'static str, sender: &'a Sender<T>) -> Result<(), Error>
+    where
+        T: Send + 'static,
+    {
+        let sender_ = sender.clone();
+        self.lock()?.push(
+            ChannelDepthObserver {
+                channel_name,
+                get_depth: Box::new(
+                    move || -> usize {
+                        sender_.max_capacity() - sender_.capacity()
+                    }
+                ),
+            },
+        );
+        Ok(())
+    }
+    pub fn increment_geyser_updates<'a>(&'a self, update_oneof: &'a UpdateOneof) {
+        let kind = match update_oneof {
+            UpdateOneof::Account(_) => "account",
+            UpdateOneof::Slot(_) => "slot",
+            UpdateOneof::Transaction(_) => "transaction",
+            UpdateOneof::TransactionStatus(_) => "transaction_status",
+            UpdateOneof::Block(_) => "block",
+            UpdateOneof::Ping(_) => "ping",
+            UpdateOneof::Pong(_) => "pong",
+            UpdateOneof::BlockMeta(_) => "block_meta",
+            UpdateOneof::Entry(_) => "entry",
+        };
+        self.geyser_updates.with_label_values(&[kind]).inc();
+    }
+    pub fn increment_geyser_dropped_records<'a>(&'a self) {
+        self.geyser_dropped_records.inc();
+    }
+    pub fn increment_detected_pools<'a>(&'a self) {
+        self.detected_pools.inc();
+    }
+    pub fn increment_rejected_pools<'a>(&'a self, pool_rejection_reason: PoolRejectionReason) {
+        self.rejected_pools.with_label_values(&[pool_rejection_reason.as_str()]).inc();
+    }
+    pub fn increment_opened_trades<'a>(&'a self) {
+        self.opened_trades.inc();
+    }
+    // The 'exit_pc_amount' is the PC amount the position has been sold for.
+    pub fn increment_closed_trades<'a>(
+        &'a self,
+        quote_mint_pubkey: &'a str,
+        exit_reason: ExitReason,
+        entry_pc_amount: u64,
+        exit_pc_amount: u128,
+    ) {
+        self.closed_trades.with_label_values(&[format!("{:?}", exit_reason).as_str()]).inc();
+        self.realized_pnl_pc_amount.with_label_values(&[quote_mint_pubkey]).add(
+            Self::saturate(exit_pc_amount as i128 - entry_pc_amount as i128),
+        );
+    }
+    pub fn increment_failed_transactions<'a>(&'a self, transaction_failure_stage: TransactionFailureStage, transaction_failure_reason: TransactionFailureReason) {
+        self.failed_transactions.with_label_values(
+            &[
+                transaction_failure_stage.as_str(),
+                transaction_failure_reason.as_str(),
+            ],
+        )
+        .inc();
+    }
+    pub fn observe_geyser_receiving_to_sending_latency<'a>(&'a self, geyser_receiving_instant: Instant) {
+        self.geyser_receiving_to_sending_latency.observe(geyser_receiving_instant.elapsed().as_secs_f64());
+    }
+    // Returns the text exposition format.
+    pub fn render<'a>(&'a self) -> Result<Vec<u8>, Error> {
+        self.update_state()?;
+        let mut data = Vec::<u8>::new();
+        TextEncoder::new().encode(
+            &self.registry.gather(),
+            &mut data,
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(data)
+    }
+    fn update_state<'a>(&'a self) -> Result<(), Error> {
+        // The counters mirror the quantities the Geyser subscription keeps by itself, which only grow.
+        Self::update_counter(
+            &self.geyser_reconnections,
+            GEYSER_RECONNECTIONS_QUANTITY.load(Ordering::Relaxed),
+        );
+        Self::update_counter(
+            &self.geyser_missed_slots,
+            GEYSER_MISSED_SLOTS_QUANTITY.load(Ordering::Relaxed),
+        );
+        self.geyser_last_processed_slot.set(Self::saturate(GEYSER_LAST_PROCESSED_SLOT.load(Ordering::Relaxed) as i128));
+        if let Some(geyser_endpoint_statistic_registry) = GEYSER_ENDPOINT_STATISTIC_REGISTRY.get() {
+            '_a: for (endpoint_index, geyser_endpoint_statistic) in geyser_endpoint_statistic_registry.iter().enumerate() {
+                let endpoint_index_ = endpoint_index.to_string();
+                self.geyser_endpoint_subscribed.with_label_values(&[endpoint_index_.as_str()]).set(
+                    geyser_endpoint_statistic.is_subscribed.load(Ordering::Relaxed) as i64,
+                );
+                self.geyser_endpoint_won_races.with_label_values(&[endpoint_index_.as_str()]).set(
+                    Self::saturate(geyser_endpoint_statistic.won_races_quantity.load(Ordering::Relaxed) as i128),
+                );
+            }
+        }
+        self.trading_tasks.set(Self::saturate(TRADE_TASK_REGISTRY.get_trading_tasks_quantity() as i128));
+        self.scheduled_entries.set(Self::saturate(ENTRY_SCHEDULER.get_scheduled_entries_quantity()? as i128));
+        let open_position_registry = TRADE_TASK_REGISTRY.get_open_position_registry()?;
+        self.open_positions.set(Self::saturate(open_position_registry.len() as i128));
+        // The quote mints without open positions are not reported.
+        self.open_positions_entry_pc_amount.reset();
+        self.open_positions_unrealized_pnl_pc_amount.reset();
+        '_a: for open_position in open_position_registry.iter() {
+            self.open_positions_entry_pc_amount.with_label_values(&[open_position.quote_mint_pubkey.as_str()]).add(
+                Self::saturate(open_position.entry_pc_amount as i128),
+            );
+            self.open_positions_unrealized_pnl_pc_amount.with_label_values(&[open_position.quote_mint_pubkey.as_str()]).add(
+                Self::saturate(open_position.unrealized_pnl_pc_amount),
+            );
+        }
+        '_a: for channel_depth_observer in self.lock()?.iter() {
+            self.channel_depth.with_label_values(&[channel_depth_observer.channel_name]).set(
+                Self::saturate((channel_depth_observer.get_depth)() as i128),
+            );
+        }
+        Ok(())
+    }
+    fn register<'a>(&'a self) -> Result<(), Error> {
+        let collector_registry: [Box<dyn prometheus::core::Collector>; 21] = [
+            Box::new(self.geyser_updates.clone()),
+            Box::new(self.geyser_reconnections.clone()),
+            Box::new(self.geyser_missed_slots.clone()),
+            Box::new(self.geyser_dropped_records.clone()),
+            Box::new(self.geyser_last_processed_slot.clone()),
+            Box::new(self.geyser_endpoint_subscribed.clone()),
+            Box::new(self.geyser_endpoint_won_races.clone()),
+            Box::new(self.detected_pools.clone()),
+            Box::new(self.rejected_pools.clone()),
+            Box::new(self.opened_trades.clone()),
+            Box::new(self.closed_trades.clone()),
+            Box::new(self.failed_transactions.clone()),
+            Box::new(self.geyser_receiving_to_sending_latency.clone()),
+            Box::new(self.trading_tasks.clone()),
+            Box::new(self.scheduled_entries.clone()),
+            Box::new(self.open_positions.clone()),
+            Box::new(self.open_positions_entry_pc_amount.clone()),
+            Box::new(self.open_positions_unrealized_pnl_pc_amount.clone()),
+            Box::new(self.realized_pnl_pc_amount.clone()),
+            Box::new(self.channel_depth.clone()),
+            Box::new(self.create_build_information()?),
+        ];
+        '_a: for collector in collector_registry.into_iter() {
+            self.registry.register(collector).into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        }
+        Ok(())
+    }
+    fn create_build_information<'a>(&'a self) -> Result<IntGaugeVec, Error> {
+        let build_information = IntGaugeVec::new(
+            Self::create_opts("build_information", "The version of the robot."),
+            &["version"],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        build_information.with_label_values(&[env!("CARGO_PKG_VERSION")]).set(1);
+        Ok(build_information)
+    }
+    fn create_opts(name: &'static str, help: &'static str) -> Opts {
+        Opts::new(name, help).namespace(Self::NAMESPACE)
+    }
+    fn update_counter<'a>(int_counter: &'a IntCounter, quantity: u64) {
+        int_counter.inc_by(quantity.saturating_sub(int_counter.get()));
+    }
+    fn saturate(value: i128) -> i64 {
+        value.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, Vec<ChannelDepthObserver>>, Error> {
+        self.channel_depth_observer_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct ChannelDepthObserver {
+    channel_name: &'static str,
+    get_depth: Box<dyn Fn() -> usize + Send + Sync>,
+}
+#[derive(Debug, Clone, Copy)]
+pub enum PoolRejectionReason {
+    GracefulShutdown,
+    EntryPaused,
+    UnsupportedQuoteMint,
+    Risk(RiskRejectionReason),
+    // The first of the failed checks.
+    TokenScreening(TokenCheck),
+    OpenTimeTooFar,
+    PriceImpactTooHigh,
+}
+impl PoolRejectionReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::GracefulShutdown => "graceful_shutdown",
+            Self::EntryPaused => "entry_paused",
+            Self::UnsupportedQuoteMint => "unsupported_quote_mint",
+            Self::Risk(risk_rejection_reason) => risk_rejection_reason.as_str(),
+            Self::TokenScreening(token_check) => token_check.as_str(),
+            Self::OpenTimeTooFar => "open_time_too_far",
+            Self::PriceImpactTooHigh => "price_impact_too_high",
+        }
+    }
+}
+#[derive(Debug, Clone, Copy)]
+pub enum TransactionFailureStage {
+    Simulation,
+    Landing,
+}
+impl TransactionFailureStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Simulation => "simulation",
+            Self::Landing => "landing",
+        }
+    }
+}
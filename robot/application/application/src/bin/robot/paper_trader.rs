@@ -0,0 +1,163 @@
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+    OptionConverter,
+    ResultConverter,
+};
+use super::exit_engine::ExitReason;
+use super::extern_source::U128;
+use super::robot::ForTrade;
+use super::token_screener::TokenVerdict;
+use super::clock::CLOCK;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{
+    BTreeMap,
+    VecDeque,
+};
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+pub static PAPER_TRADING_JOURNAL: PaperTradingJournal = PaperTradingJournal::new();
+// The virtual positions of the paper trading. They are never signed or sent, only logged and reported by the HTTP API.
+pub struct PaperTradingJournal {
+    paper_position_registry: Mutex<PaperPositionRegistry>,
+}
+impl PaperTradingJournal {
+    // Only the latest closed positions are kept, the totals are counted for all of them.
+    const CLOSED_POSITIONS_MAXIMUM_QUANTITY: usize = 1000;
+    const fn new() -> Self {
+        Self {
+            paper_position_registry: Mutex::new(
+                PaperPositionRegistry {
+                    open_position_registry: BTreeMap::new(),
+                    closed_position_registry: VecDeque::new(),
+                    closed_positions_quantity: 0,
+                    profitable_closed_positions_quantity: 0,
+                },
+            ),
+        }
+    }
+    pub fn open<'a>(&'a self, for_trade: &'a ForTrade, base_amount: u64) -> Result<(), Error> {
+        let paper_position = PaperPosition {
+            amm_market_pubkey: for_trade.amm_market_pubkey.to_string(),
+            quote_mint_pubkey: for_trade.quote_mint_pubkey.to_string(),
+            base_mint_pubkey: for_trade.base_mint_pubkey.to_string(),
+            entry_pc_amount: for_trade.initial_pc_amount,
+            base_amount,
+            position_pc_amount: for_trade.initial_pc_amount as u128,
+            maximum_position_pc_amount: for_trade.initial_pc_amount as u128,
+            opening_timestamp: CLOCK.get_timestamp()?,
+            closing_timestamp: None,
+            exit_reason: None,
+            token_verdict: for_trade.token_verdict.clone(),
+        };
+        if self.lock()?.open_position_registry.insert(
+            for_trade.amm_market_pubkey,
+            paper_position,
+        )
+        .is_some() {
+            return Err(
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        Ok(())
+    }
+    // The 'position_pc_amount' is the PC amount the whole position would be sold for at the current reserves.
+    pub fn mark<'a>(&'a self, amm_market_pubkey: &'a Pubkey, position_pc_amount: U128) -> Result<(), Error> {
+        let mut paper_position_registry = self.lock()?;
+        let paper_position = paper_position_registry.open_position_registry.get_mut(amm_market_pubkey).into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        paper_position.position_pc_amount = position_pc_amount.as_u128();
+        if paper_position.position_pc_amount > paper_position.maximum_position_pc_amount {
+            paper_position.maximum_position_pc_amount = paper_position.position_pc_amount;
+        }
+        Ok(())
+    }
+    // The position is closed at the last marked amount, as if the sell had landed at the current reserves.
+    pub fn close<'a>(&'a self, amm_market_pubkey: &'a Pubkey, exit_reason: ExitReason) -> Result<PaperPosition, Error> {
+        let closing_timestamp = CLOCK.get_timestamp()?;
+        let mut paper_position_registry = self.lock()?;
+        let mut paper_position = paper_position_registry.open_position_registry.remove(amm_market_pubkey).into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        paper_position.closing_timestamp = Some(closing_timestamp);
+        paper_position.exit_reason = Some(exit_reason);
+        paper_position_registry.closed_positions_quantity += 1;
+        if paper_position.position_pc_amount > paper_position.entry_pc_amount as u128 {
+            paper_position_registry.profitable_closed_positions_quantity += 1;
+        }
+        if paper_position_registry.closed_position_registry.len() >= Self::CLOSED_POSITIONS_MAXIMUM_QUANTITY {
+            let _ = paper_position_registry.closed_position_registry.pop_front();
+        }
+        paper_position_registry.closed_position_registry.push_back(paper_position.clone());
+        Ok(paper_position)
+    }
+    pub fn create_report<'a>(&'a self) -> Result<Vec<u8>, Error> {
+        let paper_position_registry = self.lock()?;
+        serde_json::to_vec(
+            &PaperTradingReport {
+                closed_positions_quantity: paper_position_registry.closed_positions_quantity,
+                profitable_closed_positions_quantity: paper_position_registry.profitable_closed_positions_quantity,
+                open_position_registry: paper_position_registry.open_position_registry.values().collect::<Vec<&'_ PaperPosition>>(),
+                closed_position_registry: &paper_position_registry.closed_position_registry,
+            },
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, PaperPositionRegistry>, Error> {
+        self.paper_position_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct PaperPositionRegistry {
+    open_position_registry: BTreeMap<Pubkey, PaperPosition>,
+    closed_position_registry: VecDeque<PaperPosition>,
+    closed_positions_quantity: u64,
+    profitable_closed_positions_quantity: u64,
+}
+#[derive(Clone, serde::Serialize)]
+pub struct PaperPosition {
+    pub amm_market_pubkey: String,
+    pub quote_mint_pubkey: String,
+    pub base_mint_pubkey: String,
+    pub entry_pc_amount: u64,
+    pub base_amount: u64,
+    pub position_pc_amount: u128,
+    pub maximum_position_pc_amount: u128,
+    pub opening_timestamp: u64,
+    pub closing_timestamp: Option<u64>,
+    pub exit_reason: Option<ExitReason>,
+    pub token_verdict: Option<TokenVerdict>,
+}
+#[derive(serde::Serialize)]
+struct PaperTradingReport<'a> {
+    closed_positions_quantity: u64,
+    profitable_closed_positions_quantity: u64,
+    open_position_registry: Vec<&'a PaperPosition>,
+    closed_position_registry: &'a VecDeque<PaperPosition>,
+}
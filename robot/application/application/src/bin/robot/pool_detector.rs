@@ -0,0 +1,579 @@
+use super::error::{
+    Backtrace,
+    Error,
+    OptionConverter,
+    ResultConverter,
+};
+use super::extern_source::RaydiumAmmInitializeInstruction2;
+use super::workflow_data::{
+    TransactionDifferentiation,
+    WorkflowData,
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer,
+    UiInstruction,
+    UiTransactionStatusMeta,
+};
+use std::str::FromStr;
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransactionInfo;
+pub struct PoolDetector;
+impl PoolDetector {
+    // The detectors are tried in order, the first detected pool wins.
+    const DETECTOR_REGISTRY: [&'static (dyn Detect + Sync); 2] = [
+        &RaydiumAmmOuterInitialize2,
+        &RaydiumAmmInnerInitialize2,
+    ];
+    pub fn detect<'a>(detectable_transaction: &'a DetectableTransaction) -> Result<Option<DetectedPool>, Error> {
+        '_a: for detector in Self::DETECTOR_REGISTRY.iter() {
+            if let Some(detected_pool) = detector.detect(detectable_transaction)? {
+                return Ok(Some(detected_pool));
+            }
+        }
+        Ok(None)
+    }
+}
+pub trait Detect {
+    fn detect<'a>(&'a self, detectable_transaction: &'a DetectableTransaction) -> Result<Option<DetectedPool>, Error>;
+}
+// The 'initialize2' instruction is called directly by the transaction at any position.
+pub struct RaydiumAmmOuterInitialize2;
+impl Detect for RaydiumAmmOuterInitialize2 {
+    fn detect<'a>(&'a self, detectable_transaction: &'a DetectableTransaction) -> Result<Option<DetectedPool>, Error> {
+        '_a: for detectable_instruction in detectable_transaction.instruction_registry.iter() {
+            if let Some(detected_pool) = DetectedPool::from_raydium_amm_initialize_2(
+                detectable_transaction,
+                detectable_instruction,
+            )? {
+                return Ok(Some(detected_pool));
+            }
+        }
+        Ok(None)
+    }
+}
+// The 'initialize2' instruction is called through the CPI by some other program, for example by a launchpad.
+pub struct RaydiumAmmInnerInitialize2;
+impl Detect for RaydiumAmmInnerInitialize2 {
+    fn detect<'a>(&'a self, detectable_transaction: &'a DetectableTransaction) -> Result<Option<DetectedPool>, Error> {
+        '_a: for detectable_instruction in detectable_transaction.inner_instruction_registry.iter() {
+            if let Some(detected_pool) = DetectedPool::from_raydium_amm_initialize_2(
+                detectable_transaction,
+                detectable_instruction,
+            )? {
+                return Ok(Some(detected_pool));
+            }
+        }
+        Ok(None)
+    }
+}
+#[derive(Debug)]
+pub struct DetectedPool {
+    pub amm_market_pubkey: Pubkey,
+    pub amm_authority_pubkey: Pubkey,
+    pub amm_open_orders_pubkey: Pubkey,
+    pub amm_target_orders_pubkey: Pubkey,
+    pub amm_coin_mint_pubkey: Pubkey,
+    pub amm_pc_mint_pubkey: Pubkey,
+    pub amm_coin_vault_pubkey: Pubkey,
+    pub amm_pc_vault_pubkey: Pubkey,
+    pub serum_program_pubkey: Pubkey,
+    pub serum_market_pubkey: Pubkey,
+    // The wallet that has signed the initialization and provided the initial liquidity.
+    pub creator_pubkey: Pubkey,
+    pub raydium_amm_initialize_instruction_2: RaydiumAmmInitializeInstruction2,
+}
+impl DetectedPool {
+    fn from_raydium_amm_initialize_2<'a>(
+        detectable_transaction: &'a DetectableTransaction,
+        detectable_instruction: &'a DetectableInstruction,
+    ) -> Result<Option<Self>, Error> {
+        if detectable_transaction.get_pubkey(detectable_instruction.program_id_index)? != WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY
+        || detectable_instruction.data.first() != Some(&WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_TAG)
+        || detectable_instruction.account_index_registry.len() < WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_ACCOUNTS_QUANTITY {
+            return Ok(None);
+        }
+        let get_pubkey = |vector_index: usize| -> Result<Pubkey, Error> {
+            detectable_transaction.get_pubkey(detectable_instruction.account_index_registry[vector_index] as usize)
+        };
+        Ok(
+            Some(
+                Self {
+                    amm_market_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_MARKET_PUBKEY_VECTOR_INDEX)?,
+                    amm_authority_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_AUTHORITY_PUBKEY_VECTOR_INDEX)?,
+                    amm_open_orders_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_OPEN_ORDERS_PUBKEY_VECTOR_INDEX)?,
+                    amm_target_orders_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_TARGET_ORDERS_PUBKEY_VECTOR_INDEX)?,
+                    amm_coin_mint_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_COIN_MINT_PUBKEY_VECTOR_INDEX)?,
+                    amm_pc_mint_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_MINT_PUBKEY_VECTOR_INDEX)?,
+                    amm_coin_vault_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_COIN_VAULT_PUBKEY_VECTOR_INDEX)?,
+                    amm_pc_vault_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_VAULT_PUBKEY_VECTOR_INDEX)?,
+                    serum_program_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_SERUM_PROGRAM_PUBKEY_VECTOR_INDEX)?,
+                    serum_market_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_SERUM_MARKET_PUBKEY_VECTOR_INDEX)?,
+                    creator_pubkey: get_pubkey(WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_USER_WALLET_PUBKEY_VECTOR_INDEX)?,
+                    raydium_amm_initialize_instruction_2: RaydiumAmmInitializeInstruction2::unpack(detectable_instruction.data.as_slice())?,
+                },
+            ),
+        )
+    }
+}
+// The transaction representation that does not depend on the source it was received from.
+// The account keys are the static ones followed by the writable and the readonly keys loaded
+// from the address lookup tables, which is the order the compiled account indices refer to.
+pub struct DetectableTransaction {
+    account_key_registry: Vec<Pubkey>,
+    instruction_registry: Vec<DetectableInstruction>,
+    inner_instruction_registry: Vec<DetectableInstruction>,
+}
+impl DetectableTransaction {
+    pub fn from_geyser<'a>(subscribe_update_transaction_info: &'a SubscribeUpdateTransactionInfo) -> Result<Self, Error> {
+        fn to_pubkey<'b>(bytes: &'b [u8]) -> Result<Pubkey, Error> {
+            Pubkey::try_from(bytes).into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+        }
+        let transaction_status_meta = subscribe_update_transaction_info
+        .meta
+        .as_ref()
+        .into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let message = subscribe_update_transaction_info
+        .transaction
+        .as_ref()
+        .into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?
+        .message
+        .as_ref()
+        .into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut account_key_registry = Vec::<Pubkey>::with_capacity(
+            message.account_keys.len() + transaction_status_meta.loaded_writable_addresses.len() + transaction_status_meta.loaded_readonly_addresses.len(),
+        );
+        '_a: for account_key in message.account_keys.iter()
+            .chain(transaction_status_meta.loaded_writable_addresses.iter())
+            .chain(transaction_status_meta.loaded_readonly_addresses.iter()) {
+            account_key_registry.push(to_pubkey(account_key.as_slice())?);
+        }
+        let instruction_registry = message.instructions.iter().map(
+            |compiled_instruction: _| -> _ {
+                DetectableInstruction {
+                    program_id_index: compiled_instruction.program_id_index as usize,
+                    account_index_registry: compiled_instruction.accounts.clone(),
+                    data: compiled_instruction.data.clone(),
+                }
+            }
+        )
+        .collect::<Vec<DetectableInstruction>>();
+        let inner_instruction_registry = transaction_status_meta.inner_instructions.iter().flat_map(
+            |inner_instructions: _| -> _ {
+                inner_instructions.instructions.iter()
+            }
+        )
+        .map(
+            |inner_instruction: _| -> _ {
+                DetectableInstruction {
+                    program_id_index: inner_instruction.program_id_index as usize,
+                    account_index_registry: inner_instruction.accounts.clone(),
+                    data: inner_instruction.data.clone(),
+                }
+            }
+        )
+        .collect::<Vec<DetectableInstruction>>();
+        Ok(
+            Self {
+                account_key_registry,
+                instruction_registry,
+                inner_instruction_registry,
+            },
+        )
+    }
+    // The inner instructions are expected in the compiled form, so the transaction must be requested in a binary encoding.
+    pub fn from_rpc<'a>(
+        versioned_transaction: &'a VersionedTransaction,
+        ui_transaction_status_meta: &'a UiTransactionStatusMeta,
+    ) -> Result<Self, Error> {
+        let mut account_key_registry = versioned_transaction.message.static_account_keys().to_vec();
+        if let OptionSerializer::Some(ref ui_loaded_addresses) = ui_transaction_status_meta.loaded_addresses {
+            '_a: for account_key in ui_loaded_addresses.writable.iter().chain(ui_loaded_addresses.readonly.iter()) {
+                account_key_registry.push(
+                    Pubkey::from_str(account_key.as_str()).into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?,
+                );
+            }
+        }
+        let instruction_registry = versioned_transaction.message.instructions().iter().map(
+            |compiled_instruction: _| -> _ {
+                DetectableInstruction {
+                    program_id_index: compiled_instruction.program_id_index as usize,
+                    account_index_registry: compiled_instruction.accounts.clone(),
+                    data: compiled_instruction.data.clone(),
+                }
+            }
+        )
+        .collect::<Vec<DetectableInstruction>>();
+        let mut inner_instruction_registry = Vec::<DetectableInstruction>::new();
+        if let OptionSerializer::Some(ref ui_inner_instructions_registry) = ui_transaction_status_meta.inner_instructions {
+            '_a: for ui_inner_instructions in ui_inner_instructions_registry.iter() {
+                '_b: for ui_instruction in ui_inner_instructions.instructions.iter() {
+                    // Parsed instructions are only returned for the 'JsonParsed' encoding, so there is nothing to detect in them.
+                    if let UiInstruction::Compiled(ref ui_compiled_instruction) = *ui_instruction {
+                        inner_instruction_registry.push(
+                            DetectableInstruction {
+                                program_id_index: ui_compiled_instruction.program_id_index as usize,
+                                account_index_registry: ui_compiled_instruction.accounts.clone(),
+                                data: bs58::decode(ui_compiled_instruction.data.as_str()).into_vec().into_(
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )?,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        Ok(
+            Self {
+                account_key_registry,
+                instruction_registry,
+                inner_instruction_registry,
+            },
+        )
+    }
+    // The price of the compute unit in micro-lamports the transaction has set, zero if it has not set any. The compute
+    // budget instructions are processed by the runtime only at the top level.
+    pub fn get_compute_unit_price<'a>(&'a self) -> Result<u64, Error> {
+        '_a: for detectable_instruction in self.instruction_registry.iter() {
+            if self.get_pubkey(detectable_instruction.program_id_index)? != WorkflowData::<TransactionDifferentiation>::COMPUTE_BUDGET_PROGRAM_PUBKEY
+            || detectable_instruction.data.first() != Some(&WorkflowData::<TransactionDifferentiation>::COMPUTE_BUDGET_SET_COMPUTE_UNIT_PRICE_INSTRUCTION_TAG) {
+                continue '_a;
+            }
+            let micro_lamports = detectable_instruction.data.get(1..9).into_out_of_range(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            return Ok(
+                u64::from_le_bytes(
+                    micro_lamports.try_into().into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?,
+                ),
+            );
+        }
+        Ok(0)
+    }
+    fn get_pubkey<'a>(&'a self, vector_index: usize) -> Result<Pubkey, Error> {
+        self.account_key_registry.get(vector_index).copied().into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct DetectableInstruction {
+    program_id_index: usize,
+    account_index_registry: Vec<u8>,
+    data: Vec<u8>,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::{
+        CompiledInstruction,
+        InnerInstruction,
+        InnerInstructions,
+        Message,
+        Transaction,
+        TransactionStatusMeta,
+    };
+    // The '2gMuTdGx6RaQKSrUqGib2kkNQ7XD71eMvA3fm8h5MY8qFSLoALQrnxiWo3YzCdaTSEstGd751HwD3LqVaxjX268t' transaction recorded
+    // in 'transaction_parser.rs'. It is the legacy transaction that calls the 'initialize2' directly as the third instruction.
+    const ACCOUNT_KEY_REGISTRY: [&'static str; 22] = [
+        "87nRYXqKArSLrotSCWXRLkCnk5jVfiQVo3HqjLT31VeK",
+        "5jhe4Lf4J51Afa53hjgz4WUmWN6TEnxW15bEKUGFD44M",
+        "6DdbGL4GRr4gMeE4gkHQHLVABuD8vMDNC1QmsgtUKvPZ",
+        "6ZmtFc7ZfGZCvTGwtKnNCqjDBKd8JFYrMwvXD3kZs9Yc",
+        "JBFZxVNNMrR6prECdMWbSQXMUtGjYRYy61psjgQdm5jU",
+        "93NvHA7Ci7yu6oL4sca1f976AcKpAUSXNUMs1YDQZvZb",
+        "Dc88MUmS675aV4YDLkyLvofSSBidkAFWcVWsiQKRnpX9",
+        "HXkWvZfyo8gwJZMduYVBbMXSh12X9xWs9h32syfpjoKX",
+        "7YttLkHDoNj9wyDur5pM1ejNaAvT9X4eqaYcHQqtj2G5",
+        "GPjBbuAQJ5nLCuCfbvsgxwZZLLNqwQ3jUT2Crkx3muFL",
+        "DZAbjSBqm4jerdzJymDp8bphDpoSmMCL7bbFQhAtr6Ei",
+        "11111111111111111111111111111111",
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        "So11111111111111111111111111111111111111112",
+        "SysvarRent111111111111111111111111111111111",
+        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+        "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1",
+        "q2y4FENF5cFdX95e2hg6e2MRdxkfeiF3PUHtw1ypump",
+        "9DCxsMizn3H1hprZ7xWe6LDzeUeZBksYFpBWBtSf1PQX",
+        "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX",
+        "BnMFVbommD8RLKjoGTeH5DyuoXAmMjkV8h42sPpYmJ28",
+    ];
+    const RAYDIUM_AMM_PROGRAM_ID_INDEX: u32 = 15;
+    const RAYDIUM_AMM_INITIALIZE_2_ACCOUNT_INDEX_REGISTRY: [u8; 21] = [12, 16, 11, 14, 2, 17, 3, 4, 18, 13, 5, 6, 7, 19, 8, 20, 21, 0, 9, 1, 10];
+    const RAYDIUM_AMM_INITIALIZE_2_DATA: &'static str = "4YGRzKFWLGjqCEnZ2ZNMhL4Z2pcEJ8HRH4u";
+    fn decode_account_key_registry() -> Result<Vec<Vec<u8>>, Error> {
+        let mut account_key_registry = Vec::<Vec<u8>>::with_capacity(ACCOUNT_KEY_REGISTRY.len());
+        '_a: for account_key in ACCOUNT_KEY_REGISTRY.iter() {
+            account_key_registry.push(
+                Pubkey::from_str(account_key)
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?
+                .to_bytes()
+                .to_vec(),
+            );
+        }
+        Ok(account_key_registry)
+    }
+    fn decode_data<'a>(data: &'a str) -> Result<Vec<u8>, Error> {
+        bs58::decode(data).into_vec().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn assert_recorded_pool(detected_pool: Option<DetectedPool>) -> Result<(), Error> {
+        let detected_pool = detected_pool.into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let to_string = |pubkey: Pubkey| -> String {
+            pubkey.to_string()
+        };
+        assert_eq!(to_string(detected_pool.amm_market_pubkey), "6DdbGL4GRr4gMeE4gkHQHLVABuD8vMDNC1QmsgtUKvPZ");
+        assert_eq!(to_string(detected_pool.amm_authority_pubkey), "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+        assert_eq!(to_string(detected_pool.amm_open_orders_pubkey), "6ZmtFc7ZfGZCvTGwtKnNCqjDBKd8JFYrMwvXD3kZs9Yc");
+        assert_eq!(to_string(detected_pool.amm_target_orders_pubkey), "HXkWvZfyo8gwJZMduYVBbMXSh12X9xWs9h32syfpjoKX");
+        assert_eq!(to_string(detected_pool.amm_coin_mint_pubkey), "q2y4FENF5cFdX95e2hg6e2MRdxkfeiF3PUHtw1ypump");
+        assert_eq!(to_string(detected_pool.amm_pc_mint_pubkey), "So11111111111111111111111111111111111111112");
+        assert_eq!(to_string(detected_pool.amm_coin_vault_pubkey), "93NvHA7Ci7yu6oL4sca1f976AcKpAUSXNUMs1YDQZvZb");
+        assert_eq!(to_string(detected_pool.amm_pc_vault_pubkey), "Dc88MUmS675aV4YDLkyLvofSSBidkAFWcVWsiQKRnpX9");
+        assert_eq!(to_string(detected_pool.serum_program_pubkey), "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX");
+        assert_eq!(to_string(detected_pool.serum_market_pubkey), "BnMFVbommD8RLKjoGTeH5DyuoXAmMjkV8h42sPpYmJ28");
+        assert_eq!(to_string(detected_pool.creator_pubkey), "87nRYXqKArSLrotSCWXRLkCnk5jVfiQVo3HqjLT31VeK");
+        // The values are the ones the program has logged for the instruction.
+        assert_eq!(detected_pool.raydium_amm_initialize_instruction_2.nonce, 254);
+        assert_eq!(detected_pool.raydium_amm_initialize_instruction_2.open_time, 1732807457);
+        assert_eq!(detected_pool.raydium_amm_initialize_instruction_2.init_pc_amount, 763000000000);
+        assert_eq!(detected_pool.raydium_amm_initialize_instruction_2.init_coin_amount, 206900000000000000);
+        Ok(())
+    }
+    #[test]
+    fn detect_direct_initialize_2() -> Result<(), Error> {
+        let mut subscribe_update_transaction_info = SubscribeUpdateTransactionInfo {
+            transaction: Some(
+                Transaction {
+                    message: Some(
+                        Message {
+                            account_keys: decode_account_key_registry()?,
+                            instructions: vec![
+                                CompiledInstruction {
+                                    program_id_index: 11,
+                                    accounts: vec![0, 1],
+                                    data: decode_data(
+                                        "3ipZWoAAn4YDcVQVHvBfwEQ2246mFzLaU4WzTiFjwbB26yfE6t7trEPrFrKDhPYKrYAqR21Kc3UdwikPj83Ftot5EDi7U4pzcefyf4VHA6DdajWx2PR9czmBVooYwWQ7XHTzixxJTkta5Gxudpy8sv7pxbMwaij8q77LRyrPi",
+                                    )?,
+                                },
+                                CompiledInstruction {
+                                    program_id_index: 12,
+                                    accounts: vec![1, 13, 0, 14],
+                                    data: decode_data("2")?,
+                                },
+                                CompiledInstruction {
+                                    program_id_index: RAYDIUM_AMM_PROGRAM_ID_INDEX,
+                                    accounts: RAYDIUM_AMM_INITIALIZE_2_ACCOUNT_INDEX_REGISTRY.to_vec(),
+                                    data: decode_data(RAYDIUM_AMM_INITIALIZE_2_DATA)?,
+                                },
+                                CompiledInstruction {
+                                    program_id_index: 12,
+                                    accounts: vec![1, 0, 0],
+                                    data: decode_data("A")?,
+                                },
+                            ],
+                            ..Default::default()
+                        },
+                    ),
+                    ..Default::default()
+                },
+            ),
+            meta: Some(TransactionStatusMeta::default()),
+            ..Default::default()
+        };
+        assert_recorded_pool(PoolDetector::detect(&DetectableTransaction::from_geyser(&subscribe_update_transaction_info)?)?)?;
+        // The same transaction without the 'initialize2'.
+        if let Some(ref mut transaction) = subscribe_update_transaction_info.transaction {
+            if let Some(ref mut message) = transaction.message {
+                let _ = message.instructions.remove(2);
+            }
+        }
+        assert!(PoolDetector::detect(&DetectableTransaction::from_geyser(&subscribe_update_transaction_info)?)?.is_none());
+        Ok(())
+    }
+    #[test]
+    fn detect_initialize_2_via_cpi() -> Result<(), Error> {
+        // The recorded instruction is moved into the inner instructions of the unknown program, as a launchpad calls it.
+        let mut account_key_registry = decode_account_key_registry()?;
+        let launchpad_program_id_index = account_key_registry.len() as u32;
+        account_key_registry.push(Pubkey::new_unique().to_bytes().to_vec());
+        let detectable_transaction = DetectableTransaction::from_geyser(
+            &SubscribeUpdateTransactionInfo {
+                transaction: Some(
+                    Transaction {
+                        message: Some(
+                            Message {
+                                account_keys: account_key_registry,
+                                instructions: vec![
+                                    CompiledInstruction {
+                                        program_id_index: launchpad_program_id_index,
+                                        accounts: RAYDIUM_AMM_INITIALIZE_2_ACCOUNT_INDEX_REGISTRY.to_vec(),
+                                        data: vec![1, 2, 3],
+                                    },
+                                ],
+                                ..Default::default()
+                            },
+                        ),
+                        ..Default::default()
+                    },
+                ),
+                meta: Some(
+                    TransactionStatusMeta {
+                        inner_instructions: vec![
+                            InnerInstructions {
+                                index: 0,
+                                instructions: vec![
+                                    InnerInstruction {
+                                        program_id_index: RAYDIUM_AMM_PROGRAM_ID_INDEX,
+                                        accounts: RAYDIUM_AMM_INITIALIZE_2_ACCOUNT_INDEX_REGISTRY.to_vec(),
+                                        data: decode_data(RAYDIUM_AMM_INITIALIZE_2_DATA)?,
+                                        stack_height: Some(2),
+                                    },
+                                ],
+                            },
+                        ],
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            },
+        )?;
+        assert!(RaydiumAmmOuterInitialize2.detect(&detectable_transaction)?.is_none());
+        assert_recorded_pool(PoolDetector::detect(&detectable_transaction)?)
+    }
+    #[test]
+    fn detect_initialize_2_with_address_lookup_table() -> Result<(), Error> {
+        // The recorded transaction is recompiled as the v0 one, the AMM accounts are loaded from the address lookup table
+        // as writable ones and the trailing readonly accounts as readonly ones. The compiled indices refer to the static
+        // keys, then to the loaded writable keys, then to the loaded readonly keys.
+        const STATIC_ACCOUNT_INDEX_REGISTRY: [u8; 6] = [0, 1, 11, 12, 13, 14];
+        const LOADED_WRITABLE_ACCOUNT_INDEX_REGISTRY: [u8; 9] = [2, 3, 4, 5, 6, 7, 8, 9, 10];
+        const LOADED_READONLY_ACCOUNT_INDEX_REGISTRY: [u8; 7] = [15, 16, 17, 18, 19, 20, 21];
+        let account_key_registry = decode_account_key_registry()?;
+        let recompiled_account_index_registry = STATIC_ACCOUNT_INDEX_REGISTRY
+        .iter()
+        .chain(LOADED_WRITABLE_ACCOUNT_INDEX_REGISTRY.iter())
+        .chain(LOADED_READONLY_ACCOUNT_INDEX_REGISTRY.iter())
+        .copied()
+        .collect::<Vec<u8>>();
+        let recompile = |account_index: u8| -> Result<u8, Error> {
+            recompiled_account_index_registry
+            .iter()
+            .position(
+                |account_index_: _| -> _ {
+                    *account_index_ == account_index
+                }
+            )
+            .map(
+                |position: _| -> _ {
+                    position as u8
+                }
+            )
+            .into_out_of_range(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+        };
+        let select = |account_index_registry: &[u8]| -> Vec<Vec<u8>> {
+            account_index_registry.iter().map(
+                |account_index: _| -> _ {
+                    account_key_registry[*account_index as usize].clone()
+                }
+            )
+            .collect::<Vec<Vec<u8>>>()
+        };
+        let mut accounts = Vec::<u8>::with_capacity(RAYDIUM_AMM_INITIALIZE_2_ACCOUNT_INDEX_REGISTRY.len());
+        '_a: for account_index in RAYDIUM_AMM_INITIALIZE_2_ACCOUNT_INDEX_REGISTRY.iter() {
+            accounts.push(recompile(*account_index)?);
+        }
+        let detectable_transaction = DetectableTransaction::from_geyser(
+            &SubscribeUpdateTransactionInfo {
+                transaction: Some(
+                    Transaction {
+                        message: Some(
+                            Message {
+                                account_keys: select(STATIC_ACCOUNT_INDEX_REGISTRY.as_slice()),
+                                instructions: vec![
+                                    CompiledInstruction {
+                                        program_id_index: recompile(RAYDIUM_AMM_PROGRAM_ID_INDEX as u8)? as u32,
+                                        accounts,
+                                        data: decode_data(RAYDIUM_AMM_INITIALIZE_2_DATA)?,
+                                    },
+                                ],
+                                versioned: true,
+                                ..Default::default()
+                            },
+                        ),
+                        ..Default::default()
+                    },
+                ),
+                meta: Some(
+                    TransactionStatusMeta {
+                        loaded_writable_addresses: select(LOADED_WRITABLE_ACCOUNT_INDEX_REGISTRY.as_slice()),
+                        loaded_readonly_addresses: select(LOADED_READONLY_ACCOUNT_INDEX_REGISTRY.as_slice()),
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            },
+        )?;
+        assert_recorded_pool(PoolDetector::detect(&detectable_transaction)?)
+    }
+}
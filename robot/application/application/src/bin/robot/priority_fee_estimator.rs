@@ -0,0 +1,159 @@
+use super::environment_configuration::PriorityFee;
+use super::error::{
+    Backtrace,
+    Error,
+    LockResultConverter,
+};
+use std::collections::VecDeque;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+pub static PRIORITY_FEE_ESTIMATOR: PriorityFeeEstimator = PriorityFeeEstimator::new();
+// The price of the compute unit is estimated by the prices the recent Raydium AMM v4 transactions of the Geyser stream have
+// paid. The window slides by the quantity of the transactions, so it follows the load of the AMM rather than the time.
+pub struct PriorityFeeEstimator {
+    compute_unit_price_registry: Mutex<VecDeque<u64>>,
+}
+impl PriorityFeeEstimator {
+    const MICRO_LAMPORTS_IN_LAMPORT: u128 = 1000000;
+    const PERCENTS_IN_WHOLE: u128 = 100;
+    const fn new() -> Self {
+        Self {
+            compute_unit_price_registry: Mutex::new(VecDeque::new()),
+        }
+    }
+    // The 'compute_unit_price' is in micro-lamports, zero for the transaction that has not set it.
+    pub fn observe<'a>(&'a self, priority_fee: &'a PriorityFee, compute_unit_price: u64) -> Result<(), Error> {
+        let mut compute_unit_price_registry = self.lock()?;
+        compute_unit_price_registry.push_back(compute_unit_price);
+        '_a: while compute_unit_price_registry.len() > priority_fee.window_transactions_quantity {
+            let _ = compute_unit_price_registry.pop_front();
+        }
+        Ok(())
+    }
+    // The percentile of the window is bounded by the minimum and the maximum price, then it is lowered so the swap does not
+    // spend more than is left of the priority fee of the trade. The minimum price is used until anything has been observed.
+    pub fn estimate<'a>(&'a self, priority_fee: &'a PriorityFee, spent_priority_fee_lamports: u64) -> Result<ComputeBudget, Error> {
+        let mut compute_unit_price_registry = self.lock()?.iter().copied().collect::<Vec<u64>>();
+        compute_unit_price_registry.sort_unstable();
+        let mut compute_unit_price = if compute_unit_price_registry.is_empty() {
+            priority_fee.minimum_compute_unit_price
+        } else {
+            // The nearest rank.
+            let rank = (priority_fee.percentile as u128 * compute_unit_price_registry.len() as u128).div_ceil(Self::PERCENTS_IN_WHOLE) as usize;
+            compute_unit_price_registry[rank.saturating_sub(1)].max(priority_fee.minimum_compute_unit_price)
+        };
+        if priority_fee.maximum_compute_unit_price != 0 {
+            compute_unit_price = compute_unit_price.min(priority_fee.maximum_compute_unit_price);
+        }
+        if priority_fee.maximum_trade_priority_fee_lamports != 0 {
+            let left_priority_fee_lamports = priority_fee.maximum_trade_priority_fee_lamports.saturating_sub(spent_priority_fee_lamports);
+            let affordable_compute_unit_price = left_priority_fee_lamports as u128 * Self::MICRO_LAMPORTS_IN_LAMPORT / priority_fee.compute_unit_limit as u128;
+            compute_unit_price = compute_unit_price.min(affordable_compute_unit_price.min(u64::MAX as u128) as u64);
+        }
+        Ok(
+            ComputeBudget {
+                compute_unit_limit: priority_fee.compute_unit_limit,
+                compute_unit_price,
+            },
+        )
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, VecDeque<u64>>, Error> {
+        self.compute_unit_price_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub compute_unit_limit: u32,
+    // In micro-lamports.
+    pub compute_unit_price: u64,
+}
+impl ComputeBudget {
+    // The priority fee is charged for the requested compute units, not for the consumed ones.
+    pub fn get_priority_fee_lamports(&self) -> u64 {
+        let priority_fee_lamports = (self.compute_unit_price as u128 * self.compute_unit_limit as u128).div_ceil(PriorityFeeEstimator::MICRO_LAMPORTS_IN_LAMPORT);
+        priority_fee_lamports.min(u64::MAX as u128) as u64
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn estimate_percentile_of_window() -> Result<(), Error> {
+        let priority_fee_estimator = PriorityFeeEstimator::new();
+        let priority_fee = PriorityFee {
+            compute_unit_limit: 100000,
+            percentile: 75,
+            window_transactions_quantity: 4,
+            minimum_compute_unit_price: 1000,
+            maximum_compute_unit_price: 500000,
+            maximum_trade_priority_fee_lamports: 0,
+        };
+        assert_eq!(priority_fee_estimator.estimate(&priority_fee, 0)?.compute_unit_price, 1000);
+        // The first observation slides out of the window of four.
+        '_a: for compute_unit_price in [900000, 0, 40000, 10000, 20000] {
+            priority_fee_estimator.observe(&priority_fee, compute_unit_price)?;
+        }
+        assert_eq!(
+            priority_fee_estimator.estimate(&priority_fee, 0)?,
+            ComputeBudget {
+                compute_unit_limit: 100000,
+                compute_unit_price: 20000,
+            },
+        );
+        priority_fee_estimator.observe(&priority_fee, 900000)?;
+        assert_eq!(priority_fee_estimator.estimate(&priority_fee, 0)?.compute_unit_price, 40000);
+        Ok(())
+    }
+    #[test]
+    fn bound_compute_unit_price() -> Result<(), Error> {
+        let priority_fee_estimator = PriorityFeeEstimator::new();
+        let priority_fee = PriorityFee {
+            compute_unit_limit: 100000,
+            percentile: 75,
+            window_transactions_quantity: 4,
+            minimum_compute_unit_price: 1000,
+            maximum_compute_unit_price: 500000,
+            maximum_trade_priority_fee_lamports: 0,
+        };
+        '_a: for _ in 0..4 {
+            priority_fee_estimator.observe(&priority_fee, 0)?;
+        }
+        assert_eq!(priority_fee_estimator.estimate(&priority_fee, 0)?.compute_unit_price, 1000);
+        '_a: for _ in 0..4 {
+            priority_fee_estimator.observe(&priority_fee, 900000)?;
+        }
+        assert_eq!(priority_fee_estimator.estimate(&priority_fee, 0)?.compute_unit_price, 500000);
+        Ok(())
+    }
+    #[test]
+    fn limit_trade_priority_fee() -> Result<(), Error> {
+        let priority_fee_estimator = PriorityFeeEstimator::new();
+        let priority_fee = PriorityFee {
+            compute_unit_limit: 100000,
+            percentile: 75,
+            window_transactions_quantity: 4,
+            minimum_compute_unit_price: 1000,
+            maximum_compute_unit_price: 500000,
+            maximum_trade_priority_fee_lamports: 30000,
+        };
+        '_a: for _ in 0..4 {
+            priority_fee_estimator.observe(&priority_fee, 200000)?;
+        }
+        let compute_budget = priority_fee_estimator.estimate(&priority_fee, 0)?;
+        assert_eq!(compute_budget.compute_unit_price, 200000);
+        assert_eq!(compute_budget.get_priority_fee_lamports(), 20000);
+        let compute_budget_ = priority_fee_estimator.estimate(&priority_fee, compute_budget.get_priority_fee_lamports())?;
+        assert_eq!(compute_budget_.compute_unit_price, 100000);
+        assert_eq!(compute_budget_.get_priority_fee_lamports(), 10000);
+        // Once the priority fee of the trade has been spent, the swaps are sent without it.
+        assert_eq!(priority_fee_estimator.estimate(&priority_fee, 30000)?.compute_unit_price, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,485 @@
+use super::environment_configuration::{
+    QuoteMint,
+    Risk,
+};
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+use std::time::Instant;
+use super::workflow_data::{
+    Calculation,
+    WorkflowData,
+};
+pub static RISK_MANAGER: RiskManager = RiskManager::new();
+// Every entry is checked and reserved under the single lock, so the pools detected concurrently can not exceed the
+// limits together. The reservation lives as long as the trading task.
+pub struct RiskManager {
+    risk_state: Mutex<RiskState>,
+}
+impl RiskManager {
+    const SECONDS_IN_DAY: u64 = 86400;
+    pub const fn new() -> Self {
+        Self {
+            risk_state: Mutex::new(
+                RiskState {
+                    exposure_registry: BTreeMap::new(),
+                    creator_entry_registry: BTreeMap::new(),
+                    realized_pnl_day: 0,
+                    realized_pnl_registry: BTreeMap::new(),
+                },
+            ),
+        }
+    }
+    // The 'quote_mint' is the element of the 'quote_mint_registry' the pool is quoted in. The 'timestamp' is the Unix
+    // time in seconds, the realized loss is counted per UTC day.
+    pub fn approve<'a>(
+        &'static self,
+        risk: &'a Risk,
+        quote_mint_registry: &'a [QuoteMint],
+        quote_mint: &'a QuoteMint,
+        for_entry: &'a ForEntry,
+        now: Instant,
+        timestamp: u64,
+    ) -> Result<RiskDecision, Error> {
+        let mut risk_state = self.lock()?;
+        if risk_state.exposure_registry.contains_key(&for_entry.amm_market_pubkey) {
+            return Ok(RiskDecision::Rejected(RiskRejectionReason::DuplicatePool));
+        }
+        risk_state.roll_realized_pnl_day(timestamp);
+        // The loss in any of the mints stops all the entries.
+        '_a: for quote_mint_ in quote_mint_registry.iter() {
+            if quote_mint_.daily_realized_loss_limit_pc_amount == 0 {
+                continue '_a;
+            }
+            if let Some(realized_pnl_pc_amount) = risk_state.realized_pnl_registry.get(&quote_mint_.mint_pubkey) {
+                if -*realized_pnl_pc_amount >= quote_mint_.daily_realized_loss_limit_pc_amount as i128 {
+                    return Ok(RiskDecision::Rejected(RiskRejectionReason::DailyRealizedLossLimit));
+                }
+            }
+        }
+        if risk.maximum_concurrent_positions_quantity != 0 && risk_state.exposure_registry.len() >= risk.maximum_concurrent_positions_quantity {
+            return Ok(RiskDecision::Rejected(RiskRejectionReason::MaximumConcurrentPositions));
+        }
+        if !risk.creator_cooldown_duration.is_zero() {
+            risk_state.creator_entry_registry.retain(
+                |_: _, entry_instant: _| -> _ {
+                    now.saturating_duration_since(*entry_instant) < risk.creator_cooldown_duration
+                }
+            );
+            if risk_state.creator_entry_registry.contains_key(&for_entry.creator_pubkey) {
+                return Ok(RiskDecision::Rejected(RiskRejectionReason::CreatorCooldown));
+            }
+        }
+        if quote_mint.minimum_initial_liquidity_pc_amount != 0 && for_entry.initial_liquidity_pc_amount < quote_mint.minimum_initial_liquidity_pc_amount {
+            return Ok(RiskDecision::Rejected(RiskRejectionReason::InsufficientLiquidity));
+        }
+        if quote_mint.maximum_initial_liquidity_pc_amount != 0 && for_entry.initial_liquidity_pc_amount > quote_mint.maximum_initial_liquidity_pc_amount {
+            return Ok(RiskDecision::Rejected(RiskRejectionReason::ExcessiveLiquidity));
+        }
+        let initial_pc_amount = if quote_mint.position_size_basis_points != 0 {
+            // Not more than the share of the pool, not more than the configured amount.
+            let position_size_pc_amount = for_entry.initial_liquidity_pc_amount as u128 * quote_mint.position_size_basis_points as u128 / WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE as u128;
+            (quote_mint.initial_pc_amount as u128).min(position_size_pc_amount) as u64
+        } else {
+            quote_mint.initial_pc_amount
+        };
+        if initial_pc_amount == 0 {
+            return Ok(RiskDecision::Rejected(RiskRejectionReason::ZeroPositionSize));
+        }
+        if quote_mint.maximum_exposure_pc_amount != 0 {
+            let exposure_pc_amount = risk_state.exposure_registry.values().filter(
+                |exposure: _| -> _ {
+                    exposure.quote_mint_pubkey == quote_mint.mint_pubkey
+                }
+            )
+            .map(
+                |exposure: _| -> _ {
+                    exposure.pc_amount as u128
+                }
+            )
+            .sum::<u128>();
+            if exposure_pc_amount + initial_pc_amount as u128 > quote_mint.maximum_exposure_pc_amount as u128 {
+                return Ok(RiskDecision::Rejected(RiskRejectionReason::MaximumExposure));
+            }
+        }
+        if !risk.creator_cooldown_duration.is_zero() {
+            let _ = risk_state.creator_entry_registry.insert(
+                for_entry.creator_pubkey,
+                now,
+            );
+        }
+        let _ = risk_state.exposure_registry.insert(
+            for_entry.amm_market_pubkey,
+            Exposure {
+                quote_mint_pubkey: quote_mint.mint_pubkey,
+                pc_amount: initial_pc_amount,
+            },
+        );
+        Ok(
+            RiskDecision::Approved {
+                initial_pc_amount,
+                risk_guard: RiskGuard {
+                    risk_manager: self,
+                    amm_market_pubkey: for_entry.amm_market_pubkey,
+                },
+            },
+        )
+    }
+    // The position that already exists, for example the one resumed from the trade journal, is reserved without the checks.
+    pub fn reserve<'a>(&'static self, amm_market_pubkey: &'a Pubkey, quote_mint_pubkey: &'a Pubkey, pc_amount: u64) -> Result<RiskGuard, Error> {
+        let mut risk_state = self.lock()?;
+        if risk_state.exposure_registry.contains_key(amm_market_pubkey) {
+            return Err(
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        let _ = risk_state.exposure_registry.insert(
+            *amm_market_pubkey,
+            Exposure {
+                quote_mint_pubkey: *quote_mint_pubkey,
+                pc_amount,
+            },
+        );
+        Ok(
+            RiskGuard {
+                risk_manager: self,
+                amm_market_pubkey: *amm_market_pubkey,
+            },
+        )
+    }
+    // The realized PnL is kept in the memory only, so the loss of the current day is forgotten on the restart.
+    pub fn record_realized_pnl<'a>(
+        &'a self,
+        quote_mint_pubkey: &'a Pubkey,
+        entry_pc_amount: u64,
+        exit_pc_amount: u128,
+        timestamp: u64,
+    ) -> Result<(), Error> {
+        let mut risk_state = self.lock()?;
+        risk_state.roll_realized_pnl_day(timestamp);
+        *risk_state.realized_pnl_registry.entry(*quote_mint_pubkey).or_insert(0) += exit_pc_amount as i128 - entry_pc_amount as i128;
+        Ok(())
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, RiskState>, Error> {
+        self.risk_state.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct RiskState {
+    exposure_registry: BTreeMap<Pubkey, Exposure>,
+    // The last entry into a pool of the creator.
+    creator_entry_registry: BTreeMap<Pubkey, Instant>,
+    realized_pnl_day: u64,
+    realized_pnl_registry: BTreeMap<Pubkey, i128>,
+}
+impl RiskState {
+    fn roll_realized_pnl_day(&mut self, timestamp: u64) {
+        let day = timestamp / RiskManager::SECONDS_IN_DAY;
+        if day != self.realized_pnl_day {
+            self.realized_pnl_day = day;
+            self.realized_pnl_registry.clear();
+        }
+    }
+}
+struct Exposure {
+    quote_mint_pubkey: Pubkey,
+    pc_amount: u64,
+}
+pub struct ForEntry {
+    pub amm_market_pubkey: Pubkey,
+    // The wallet that has initialized the pool.
+    pub creator_pubkey: Pubkey,
+    // The initial reserve of the quote mint in the pool.
+    pub initial_liquidity_pc_amount: u64,
+}
+pub enum RiskDecision {
+    Approved {
+        // The amount of the quote mint to spend on the buy.
+        initial_pc_amount: u64,
+        risk_guard: RiskGuard,
+    },
+    Rejected(RiskRejectionReason),
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RiskRejectionReason {
+    DuplicatePool,
+    DailyRealizedLossLimit,
+    MaximumConcurrentPositions,
+    CreatorCooldown,
+    InsufficientLiquidity,
+    ExcessiveLiquidity,
+    ZeroPositionSize,
+    MaximumExposure,
+}
+impl RiskRejectionReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DuplicatePool => "duplicate_pool",
+            Self::DailyRealizedLossLimit => "daily_realized_loss_limit",
+            Self::MaximumConcurrentPositions => "maximum_concurrent_positions",
+            Self::CreatorCooldown => "creator_cooldown",
+            Self::InsufficientLiquidity => "insufficient_liquidity",
+            Self::ExcessiveLiquidity => "excessive_liquidity",
+            Self::ZeroPositionSize => "zero_position_size",
+            Self::MaximumExposure => "maximum_exposure",
+        }
+    }
+}
+// The exposure of the pool counts against the concurrent positions and the exposure limits while the guard is held. The
+// failed buy gives the exposure back on the drop the same way the sold position does.
+pub struct RiskGuard {
+    risk_manager: &'static RiskManager,
+    amm_market_pubkey: Pubkey,
+}
+impl Drop for RiskGuard {
+    fn drop(&mut self) {
+        if let Ok(mut risk_state) = self.risk_manager.lock() {
+            let _ = risk_state.exposure_registry.remove(&self.amm_market_pubkey);
+        }
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+    const TIMESTAMP: u64 = 1700000000;
+    fn get_rejection_reason(risk_decision: RiskDecision) -> Option<RiskRejectionReason> {
+        match risk_decision {
+            RiskDecision::Approved { .. } => None,
+            RiskDecision::Rejected(risk_rejection_reason) => Some(risk_rejection_reason),
+        }
+    }
+    fn create_risk(maximum_concurrent_positions_quantity: usize) -> (Risk, [QuoteMint; 1]) {
+        (
+            Risk {
+                maximum_concurrent_positions_quantity,
+                creator_cooldown_duration: Duration::from_secs(60),
+            },
+            [
+                QuoteMint {
+                    mint_pubkey: Pubkey::new_unique(),
+                    initial_pc_amount: 1000,
+                    maximum_exposure_pc_amount: 2500,
+                    daily_realized_loss_limit_pc_amount: 500,
+                    minimum_initial_liquidity_pc_amount: 10000,
+                    maximum_initial_liquidity_pc_amount: 1000000,
+                    position_size_basis_points: 500,
+                },
+            ],
+        )
+    }
+    #[test]
+    fn size_position_by_liquidity() -> Result<(), Error> {
+        static RISK_MANAGER_: RiskManager = RiskManager::new();
+        let (
+            risk,
+            quote_mint_registry,
+        ) = create_risk(2);
+        let quote_mint = &quote_mint_registry[0];
+        let now = Instant::now();
+        let for_entry = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 12000,
+        };
+        match RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP)? {
+            RiskDecision::Approved { initial_pc_amount, .. } => assert_eq!(initial_pc_amount, 600),
+            RiskDecision::Rejected(_) => panic!(),
+        }
+        // The configured amount is the upper bound.
+        let for_entry_ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        match RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry_, now, TIMESTAMP)? {
+            RiskDecision::Approved { initial_pc_amount, .. } => assert_eq!(initial_pc_amount, 1000),
+            RiskDecision::Rejected(_) => panic!(),
+        }
+        Ok(())
+    }
+    #[test]
+    fn reject_by_liquidity() -> Result<(), Error> {
+        static RISK_MANAGER_: RiskManager = RiskManager::new();
+        let (
+            risk,
+            quote_mint_registry,
+        ) = create_risk(2);
+        let quote_mint = &quote_mint_registry[0];
+        let now = Instant::now();
+        let for_entry = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 9999,
+        };
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP)?),
+            Some(RiskRejectionReason::InsufficientLiquidity),
+        );
+        let for_entry_ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 1000001,
+        };
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry_, now, TIMESTAMP)?),
+            Some(RiskRejectionReason::ExcessiveLiquidity),
+        );
+        Ok(())
+    }
+    #[test]
+    fn release_reservation_on_guard_drop() -> Result<(), Error> {
+        static RISK_MANAGER_: RiskManager = RiskManager::new();
+        let (
+            risk,
+            quote_mint_registry,
+        ) = create_risk(2);
+        let quote_mint = &quote_mint_registry[0];
+        let now = Instant::now();
+        let for_entry = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let for_entry_ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let for_entry__ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let risk_decision = RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP)?;
+        let _risk_decision = RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry_, now, TIMESTAMP)?;
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry__, now, TIMESTAMP)?),
+            Some(RiskRejectionReason::MaximumConcurrentPositions),
+        );
+        drop(risk_decision);
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry__, now, TIMESTAMP)?),
+            None,
+        );
+        Ok(())
+    }
+    #[test]
+    fn reject_by_exposure() -> Result<(), Error> {
+        static RISK_MANAGER_: RiskManager = RiskManager::new();
+        let (
+            risk,
+            quote_mint_registry,
+        ) = create_risk(0);
+        let quote_mint = &quote_mint_registry[0];
+        let now = Instant::now();
+        let for_entry = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let for_entry_ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let for_entry__ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let _risk_decision = RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP)?;
+        let _risk_decision_ = RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry_, now, TIMESTAMP)?;
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry__, now, TIMESTAMP)?),
+            Some(RiskRejectionReason::MaximumExposure),
+        );
+        // The smaller position still fits.
+        let for_entry___ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 10000,
+        };
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry___, now, TIMESTAMP)?),
+            None,
+        );
+        Ok(())
+    }
+    #[test]
+    fn reject_by_creator_cooldown() -> Result<(), Error> {
+        static RISK_MANAGER_: RiskManager = RiskManager::new();
+        let (
+            risk,
+            quote_mint_registry,
+        ) = create_risk(2);
+        let quote_mint = &quote_mint_registry[0];
+        let now = Instant::now();
+        let for_entry = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        let for_entry_ = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: for_entry.creator_pubkey,
+            initial_liquidity_pc_amount: 500000,
+        };
+        let _risk_decision = RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP)?;
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry_, now, TIMESTAMP)?),
+            Some(RiskRejectionReason::CreatorCooldown),
+        );
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry_, now + Duration::from_secs(60), TIMESTAMP)?),
+            None,
+        );
+        Ok(())
+    }
+    #[test]
+    fn trip_daily_realized_loss_limit() -> Result<(), Error> {
+        static RISK_MANAGER_: RiskManager = RiskManager::new();
+        let (
+            risk,
+            quote_mint_registry,
+        ) = create_risk(2);
+        let quote_mint = &quote_mint_registry[0];
+        let now = Instant::now();
+        let for_entry = ForEntry {
+            amm_market_pubkey: Pubkey::new_unique(),
+            creator_pubkey: Pubkey::new_unique(),
+            initial_liquidity_pc_amount: 500000,
+        };
+        RISK_MANAGER_.record_realized_pnl(&quote_mint.mint_pubkey, 1000, 600, TIMESTAMP)?;
+        RISK_MANAGER_.record_realized_pnl(&quote_mint.mint_pubkey, 1000, 900, TIMESTAMP)?;
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP)?),
+            Some(RiskRejectionReason::DailyRealizedLossLimit),
+        );
+        // The loss is forgotten on the next UTC day.
+        assert_eq!(
+            get_rejection_reason(RISK_MANAGER_.approve(&risk, &quote_mint_registry, quote_mint, &for_entry, now, TIMESTAMP + RiskManager::SECONDS_IN_DAY)?),
+            None,
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,10 @@
+use super::account_mailbox::{
+    AccountMailbox,
+    AccountMailboxReceiver,
+    AccountMailboxSender,
+};
 use super::environment_configuration::EnvironmentConfiguration;
-use std::{future::Future, sync::atomic::{AtomicBool, AtomicUsize, Ordering}, time::Duration};
+use std::{future::Future, sync::atomic::{AtomicBool, AtomicU64, Ordering}, time::Duration};
 use super::error::{
     Error,
     ResultConverter,
@@ -8,38 +13,140 @@ use super::error::{
 use ahash::RandomState;
 use std::collections::HashMap;
 use super::spawner::Spawner;
-use super::environment_configuration::Trade;
+use super::environment_configuration::{
+    GracefulShutdownPolicy,
+    Trade,
+};
 use spl_token::{solana_program::program_pack::Pack, state::Account};
 use {
     std::convert::TryFrom,
-    solana_sdk::pubkey::Pubkey,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::Signature,
+    },
 };
 use super::http_server::HttpServer;
+use super::audit_log::AuditLog;
+use super::metrics::{
+    Metrics,
+    PoolRejectionReason,
+    TransactionFailureStage,
+};
+use super::transaction_error_decoder::{
+    TransactionErrorDecoder,
+    TransactionFailureAction,
+    TransactionFailureReason,
+};
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use super::risk_manager::{
+    ForEntry,
+    RiskDecision,
+    RiskGuard,
+    RISK_MANAGER,
+};
 use super::capture::Capture;
 use super::grpc_server::GrpcServer;
 use crate::extern_source::{
-    RaydiumAmmInitializeInstruction2,
+    RaydiumAmmInfo,
+    SerumMarketState,
+    SerumOpenOrders,
+    SwapDirection,
     Calcaulator,
+    U128,
 };
 use super::error::{
     OptionConverter,
     Common,
 };
-use super::workflow_data::{
-    TransactionDifferentiation,
-    WorkflowData,
+use super::pool_detector::{
+    DetectableTransaction,
+    DetectedPool,
+    PoolDetector,
+};
+use super::priority_fee_estimator::PRIORITY_FEE_ESTIMATOR;
+use super::transaction_creator::TransactionCreator;
+use super::transaction_sender::TransactionSender;
+use super::entry_scheduler::{
+    EntryScheduler,
+    ENTRY_SCHEDULER,
+};
+use super::slot_clock::SLOT_CLOCK;
+use super::clock::CLOCK;
+use super::blockhash_cache::BLOCKHASH_CACHE;
+use super::unix_time::UnixTime;
+use super::token_screener::{
+    TokenScreener,
+    TokenVerdict,
+};
+use super::paper_trader::PAPER_TRADING_JOURNAL;
+use super::trade_task_registry::{
+    TradeTaskGuard,
+    TRADE_TASK_REGISTRY,
 };
-use yellowstone_grpc_proto::geyser::{SubscribeUpdateAccount, SubscribeUpdateTransaction};
+use super::trade_journal::{
+    JournaledPosition,
+    OrderSide,
+    TradeJournal,
+};
+use super::geyser_recorder::{
+    GeyserRecordReader,
+    GeyserRecorder,
+};
+use super::exit_engine::{
+    ExitEngine,
+    ExitReason,
+    ExitStrategy,
+    EXIT_STRATEGY_HOLDER,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    signer::Signer,
+    transaction::Transaction,
+};
+use std::time::Instant;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use std::sync::Arc;
+use yellowstone_grpc_proto::{
+    convert_from::create_tx_error,
+    geyser::{
+        subscribe_update::UpdateOneof,
+        SubscribeUpdateAccount,
+        SubscribeUpdateTransaction,
+        SubscribeUpdateTransactionStatus,
+        SubscribeRequest,
+    },
+};
+use solana_sdk::transaction::TransactionError;
 use tokio::sync::mpsc::{
     Receiver,
     Sender
 };
+use tokio::sync::oneshot::{
+    Receiver as OneshotReceiver,
+    Sender as OneshotSender,
+};
+use tokio::sync::watch::Sender as WatchSender;
 use tokio::signal::unix::SignalKind;
 static IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED: AtomicBool = AtomicBool::new(false);
-static TRADING_TASKS_QUANTITY: AtomicUsize = AtomicUsize::new(0);
+// New positions are not opened while it is set, the open ones are managed as usual.
+static IS_ENTRY_PAUSED: AtomicBool = AtomicBool::new(false);
 pub struct Robot;
 impl Robot {
-    pub fn start(environment_configuration: &'static EnvironmentConfiguration<Trade>) -> impl Future<Output = Result<(), Error>> + Send {
+    const TRANSACTION_EXPIRATION_CHECK_INTERVAL_MILLISECONDS: u64 = 2000;
+    const GRACEFUL_SHUTDOWN_CHECK_INTERVAL_SECONDS: u64 = 1;
+    const PREFLIGHT_SIMULATION_MAXIMUM_ATTEMPTS_QUANTITY: usize = 3;
+    // About a slot.
+    const PREFLIGHT_SIMULATION_RETRY_DELAY_MILLISECONDS: u64 = 400;
+    const FILLED_BASE_AMOUNT_REQUEST_ATTEMPTS_QUANTITY: usize = 5;
+    const FILLED_BASE_AMOUNT_REQUEST_RETRY_DELAY_MILLISECONDS: u64 = 400;
+    // The unconditional exit is not left to the next account update, the quiet pool may not have it for long.
+    const EXIT_RETRY_DELAY_MILLISECONDS: u64 = 1000;
+    // The Geyser stream is recorded into the file if the path is set.
+    pub fn start(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        geyser_record_file_path: Option<String>,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
         fn create_signal(signal_kind: SignalKind) -> Result<impl Future<Output = ()> + Send, Error> {
             let mut signal = tokio::signal::unix::signal(signal_kind).into_(
                 Backtrace::new(
@@ -53,6 +160,8 @@ impl Robot {
             Ok(signal_future)
         }
         async move {
+            let starting_instant = Instant::now();
+            EXIT_STRATEGY_HOLDER.set(ExitStrategy::from(&environment_configuration.subject.trading))?;
             let (
                 accumulate_trackable_account_sender,
                 mut accumulate_trackable_account_receiver,
@@ -65,9 +174,51 @@ impl Robot {
                 process_account_sender,
                 mut process_account_receiver,
             ) = tokio::sync::mpsc::channel::<ForAccountProcessing>(100000);
+            let (
+                track_transaction_sender,
+                mut track_transaction_receiver,
+            ) = tokio::sync::mpsc::channel::<ForTransactionTracking>(100);
+            let (
+                process_transaction_status_sender,
+                mut process_transaction_status_receiver,
+            ) = tokio::sync::mpsc::channel::<ForTransactionStatusProcessing>(1000);
+            Metrics::initialize()?;
+            let metrics = Metrics::get()?;
+            metrics.observe_channel_depth("accumulate_trackable_account", &accumulate_trackable_account_sender)?;
+            metrics.observe_channel_depth("remove_trackable_account", &remove_trackable_account_sender)?;
+            metrics.observe_channel_depth("process_account", &process_account_sender)?;
+            metrics.observe_channel_depth("track_transaction", &track_transaction_sender)?;
+            metrics.observe_channel_depth("process_transaction_status", &process_transaction_status_sender)?;
+            let (
+                subscribe_request_sender,
+                subscribe_request_receiver,
+            ) = tokio::sync::watch::channel::<SubscribeRequest>(
+                GrpcServer::create_subscribe_request(
+                    environment_configuration,
+                    vec![],
+                ),
+            );
+            let rpc_client = Arc::new(
+                RpcClient::new(
+                    environment_configuration.subject.solana_rpc.url.clone(),
+                ),
+            );
+            // Nothing is sent in the paper trading.
+            if !environment_configuration.subject.trading.is_paper_trading {
+                TransactionSender::initialize(
+                    TransactionSender::create(
+                        environment_configuration.subject.transaction_sending.backend_registry.as_slice(),
+                        rpc_client.clone(),
+                        &process_transaction_status_sender,
+                    )
+                    .await?,
+                )?;
+            }
             Spawner::spawn_tokio_non_blocking_task_into_background(
                 async move {
                     Robot::accumulate_trackable_account(
+                        environment_configuration,
+                        &subscribe_request_sender,
                         &mut accumulate_trackable_account_receiver,
                         &mut remove_trackable_account_receiver,
                         &mut process_account_receiver,
@@ -75,20 +226,75 @@ impl Robot {
                     .await
                 },
             );
+            let rpc_client_ = rpc_client.clone();
+            Spawner::spawn_tokio_non_blocking_task_into_background(
+                async move {
+                    Robot::track_transaction(
+                        rpc_client_.as_ref(),
+                        &mut track_transaction_receiver,
+                        &mut process_transaction_status_receiver,
+                    )
+                    .await
+                },
+            );
+            TradeJournal::initialize(environment_configuration.subject.trade_journal.database_file_path.as_str())?;
+            AuditLog::initialize(environment_configuration.subject.http_server.audit_log_file_path.as_str())?;
+            let unclosed_position_registry = TradeJournal::get()?.load_unclosed_positions()?;
+            if environment_configuration.subject.trading.is_paper_trading {
+                if !unclosed_position_registry.is_empty() {
+                    tracing::warn!(
+                        "{} journaled positions are not resumed in the paper trading.",
+                        unclosed_position_registry.len(),
+                    );
+                }
+            } else {
+                '_a: for (for_trade, journaled_position) in unclosed_position_registry.into_iter() {
+                    // The resumed positions are not checked, but are counted in the limits of the new ones.
+                    let risk_guard = RISK_MANAGER.reserve(
+                        &for_trade.amm_market_pubkey,
+                        &for_trade.quote_mint_pubkey,
+                        for_trade.initial_pc_amount,
+                    )?;
+                    Self::start_trade(
+                        environment_configuration,
+                        rpc_client.clone(),
+                        for_trade,
+                        Some(journaled_position),
+                        risk_guard,
+                        &accumulate_trackable_account_sender,
+                        remove_trackable_account_sender.clone(),
+                        track_transaction_sender.clone(),
+                    )
+                    .await?;
+                }
+            }
             let mpsc_receiver_guard = MpscReceiverGuard {
                 accumulate_trackable_account_sender: accumulate_trackable_account_sender.clone(),
                 remove_trackable_account_sender: remove_trackable_account_sender.clone(),
                 process_account_sender: process_account_sender.clone(),
+                track_transaction_sender: track_transaction_sender.clone(),
+                process_transaction_status_sender: process_transaction_status_sender.clone(),
+            };
+            let record_sender = match geyser_record_file_path {
+                Some(geyser_record_file_path_) => Some(GeyserRecorder::spawn(geyser_record_file_path_)?),
+                None => None,
             };
             let grpc_serving_future = GrpcServer::run(
                 environment_configuration,
+                rpc_client,
+                subscribe_request_receiver,
+                record_sender,
                 accumulate_trackable_account_sender,
                 remove_trackable_account_sender,
                 process_account_sender,
+                track_transaction_sender,
+                process_transaction_status_sender,
             );
             let http_serving_future = HttpServer::run(
                 environment_configuration,
+                starting_instant,
                 &IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED,
+                &IS_ENTRY_PAUSED,
             );
             let signal_interrupt_receiving_future = create_signal(SignalKind::interrupt())?;
             let signal_terminate_receiving_future = create_signal(SignalKind::terminate())?;
@@ -101,14 +307,13 @@ impl Robot {
             };
             let graceful_shutdown_process_future = async move {
                 'a: loop {
-                    if IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED.load(Ordering::Relaxed)
-                    && TRADING_TASKS_QUANTITY.load(Ordering::Relaxed) == 0 {
+                    if IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED.load(Ordering::Relaxed) {
                         break 'a;
                     } else {
-                        tokio::time::sleep(Duration::from_secs(10)).await
+                        tokio::time::sleep(Duration::from_secs(Self::GRACEFUL_SHUTDOWN_CHECK_INTERVAL_SECONDS)).await
                     }
                 }
-                Ok::<_, Error>(())
+                Self::complete_trading_tasks(environment_configuration).await
             };
             Spawner::spawn_tokio_non_blocking_task_into_background(grpc_serving_future);
             Spawner::spawn_tokio_non_blocking_task_into_background(http_serving_future);
@@ -136,159 +341,513 @@ impl Robot {
             Ok(())
         }
     }
+    fn complete_trading_tasks(environment_configuration: &'static EnvironmentConfiguration<Trade>) -> impl Future<Output = Result<(), Error>> + Send {
+        async move {
+            // Nothing has been bought for the pools waiting for the open time yet.
+            let cancelled_entries_quantity = ENTRY_SCHEDULER.cancel_all()?;
+            if cancelled_entries_quantity != 0 {
+                tracing::info!(
+                    "{} scheduled entries have been cancelled by the graceful shutdown.",
+                    cancelled_entries_quantity,
+                );
+            }
+            match environment_configuration.subject.graceful_shutdown.policy {
+                GracefulShutdownPolicy::Wait => {
+                    let _ = Self::wait_for_trading_tasks(None).await;
+                }
+                GracefulShutdownPolicy::WaitWithDeadline {
+                    deadline_duration,
+                } => {
+                    if !Self::wait_for_trading_tasks(Some(Instant::now() + deadline_duration)).await {
+                        tracing::warn!(
+                            "The graceful shutdown deadline has been reached. trading_tasks_quantity: {}, open_positions_quantity: {}.",
+                            TRADE_TASK_REGISTRY.get_trading_tasks_quantity(),
+                            TRADE_TASK_REGISTRY.get_open_positions_quantity()?,
+                        );
+                    }
+                }
+                GracefulShutdownPolicy::Liquidate {
+                    deadline_duration,
+                } => {
+                    if !Self::wait_for_trading_tasks(Some(Instant::now() + deadline_duration)).await {
+                        tracing::warn!(
+                            "The graceful shutdown deadline has been reached, the liquidation has been requested from {} trading tasks.",
+                            TRADE_TASK_REGISTRY.request_exit_all(ExitReason::Liquidation)?,
+                        );
+                        let _ = Self::wait_for_trading_tasks(None).await;
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+    // Returns false if the deadline has been reached before all trading tasks have been completed.
+    fn wait_for_trading_tasks(deadline: Option<Instant>) -> impl Future<Output = bool> + Send {
+        async move {
+            'a: loop {
+                if TRADE_TASK_REGISTRY.get_trading_tasks_quantity() == 0 {
+                    break 'a;
+                }
+                if let Some(deadline_) = deadline {
+                    if Instant::now() >= deadline_ {
+                        return false;
+                    }
+                }
+                tokio::time::sleep(Duration::from_secs(Self::GRACEFUL_SHUTDOWN_CHECK_INTERVAL_SECONDS)).await
+            }
+            true
+        }
+    }
+    // The recorded Geyser stream is processed in the order of receiving. Nothing can be signed or sent, so the paper
+    // trading is required. The time is taken from the records and nothing is requested from the network, so the same
+    // record gives the same trades on every replay.
+    pub fn replay(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        geyser_record_file_path: String,
+        is_original_pace: bool,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async move {
+            if !environment_configuration.subject.trading.is_paper_trading {
+                return Err(
+                    Error::new(
+                        "The replay is possible in the paper trading only.".into(),
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    ),
+                );
+            }
+            EXIT_STRATEGY_HOLDER.set(ExitStrategy::from(&environment_configuration.subject.trading))?;
+            let (
+                accumulate_trackable_account_sender,
+                mut accumulate_trackable_account_receiver,
+            ) = tokio::sync::mpsc::channel::<ForAccountTracking1>(100);
+            let (
+                remove_trackable_account_sender,
+                mut remove_trackable_account_receiver,
+            ) = tokio::sync::mpsc::channel::<ForAccountTracking2>(10);
+            let (
+                process_account_sender,
+                mut process_account_receiver,
+            ) = tokio::sync::mpsc::channel::<ForAccountProcessing>(100000);
+            let (
+                track_transaction_sender,
+                mut track_transaction_receiver,
+            ) = tokio::sync::mpsc::channel::<ForTransactionTracking>(100);
+            let (
+                process_transaction_status_sender,
+                mut process_transaction_status_receiver,
+            ) = tokio::sync::mpsc::channel::<ForTransactionStatusProcessing>(1000);
+            Metrics::initialize()?;
+            // There is no subscription to update, the requests are only kept for the consistency with the live run.
+            let (
+                subscribe_request_sender,
+                _subscribe_request_receiver,
+            ) = tokio::sync::watch::channel::<SubscribeRequest>(
+                GrpcServer::create_subscribe_request(
+                    environment_configuration,
+                    vec![],
+                ),
+            );
+            // The paper trading requests nothing once the token screening is skipped, the mock only stands in for the
+            // client, so a request added by mistake fails instead of reaching the network.
+            let rpc_client = Arc::new(RpcClient::new_mock("fails".to_string()));
+            Spawner::spawn_tokio_non_blocking_task_into_background(
+                async move {
+                    Robot::accumulate_trackable_account(
+                        environment_configuration,
+                        &subscribe_request_sender,
+                        &mut accumulate_trackable_account_receiver,
+                        &mut remove_trackable_account_receiver,
+                        &mut process_account_receiver,
+                    )
+                    .await
+                },
+            );
+            let rpc_client_ = rpc_client.clone();
+            Spawner::spawn_tokio_non_blocking_task_into_background(
+                async move {
+                    Robot::track_transaction(
+                        rpc_client_.as_ref(),
+                        &mut track_transaction_receiver,
+                        &mut process_transaction_status_receiver,
+                    )
+                    .await
+                },
+            );
+            let mut geyser_record_reader = GeyserRecordReader::new(geyser_record_file_path.as_str())?;
+            let mut first_record_receiving = Option::<(u64, Instant)>::None;
+            let mut replayed_records_quantity: u64 = 0;
+            let mut failed_records_quantity: u64 = 0;
+            '_a: while let Some(geyser_record) = geyser_record_reader.read()? {
+                if is_original_pace {
+                    match first_record_receiving {
+                        Some((first_record_receive_timestamp_microseconds, first_record_instant)) => {
+                            let offset = Duration::from_micros(
+                                geyser_record.receive_timestamp_microseconds.saturating_sub(first_record_receive_timestamp_microseconds),
+                            );
+                            tokio::time::sleep_until((first_record_instant + offset).into()).await;
+                        }
+                        None => {
+                            first_record_receiving = Some((geyser_record.receive_timestamp_microseconds, Instant::now()));
+                        }
+                    }
+                }
+                replayed_records_quantity += 1;
+                CLOCK.replay(geyser_record.receive_timestamp_microseconds)?;
+                // The updates are processed one by one, so the order of the processing is the order of the record.
+                let processing_result = match geyser_record.subscribe_update.update_oneof {
+                    Some(UpdateOneof::Account(subscribe_update_account)) => {
+                        Self::process_account(
+                            &subscribe_update_account,
+                            &process_account_sender,
+                        )
+                        .await
+                    }
+                    Some(UpdateOneof::Transaction(subscribe_update_transaction)) => {
+                        Self::process_transaction(
+                            environment_configuration,
+                            rpc_client.clone(),
+                            &subscribe_update_transaction,
+                            CLOCK.get_instant()?,
+                            true,
+                            &accumulate_trackable_account_sender,
+                            remove_trackable_account_sender.clone(),
+                            track_transaction_sender.clone(),
+                        )
+                        .await
+                    }
+                    Some(UpdateOneof::TransactionStatus(subscribe_update_transaction_status)) => {
+                        Self::process_transaction_status(
+                            &subscribe_update_transaction_status,
+                            &process_transaction_status_sender,
+                        )
+                        .await
+                    }
+                    _ => Ok(()),
+                };
+                // The record that can not be processed is reported and skipped, as it would be in the live run.
+                if let Err(error) = processing_result {
+                    failed_records_quantity += 1;
+                    tracing::warn!(
+                        "Replayed record has not been processed: {}. receive_timestamp_microseconds: {}.",
+                        &error,
+                        geyser_record.receive_timestamp_microseconds,
+                    );
+                }
+                // The replay runs on the single thread, so the tasks the record has woken run before the next record.
+                tokio::task::yield_now().await;
+            }
+            tracing::info!(
+                "Replay has been completed. replayed_records_quantity: {}, failed_records_quantity: {}.",
+                replayed_records_quantity,
+                failed_records_quantity,
+            );
+            tracing::info!(
+                "Paper trading report: {}.",
+                String::from_utf8_lossy(PAPER_TRADING_JOURNAL.create_report()?.as_slice()),
+            );
+            Ok(())
+        }
+    }
     pub fn process_transaction<'a>(
         environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: Arc<RpcClient>,
         subscribe_update_transaction: &'a SubscribeUpdateTransaction,
+        geyser_receiving_instant: Instant,
+        is_offline: bool,
         accumulate_trackable_account_sender: &'a Sender<ForAccountTracking1>,
         remove_trackable_account_sender: Sender<ForAccountTracking2>,
+        track_transaction_sender: Sender<ForTransactionTracking>,
     )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
         async move {
-            if !IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED.load(Ordering::Relaxed) {
-                let subscribe_update_transaction_info = subscribe_update_transaction
-                .transaction
-                .as_ref()
-                .into_value_does_not_exist(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
-                )?;
-                let transaction_status_meta = subscribe_update_transaction_info
-                .meta
-                .as_ref()
-                .into_value_does_not_exist(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
-                )?;
-                if transaction_status_meta.err.is_some() {
-                    return Ok(());
-                }
-                let message = subscribe_update_transaction_info
-                .transaction
-                .as_ref()
-                .into_value_does_not_exist(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
+            let subscribe_update_transaction_info = subscribe_update_transaction
+            .transaction
+            .as_ref()
+            .into_value_does_not_exist(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let transaction_status_meta = subscribe_update_transaction_info
+            .meta
+            .as_ref()
+            .into_value_does_not_exist(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            // The failed transactions are received for the trading account only.
+            if transaction_status_meta.err.is_some() {
+                let transaction_error = create_tx_error(transaction_status_meta.err.as_ref())
+                .map_err(
+                    |error: _| -> _ {
+                        Error::new(
+                            error.into(),
+                            Backtrace::new(
+                                line!(),
+                                file!(),
+                            ),
+                        )
+                    }
                 )?
-                .message
-                .as_ref()
                 .into_value_does_not_exist(
                     Backtrace::new(
                         line!(),
                         file!(),
                     ),
                 )?;
-                if message.instructions.len() != WorkflowData::<TransactionDifferentiation>::INSTRUCTIONS_QUANTITY {
+                let transaction_failure_reason = TransactionErrorDecoder::decode(
+                    &transaction_error,
+                    transaction_status_meta.log_messages.as_slice(),
+                );
+                Metrics::get()?.increment_failed_transactions(
+                    TransactionFailureStage::Landing,
+                    transaction_failure_reason,
+                );
+                tracing::warn!(
+                    "Transaction {} has landed failed. transaction_failure_reason: {:?}, transaction_error: {}.",
+                    Signature::try_from(subscribe_update_transaction_info.signature.as_slice()).into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?,
+                    transaction_failure_reason,
+                    &transaction_error,
+                );
+                return Ok(());
+            }
+            let detectable_transaction = DetectableTransaction::from_geyser(subscribe_update_transaction_info)?;
+            // Every transaction of the stream that has not failed calls the Raydium AMM v4.
+            PRIORITY_FEE_ESTIMATOR.observe(
+                &environment_configuration.subject.trading.priority_fee,
+                detectable_transaction.get_compute_unit_price()?,
+            )?;
+            let detected_pool = match PoolDetector::detect(&detectable_transaction)? {
+                Some(detected_pool_) => detected_pool_,
+                None => {
                     return Ok(());
                 }
-                let initialize_2_compiled_instruction = &message.instructions[
-                    WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_VECTOR_INDEX
-                ];
-                if message.account_keys[initialize_2_compiled_instruction.program_id_index as usize].as_slice() != WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY.to_bytes().as_slice()
-                || transaction_status_meta.inner_instructions.len() != WorkflowData::<TransactionDifferentiation>::INSTRUCTIONS_WITH_INNER_INSTRUCTIONS_QUANTITY
-                || transaction_status_meta.inner_instructions[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX].index as usize != WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_VECTOR_INDEX
-                || transaction_status_meta.inner_instructions[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX].instructions.len() != WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTIONS_QUANTITY
-                || transaction_status_meta.log_messages.len() < WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_LOG_VECTOR_INDEX + 1
-                || transaction_status_meta.log_messages[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_LOG_VECTOR_INDEX].as_bytes()[0..=47] != *WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_LOG_PATTERN
-                {
-                    return Ok(());
+            };
+            let metrics = Metrics::get()?;
+            metrics.increment_detected_pools();
+            if IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED.load(Ordering::Relaxed) {
+                metrics.increment_rejected_pools(PoolRejectionReason::GracefulShutdown);
+                return Ok(());
+            }
+            if IS_ENTRY_PAUSED.load(Ordering::Relaxed) {
+                metrics.increment_rejected_pools(PoolRejectionReason::EntryPaused);
+                return Ok(());
+            }
+            // The PC side is preferred as the quote, so the pools quoted in the usual way are traded as before.
+            let (
+                quote_mint,
+                buy_swap_direction,
+            ) = match environment_configuration.subject.trading.quote_mint_registry.iter().find(
+                |quote_mint: _| -> _ {
+                    quote_mint.mint_pubkey == detected_pool.amm_pc_mint_pubkey
                 }
-                let create_token_account_compiled_instruction = &transaction_status_meta.inner_instructions[
-                    WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX
-                ].instructions[
-                    WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_PC_VAULT_TOKEN_ACCOUNT_INITIALIZING_INSTRUCTION_VECTOR_INDEX
-                ];
-                if message.account_keys[create_token_account_compiled_instruction.program_id_index as usize].as_slice() != WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY.to_bytes().as_slice()
-                || message.account_keys[create_token_account_compiled_instruction.accounts[1] as usize].as_slice() != WorkflowData::<TransactionDifferentiation>::WRAPPED_SOL_TOKEN_ACCOUNT_PUBKEY.to_bytes().as_slice()
-                || create_token_account_compiled_instruction.accounts[0] != initialize_2_compiled_instruction.accounts[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_VAULT_PUBKEY_VECTOR_INDEX] {
-                    return Ok(());
+            ) {
+                Some(quote_mint_) => (quote_mint_, SwapDirection::PcToCoin),
+                None => {
+                    match environment_configuration.subject.trading.quote_mint_registry.iter().find(
+                        |quote_mint: _| -> _ {
+                            quote_mint.mint_pubkey == detected_pool.amm_coin_mint_pubkey
+                        }
+                    ) {
+                        Some(quote_mint_) => (quote_mint_, SwapDirection::CoinToPc),
+                        None => {
+                            metrics.increment_rejected_pools(PoolRejectionReason::UnsupportedQuoteMint);
+                            return Ok(());
+                        }
+                    }
                 }
-                let amm_market_pubkey = Pubkey::try_from(
-                    message.account_keys[
-                        initialize_2_compiled_instruction.accounts[
-                            WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_MARKET_PUBKEY_VECTOR_INDEX
-                        ] as usize
-                    ].as_slice()
-                )
-                .into_(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
-                )?;
-                let amm_coin_vault_pubkey = Pubkey::try_from(
-                    message.account_keys[
-                        initialize_2_compiled_instruction.accounts[
-                            WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_COIN_VAULT_PUBKEY_VECTOR_INDEX
-                        ] as usize
-                    ].as_slice()
-                )
-                .into_(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
-                )?;
-                let amm_pc_vault_pubkey = Pubkey::try_from(
-                    message.account_keys[
-                        initialize_2_compiled_instruction.accounts[
-                            WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_VAULT_PUBKEY_VECTOR_INDEX
-                        ] as usize
-                    ].as_slice()
-                )
-                .into_(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
-                )?;
-                let raydium_amm_initialize_instruction_2 = RaydiumAmmInitializeInstruction2::unpack(
-                    initialize_2_compiled_instruction.data.as_slice(),
-                )?;
-                let (
-                    trade_trackable_account_sender,
-                    mut trade_trackable_account_receiver,
-                ) = tokio::sync::mpsc::channel::<ForAccountProcessing>(10);
-                let amm_coin_vault_pubkey_ = amm_coin_vault_pubkey.clone();
-                let amm_pc_vault_pubkey_ = amm_pc_vault_pubkey.clone();
-                Spawner::spawn_tokio_non_blocking_task_into_background(
-                    async move {
-                        Self::trade(
-                            environment_configuration,
-                            ForTrade {
-                                amm_market_pubkey,
-                                amm_coin_vault_pubkey: amm_coin_vault_pubkey_,
-                                init_coin_amount: raydium_amm_initialize_instruction_2.init_coin_amount,
-                                amm_pc_vault_pubkey: amm_pc_vault_pubkey_,
-                                init_pc_amount: raydium_amm_initialize_instruction_2.init_pc_amount,
-                            },
-                            &mut trade_trackable_account_receiver,
-                            &remove_trackable_account_sender,
-                        ).await
-                    },
+            };
+            let (
+                quote_mint_pubkey,
+                base_mint_pubkey,
+            ) = match buy_swap_direction {
+                SwapDirection::PcToCoin => (detected_pool.amm_pc_mint_pubkey, detected_pool.amm_coin_mint_pubkey),
+                SwapDirection::CoinToPc => (detected_pool.amm_coin_mint_pubkey, detected_pool.amm_pc_mint_pubkey),
+            };
+            let DetectedPool {
+                amm_market_pubkey,
+                amm_authority_pubkey,
+                amm_open_orders_pubkey,
+                amm_target_orders_pubkey,
+                amm_coin_mint_pubkey,
+                amm_pc_mint_pubkey,
+                amm_coin_vault_pubkey,
+                amm_pc_vault_pubkey,
+                serum_program_pubkey,
+                serum_market_pubkey,
+                creator_pubkey,
+                raydium_amm_initialize_instruction_2,
+            } = detected_pool;
+            let open_delay = EntryScheduler::get_open_delay(raydium_amm_initialize_instruction_2.open_time)?;
+            if environment_configuration.subject.trading.maximum_open_time_delay_seconds != 0
+            && open_delay > Duration::from_secs(environment_configuration.subject.trading.maximum_open_time_delay_seconds) {
+                tracing::info!(
+                    "Pool has been rejected for the open time: {}. amm_market_pubkey: {}.",
+                    raydium_amm_initialize_instruction_2.open_time,
+                    &amm_market_pubkey,
                 );
-                accumulate_trackable_account_sender.send(
-                    ForAccountTracking1 {
-                        amm_coin_vault_pubkey,
-                        amm_pc_vault_pubkey,
-                        trade_trackable_account_sender,
-                    },
-                )
-                .await
-                .into_(
-                    Backtrace::new(
-                        line!(),
-                        file!(),
-                    ),
-                )?;
+                metrics.increment_rejected_pools(PoolRejectionReason::OpenTimeTooFar);
+                return Ok(());
             }
+            let initial_liquidity_pc_amount = match buy_swap_direction {
+                SwapDirection::PcToCoin => raydium_amm_initialize_instruction_2.init_pc_amount,
+                SwapDirection::CoinToPc => raydium_amm_initialize_instruction_2.init_coin_amount,
+            };
+            let (
+                initial_pc_amount,
+                risk_guard,
+            ) = match RISK_MANAGER.approve(
+                &environment_configuration.subject.risk,
+                environment_configuration.subject.trading.quote_mint_registry.as_slice(),
+                quote_mint,
+                &ForEntry {
+                    amm_market_pubkey,
+                    creator_pubkey,
+                    initial_liquidity_pc_amount,
+                },
+                CLOCK.get_instant()?,
+                CLOCK.get_timestamp()?,
+            )? {
+                RiskDecision::Approved {
+                    initial_pc_amount: initial_pc_amount_,
+                    risk_guard: risk_guard_,
+                } => (initial_pc_amount_, risk_guard_),
+                RiskDecision::Rejected(risk_rejection_reason) => {
+                    tracing::info!(
+                        "Pool has been rejected by the risk manager: {}. amm_market_pubkey: {}, creator_pubkey: {}, initial_liquidity_pc_amount: {}.",
+                        risk_rejection_reason.as_str(),
+                        &amm_market_pubkey,
+                        &creator_pubkey,
+                        initial_liquidity_pc_amount,
+                    );
+                    metrics.increment_rejected_pools(PoolRejectionReason::Risk(risk_rejection_reason));
+                    return Ok(());
+                }
+            };
+            // The mint can not be loaded offline, so the replayed pool is not screened.
+            let token_verdict = if is_offline {
+                None
+            } else {
+                // The reservation is held while the mint is loaded, so the same pool is not screened twice.
+                let token_verdict_ = TokenScreener::screen(
+                    rpc_client.as_ref(),
+                    &environment_configuration.subject.trading.token_screening,
+                    &base_mint_pubkey,
+                    &creator_pubkey,
+                )
+                .await?;
+                if let Some(token_check) = token_verdict_.failed_check_registry.first() {
+                    tracing::info!(
+                        "Pool has been rejected by the token screening: {:?}. amm_market_pubkey: {}, base_mint_pubkey: {}.",
+                        &token_verdict_.failed_check_registry,
+                        &amm_market_pubkey,
+                        &base_mint_pubkey,
+                    );
+                    metrics.increment_rejected_pools(PoolRejectionReason::TokenScreening(*token_check));
+                    return Ok(());
+                }
+                Some(token_verdict_)
+            };
+            Self::start_trade(
+                environment_configuration,
+                rpc_client,
+                ForTrade {
+                    amm_market_pubkey,
+                    amm_authority_pubkey,
+                    amm_open_orders_pubkey,
+                    amm_target_orders_pubkey,
+                    amm_coin_mint_pubkey,
+                    amm_pc_mint_pubkey,
+                    amm_coin_vault_pubkey,
+                    init_coin_amount: raydium_amm_initialize_instruction_2.init_coin_amount,
+                    amm_pc_vault_pubkey,
+                    init_pc_amount: raydium_amm_initialize_instruction_2.init_pc_amount,
+                    serum_program_pubkey,
+                    serum_market_pubkey,
+                    quote_mint_pubkey,
+                    base_mint_pubkey,
+                    buy_swap_direction,
+                    initial_pc_amount,
+                    open_time: raydium_amm_initialize_instruction_2.open_time,
+                    geyser_receiving_instant,
+                    token_verdict,
+                    spent_priority_fee_lamports: AtomicU64::new(0),
+                },
+                None,
+                risk_guard,
+                accumulate_trackable_account_sender,
+                remove_trackable_account_sender,
+                track_transaction_sender,
+            )
+            .await?;
             Ok(())
         }
     }
+    // The accounts of the pool are registered for tracking after the task is spawned, so no update is lost.
+    fn start_trade<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: Arc<RpcClient>,
+        for_trade: ForTrade,
+        journaled_position: Option<JournaledPosition>,
+        risk_guard: RiskGuard,
+        accumulate_trackable_account_sender: &'a Sender<ForAccountTracking1>,
+        remove_trackable_account_sender: Sender<ForAccountTracking2>,
+        track_transaction_sender: Sender<ForTransactionTracking>,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let (
+                trade_trackable_account_sender,
+                mut trade_trackable_account_receiver,
+            ) = AccountMailbox::create::<ForAccountProcessing>();
+            // The task is registered before it is spawned, so the graceful shutdown can not miss it.
+            let trade_task_guard = TRADE_TASK_REGISTRY.register(&for_trade)?;
+            let amm_market_pubkey = for_trade.amm_market_pubkey;
+            let amm_open_orders_pubkey = for_trade.amm_open_orders_pubkey;
+            let amm_coin_vault_pubkey = for_trade.amm_coin_vault_pubkey;
+            let amm_pc_vault_pubkey = for_trade.amm_pc_vault_pubkey;
+            Spawner::spawn_tokio_non_blocking_task_into_background(
+                async move {
+                    Self::trade(
+                        environment_configuration,
+                        rpc_client.as_ref(),
+                        for_trade,
+                        journaled_position,
+                        trade_task_guard,
+                        risk_guard,
+                        &mut trade_trackable_account_receiver,
+                        &remove_trackable_account_sender,
+                        &track_transaction_sender,
+                    ).await
+                },
+            );
+            accumulate_trackable_account_sender.send(
+                ForAccountTracking1 {
+                    amm_market_pubkey,
+                    amm_open_orders_pubkey,
+                    amm_coin_vault_pubkey,
+                    amm_pc_vault_pubkey,
+                    trade_trackable_account_sender,
+                },
+            )
+            .await
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+        }
+    }
     pub fn process_account<'a>(
         subscribe_update_account: &'a SubscribeUpdateAccount,
         process_account_sender: &'a Sender<ForAccountProcessing>,
@@ -327,85 +886,1286 @@ impl Robot {
             )
         }
     }
+    pub fn process_transaction_status<'a>(
+        subscribe_update_transaction_status: &'a SubscribeUpdateTransactionStatus,
+        process_transaction_status_sender: &'a Sender<ForTransactionStatusProcessing>,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let signature = Signature::try_from(
+                subscribe_update_transaction_status.signature.as_slice()
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let transaction_error = create_tx_error(subscribe_update_transaction_status.err.as_ref()).map_err(
+                |error: _| -> _ {
+                    Error::new(
+                        error.into(),
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )
+                }
+            )?;
+            process_transaction_status_sender.send(
+                ForTransactionStatusProcessing {
+                    signature,
+                    slot: subscribe_update_transaction_status.slot,
+                    transaction_error,
+                },
+            )
+            .await
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+        }
+    }
     fn trade<'a>(
         environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
         for_trade: ForTrade,
-        trade_trackable_account_receiver: &'a mut Receiver<ForAccountProcessing>,
+        journaled_position: Option<JournaledPosition>,
+        mut trade_task_guard: TradeTaskGuard,
+        risk_guard: RiskGuard,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
         remove_trackable_account_sender: &'a Sender<ForAccountTracking2>,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
     )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
-
-        todo!("инкрементировать счетчик тасков.");
-
-
-
         async move {
-            let expected_coin_amount = match Calcaulator::get_coin_amount_from_pc_amount(
-                environment_configuration.subject.trading.initial_pc_amount,
-                for_trade.init_pc_amount,
-                for_trade.init_coin_amount,
-            ) {
-                Ok(expected_coin_amount_) => expected_coin_amount_,
-                Err(error) => {
-                    let _ = remove_trackable_account_sender.send(
-                        ForAccountTracking2 {
-                            amm_coin_vault_pubkey: for_trade.amm_coin_vault_pubkey,
-                            amm_pc_vault_pubkey: for_trade.amm_pc_vault_pubkey,
-                        }
+            let trading_result = match journaled_position {
+                Some(journaled_position_) => {
+                    Self::resume_trade_(
+                        environment_configuration,
+                        rpc_client,
+                        &for_trade,
+                        &journaled_position_,
+                        &mut trade_task_guard,
+                        trade_trackable_account_receiver,
+                        track_transaction_sender,
                     )
-                    .await;
+                    .await
+                }
+                None => {
+                    Self::trade_(
+                        environment_configuration,
+                        rpc_client,
+                        &for_trade,
+                        &mut trade_task_guard,
+                        trade_trackable_account_receiver,
+                        track_transaction_sender,
+                    )
+                    .await
+                }
+            };
+            // Удаление аккаунтов из отлеживаемых после завершения трейдинга на текущую пару монет
+            let _ = remove_trackable_account_sender.send(
+                ForAccountTracking2 {
+                    amm_market_pubkey: for_trade.amm_market_pubkey,
+                    amm_open_orders_pubkey: for_trade.amm_open_orders_pubkey,
+                    amm_coin_vault_pubkey: for_trade.amm_coin_vault_pubkey,
+                    amm_pc_vault_pubkey: for_trade.amm_pc_vault_pubkey,
+                }
+            )
+            .await;
+            // The task is unregistered only after its accounts are not tracked anymore.
+            drop(trade_task_guard);
+            drop(risk_guard);
+            trading_result
+        }
+    }
+    fn trade_<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+        trade_task_guard: &'a mut TradeTaskGuard,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let expected_base_amount = Calcaulator::get_amount_out(
+                for_trade.initial_pc_amount,
+                for_trade.init_pc_amount,
+                for_trade.init_coin_amount,
+                for_trade.buy_swap_direction,
+            )?;
+            // The pool has just been initialized, so its reserves are the init amounts until the first account update arrives.
+            let mut amm_reserve = AmmReserve {
+                coin_vault_amount: for_trade.init_coin_amount,
+                pc_vault_amount: for_trade.init_pc_amount,
+                need_take_pnl_coin_amount: 0,
+                need_take_pnl_pc_amount: 0,
+                open_orders_coin_amount: 0,
+                open_orders_pc_amount: 0,
+                is_order_book_enabled: false,
+            };
+            let open_delay = EntryScheduler::get_open_delay(for_trade.open_time)?;
+            if !open_delay.is_zero() && !Self::wait_for_open_time(
+                for_trade,
+                open_delay,
+                trade_trackable_account_receiver,
+                &mut amm_reserve,
+                trade_task_guard,
+            )
+            .await? {
+                return Ok(());
+            }
+            let entry_swap_quote = Self::quote_swap(
+                for_trade,
+                trade_trackable_account_receiver,
+                &mut amm_reserve,
+                for_trade.initial_pc_amount,
+                for_trade.buy_swap_direction,
+                environment_configuration.subject.trading.entry_slippage_basis_points,
+            )?;
+            if entry_swap_quote.is_price_impact_exceeded(environment_configuration.subject.trading.maximum_entry_price_impact_basis_points) {
+                tracing::info!(
+                    "Pool has been rejected for the price impact: {} basis points. amm_market_pubkey: {}.",
+                    entry_swap_quote.price_impact_basis_points,
+                    &for_trade.amm_market_pubkey,
+                );
+                Metrics::get()?.increment_rejected_pools(PoolRejectionReason::PriceImpactTooHigh);
+                return Ok(());
+            }
+            if environment_configuration.subject.trading.is_paper_trading {
+                return Self::paper_trade(
+                    for_trade,
+                    expected_base_amount,
+                    trade_trackable_account_receiver,
+                    &mut amm_reserve,
+                    trade_task_guard,
+                )
+                .await;
+            }
+            let trade_journal = TradeJournal::get()?;
+            let position_id = trade_journal.open_position(for_trade)?;
+            let serum_market = match Self::get_serum_market(
+                rpc_client,
+                for_trade,
+            )
+            .await {
+                Ok(serum_market_) => serum_market_,
+                Err(error) => {
+                    trade_journal.abandon_position(position_id)?;
                     return Err(error);
                 }
             };
-
-            todo!("create transaction for buy, Отслеживаение удачи этой транзакции");
-
-
+            let (
+                buy_transaction_signature,
+                buy_transaction_confirmation_receiver,
+                minimum_base_amount,
+            ) = match Self::buy(
+                environment_configuration,
+                rpc_client,
+                for_trade,
+                &serum_market,
+                position_id,
+                trade_trackable_account_receiver,
+                &mut amm_reserve,
+                track_transaction_sender,
+            )
+            .await {
+                Ok(buy_) => buy_,
+                Err(error) => {
+                    trade_journal.abandon_position(position_id)?;
+                    return Err(error);
+                }
+            };
+            tracing::info!(
+                "Buy transaction {} has been sent. amm_market_pubkey: {}, base_mint_pubkey: {}, expected_base_amount: {}.",
+                &buy_transaction_signature,
+                &for_trade.amm_market_pubkey,
+                &for_trade.base_mint_pubkey,
+                &expected_base_amount,
+            );
+            let buy_transaction_confirmation = Self::wait_for_transaction_confirmation(
+                buy_transaction_confirmation_receiver,
+                trade_trackable_account_receiver,
+                for_trade,
+                &mut amm_reserve,
+            )
+            .await?;
+            trade_journal.record_order_confirmation(
+                &buy_transaction_signature,
+                &buy_transaction_confirmation,
+            )?;
+            match buy_transaction_confirmation {
+                TransactionConfirmation::Landed {
+                    slot,
+                } => {
+                    tracing::info!(
+                        "Buy transaction {} has landed in slot {}.",
+                        &buy_transaction_signature,
+                        slot,
+                    );
+                }
+                TransactionConfirmation::Failed {
+                    slot,
+                    transaction_error,
+                } => {
+                    tracing::warn!(
+                        "Buy transaction {} has failed in slot {}: {}.",
+                        &buy_transaction_signature,
+                        slot,
+                        &transaction_error,
+                    );
+                    trade_journal.abandon_position(position_id)?;
+                    return Ok(());
+                }
+                TransactionConfirmation::Expired => {
+                    tracing::warn!(
+                        "Buy transaction {} has expired.",
+                        &buy_transaction_signature,
+                    );
+                    trade_journal.abandon_position(position_id)?;
+                    return Ok(());
+                }
+            }
+            let base_amount = Self::get_filled_base_amount(
+                rpc_client,
+                &TransactionCreator::get_associated_token_account_pubkey(
+                    &environment_configuration.subject.trading.private_key.pubkey(),
+                    &for_trade.base_mint_pubkey,
+                ),
+                minimum_base_amount,
+            )
+            .await;
+            trade_journal.record_fill(
+                &buy_transaction_signature,
+                base_amount,
+            )?;
+            trade_journal.mark_position_open(
+                position_id,
+                base_amount,
+            )?;
+            trade_task_guard.mark_position_open(
+                base_amount,
+                UnixTime::get_timestamp()?,
+            )?;
+            Metrics::get()?.increment_opened_trades();
+            Self::manage_position(
+                environment_configuration,
+                rpc_client,
+                for_trade,
+                &serum_market,
+                position_id,
+                base_amount,
+                Instant::now(),
+                trade_task_guard,
+                trade_trackable_account_receiver,
+                track_transaction_sender,
+                &mut amm_reserve,
+            )
+            .await
+        }
+    }
+    // The exit rules are evaluated on every update of the pool reserves until the position is sold.
+    fn manage_position<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        position_id: i64,
+        base_amount: u64,
+        opening_instant: Instant,
+        trade_task_guard: &'a mut TradeTaskGuard,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
+        amm_reserve: &'a mut AmmReserve,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let trade_journal = TradeJournal::get()?;
+            let mut exit_engine = ExitEngine::new(
+                &EXIT_STRATEGY_HOLDER.get()?,
+                for_trade.initial_pc_amount,
+                opening_instant,
+            )?;
+            let deadline = exit_engine.get_deadline();
+            let mut deadline_future = std::pin::pin!(
+                async move {
+                    match deadline {
+                        Some(deadline_) => tokio::time::sleep_until(deadline_.into()).await,
+                        None => std::future::pending::<()>().await,
+                    }
+                }
+            );
+            let mut is_deadline_reached = false;
+            let mut exit_retry_future = std::pin::pin!(tokio::time::sleep(Duration::ZERO));
+            let mut retried_exit_reason: Option<ExitReason> = None;
             'a: loop {
-                if let Some(for_account_processing) = trade_trackable_account_receiver.recv().await {
-                    let token_account = Account::unpack(for_account_processing.data.as_slice()).map_err(
-                        |_: _| -> _ {
-                            Error::new_(
-                                Common::UnreachableState,
+                let exit_reason = tokio::select! {
+                    for_account_processing = trade_trackable_account_receiver.recv() => {
+                        let for_account_processing_ = for_account_processing?;
+                        if !amm_reserve.update(
+                            for_trade,
+                            &for_account_processing_,
+                        )? {
+                            continue 'a;
+                        }
+                        let position_pc_amount = Calcaulator::get_amount_out(
+                            base_amount,
+                            amm_reserve.get_total_pc_amount_without_take_pnl()?,
+                            amm_reserve.get_total_coin_amount_without_take_pnl()?,
+                            for_trade.buy_swap_direction.reverse(),
+                        )?;
+                        trade_task_guard.mark(position_pc_amount)?;
+                        // The sell of the position the exit has been requested from is repeated until it lands.
+                        if let Some(requested_exit_reason) = trade_task_guard.get_requested_exit_reason() {
+                            requested_exit_reason
+                        } else {
+                            match exit_engine.evaluate(
+                                position_pc_amount,
+                                Instant::now(),
+                            )? {
+                                Some(exit_reason_) => exit_reason_,
+                                None => {
+                                    continue 'a;
+                                }
+                            }
+                        }
+                    }
+                    _ = deadline_future.as_mut(), if !is_deadline_reached => {
+                        is_deadline_reached = true;
+                        ExitReason::MaximumHoldingDuration
+                    }
+                    exit_reason_ = trade_task_guard.wait_for_exit_request(), if trade_task_guard.get_requested_exit_reason().is_none() => {
+                        exit_reason_
+                    }
+                    _ = exit_retry_future.as_mut(), if retried_exit_reason.is_some() => {
+                        retried_exit_reason.take().into_unreachable_state(
+                            Backtrace::new(
+                                line!(),
+                                file!(),
+                            ),
+                        )?
+                    }
+                };
+                retried_exit_reason = None;
+                // The position has to be closed whatever the price is after the deadline or the exit request, so the failed
+                // sell is repeated by the timer. The sell by the other exit rules is repeated only if the rules still hold on
+                // the next update.
+                let is_exit_unconditional = is_deadline_reached || trade_task_guard.get_requested_exit_reason().is_some();
+                // The realized PnL is valued at the reserves the sell is sent at.
+                let exit_pc_amount = Calcaulator::get_amount_out(
+                    base_amount,
+                    amm_reserve.get_total_pc_amount_without_take_pnl()?,
+                    amm_reserve.get_total_coin_amount_without_take_pnl()?,
+                    for_trade.buy_swap_direction.reverse(),
+                )?;
+                let (
+                    sell_transaction_signature,
+                    sell_transaction_confirmation_receiver,
+                ) = match Self::sell(
+                    environment_configuration,
+                    rpc_client,
+                    for_trade,
+                    serum_market,
+                    position_id,
+                    base_amount,
+                    trade_trackable_account_receiver,
+                    amm_reserve,
+                    track_transaction_sender,
+                )
+                .await {
+                    Ok(sell_) => sell_,
+                    Err(error) => {
+                        tracing::error!("{}", &error);
+                        if is_exit_unconditional {
+                            exit_retry_future.as_mut().reset((Instant::now() + Duration::from_millis(Self::EXIT_RETRY_DELAY_MILLISECONDS)).into());
+                            retried_exit_reason = Some(exit_reason);
+                        }
+                        continue 'a;
+                    }
+                };
+                tracing::info!(
+                    "Sell transaction {} has been sent. amm_market_pubkey: {}, exit_reason: {:?}.",
+                    &sell_transaction_signature,
+                    &for_trade.amm_market_pubkey,
+                    exit_reason,
+                );
+                let sell_transaction_confirmation = Self::wait_for_transaction_confirmation(
+                    sell_transaction_confirmation_receiver,
+                    trade_trackable_account_receiver,
+                    for_trade,
+                    amm_reserve,
+                )
+                .await?;
+                trade_journal.record_order_confirmation(
+                    &sell_transaction_signature,
+                    &sell_transaction_confirmation,
+                )?;
+                // A failed or expired sell keeps the position open, so the exit rules are evaluated again on the next update.
+                match sell_transaction_confirmation {
+                    TransactionConfirmation::Landed {
+                        slot,
+                    } => {
+                        tracing::info!(
+                            "Sell transaction {} has landed in slot {}.",
+                            &sell_transaction_signature,
+                            slot,
+                        );
+                        trade_journal.close_position(
+                            position_id,
+                            exit_reason,
+                        )?;
+                        Metrics::get()?.increment_closed_trades(
+                            for_trade.quote_mint_pubkey.to_string().as_str(),
+                            exit_reason,
+                            for_trade.initial_pc_amount,
+                            exit_pc_amount.as_u128(),
+                        );
+                        RISK_MANAGER.record_realized_pnl(
+                            &for_trade.quote_mint_pubkey,
+                            for_trade.initial_pc_amount,
+                            exit_pc_amount.as_u128(),
+                            UnixTime::get_timestamp()?,
+                        )?;
+                        break 'a;
+                    }
+                    TransactionConfirmation::Failed {
+                        slot,
+                        transaction_error,
+                    } => {
+                        tracing::warn!(
+                            "Sell transaction {} has failed in slot {}: {}.",
+                            &sell_transaction_signature,
+                            slot,
+                            &transaction_error,
+                        );
+                    }
+                    TransactionConfirmation::Expired => {
+                        tracing::warn!(
+                            "Sell transaction {} has expired.",
+                            &sell_transaction_signature,
+                        );
+                    }
+                }
+                if is_exit_unconditional {
+                    exit_retry_future.as_mut().reset((Instant::now() + Duration::from_millis(Self::EXIT_RETRY_DELAY_MILLISECONDS)).into());
+                    retried_exit_reason = Some(exit_reason);
+                }
+            }
+            Ok(())
+        }
+    }
+    // The position journaled before the restart is resumed at the current reserves, the holding duration counts from the
+    // original opening.
+    fn resume_trade_<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+        journaled_position: &'a JournaledPosition,
+        trade_task_guard: &'a mut TradeTaskGuard,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let trade_journal = TradeJournal::get()?;
+            let base_amount = match journaled_position.base_amount {
+                Some(base_amount_) => base_amount_,
+                None => {
+                    // The buy could land after it has been journaled, so the balance of the base mint decides.
+                    let base_amount_ = match Self::get_token_account_amount(
+                        rpc_client,
+                        &TransactionCreator::get_associated_token_account_pubkey(
+                            &environment_configuration.subject.trading.private_key.pubkey(),
+                            &for_trade.base_mint_pubkey,
+                        ),
+                    )
+                    .await {
+                        Ok(base_amount__) => base_amount__,
+                        Err(error) => {
+                            tracing::error!("{}", &error);
+                            0
+                        }
+                    };
+                    if base_amount_ == 0 {
+                        tracing::warn!(
+                            "Position {} has not been filled before the restart. amm_market_pubkey: {}.",
+                            journaled_position.position_id,
+                            &for_trade.amm_market_pubkey,
+                        );
+                        trade_journal.abandon_position(journaled_position.position_id)?;
+                        return Ok(());
+                    }
+                    trade_journal.mark_position_open(
+                        journaled_position.position_id,
+                        base_amount_,
+                    )?;
+                    base_amount_
+                }
+            };
+            trade_task_guard.mark_position_open(
+                base_amount,
+                journaled_position.opening_timestamp,
+            )?;
+            let serum_market = Self::get_serum_market(
+                rpc_client,
+                for_trade,
+            )
+            .await?;
+            let mut amm_reserve = Self::get_amm_reserve(
+                rpc_client,
+                for_trade,
+            )
+            .await?;
+            let now = Instant::now();
+            let holding_duration = Duration::from_secs(
+                UnixTime::get_timestamp()?.saturating_sub(journaled_position.opening_timestamp),
+            );
+            let opening_instant = now.checked_sub(holding_duration).unwrap_or(now);
+            tracing::info!(
+                "Position {} has been resumed. amm_market_pubkey: {}, base_mint_pubkey: {}, base_amount: {}.",
+                journaled_position.position_id,
+                &for_trade.amm_market_pubkey,
+                &for_trade.base_mint_pubkey,
+                base_amount,
+            );
+            Self::manage_position(
+                environment_configuration,
+                rpc_client,
+                for_trade,
+                &serum_market,
+                journaled_position.position_id,
+                base_amount,
+                opening_instant,
+                trade_task_guard,
+                trade_trackable_account_receiver,
+                track_transaction_sender,
+                &mut amm_reserve,
+            )
+            .await
+        }
+    }
+    // The position is opened at the reserves the pool has been initialized with and is closed by the same exit rules as
+    // the live one, at the reserves of the moment the rule fires.
+    fn paper_trade<'a>(
+        for_trade: &'a ForTrade,
+        expected_base_amount: U128,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        amm_reserve: &'a mut AmmReserve,
+        trade_task_guard: &'a mut TradeTaskGuard,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let base_amount = u64::try_from(expected_base_amount).ok().into_out_of_range(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            PAPER_TRADING_JOURNAL.open(
+                for_trade,
+                base_amount,
+            )?;
+            trade_task_guard.mark_position_open(
+                base_amount,
+                CLOCK.get_timestamp()?,
+            )?;
+            Metrics::get()?.increment_opened_trades();
+            tracing::info!(
+                "Paper position has been opened. amm_market_pubkey: {}, base_mint_pubkey: {}, quote_mint_pubkey: {}, entry_pc_amount: {}, base_amount: {}.",
+                &for_trade.amm_market_pubkey,
+                &for_trade.base_mint_pubkey,
+                &for_trade.quote_mint_pubkey,
+                for_trade.initial_pc_amount,
+                base_amount,
+            );
+            let mut exit_engine = ExitEngine::new(
+                &EXIT_STRATEGY_HOLDER.get()?,
+                for_trade.initial_pc_amount,
+                CLOCK.get_instant()?,
+            )?;
+            let deadline = exit_engine.get_deadline();
+            let mut deadline_future = std::pin::pin!(
+                async move {
+                    match deadline {
+                        Some(deadline_) => CLOCK.sleep_until(deadline_).await,
+                        None => std::future::pending::<Result<(), Error>>().await,
+                    }
+                }
+            );
+            // The branches are polled in order, so the replay closes the position by the same rule every time.
+            let exit_reason = 'a: loop {
+                tokio::select! {
+                    biased;
+                    for_account_processing = trade_trackable_account_receiver.recv() => {
+                        let for_account_processing_ = for_account_processing?;
+                        if !amm_reserve.update(
+                            for_trade,
+                            &for_account_processing_,
+                        )? {
+                            continue 'a;
+                        }
+                        let position_pc_amount = Calcaulator::get_amount_out(
+                            base_amount,
+                            amm_reserve.get_total_pc_amount_without_take_pnl()?,
+                            amm_reserve.get_total_coin_amount_without_take_pnl()?,
+                            for_trade.buy_swap_direction.reverse(),
+                        )?;
+                        PAPER_TRADING_JOURNAL.mark(
+                            &for_trade.amm_market_pubkey,
+                            position_pc_amount,
+                        )?;
+                        trade_task_guard.mark(position_pc_amount)?;
+                        if let Some(exit_reason_) = exit_engine.evaluate(
+                            position_pc_amount,
+                            CLOCK.get_instant()?,
+                        )? {
+                            break 'a exit_reason_;
+                        }
+                    }
+                    deadline_result = deadline_future.as_mut() => {
+                        deadline_result?;
+                        break 'a ExitReason::MaximumHoldingDuration;
+                    }
+                    exit_reason_ = trade_task_guard.wait_for_exit_request() => {
+                        break 'a exit_reason_;
+                    }
+                }
+            };
+            let paper_position = PAPER_TRADING_JOURNAL.close(
+                &for_trade.amm_market_pubkey,
+                exit_reason,
+            )?;
+            tracing::info!(
+                "Paper position has been closed. amm_market_pubkey: {}, exit_reason: {:?}, entry_pc_amount: {}, exit_pc_amount: {}.",
+                &for_trade.amm_market_pubkey,
+                exit_reason,
+                paper_position.entry_pc_amount,
+                paper_position.position_pc_amount,
+            );
+            Metrics::get()?.increment_closed_trades(
+                for_trade.quote_mint_pubkey.to_string().as_str(),
+                exit_reason,
+                paper_position.entry_pc_amount,
+                paper_position.position_pc_amount,
+            );
+            RISK_MANAGER.record_realized_pnl(
+                &for_trade.quote_mint_pubkey,
+                paper_position.entry_pc_amount,
+                paper_position.position_pc_amount,
+                CLOCK.get_timestamp()?,
+            )?;
+            Ok(())
+        }
+    }
+    // The buy is sent at the estimated start of the first slot after the open time, so it lands in the block the pool can be
+    // swapped in. Returns false if the entry has been cancelled by the graceful shutdown or the exit request.
+    fn wait_for_open_time<'a>(
+        for_trade: &'a ForTrade,
+        open_delay: Duration,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        amm_reserve: &'a mut AmmReserve,
+        trade_task_guard: &'a mut TradeTaskGuard,
+    )-> impl Future<Output = Result<bool, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let mut scheduled_entry_guard = ENTRY_SCHEDULER.schedule(&for_trade.amm_market_pubkey)?;
+            // The entry scheduled after the graceful shutdown has cancelled the rest is not waited for.
+            if IS_GRACEFUL_SHUTDOWN_COMMAND_RECEIVED.load(Ordering::Relaxed) {
+                return Ok(false);
+            }
+            let entry_instant = SLOT_CLOCK.align(CLOCK.get_instant()? + open_delay)?;
+            tracing::info!(
+                "Entry has been scheduled at the open time. amm_market_pubkey: {}, open_time: {}, open_delay_milliseconds: {}.",
+                &for_trade.amm_market_pubkey,
+                for_trade.open_time,
+                open_delay.as_millis(),
+            );
+            let mut entry_sleep = std::pin::pin!(CLOCK.sleep_until(entry_instant));
+            '_a: loop {
+                tokio::select! {
+                    biased;
+                    entry_sleep_result = entry_sleep.as_mut() => {
+                        entry_sleep_result?;
+                        return Ok(true);
+                    }
+                    for_account_processing = trade_trackable_account_receiver.recv() => {
+                        let for_account_processing_ = for_account_processing?;
+                        let _ = amm_reserve.update(
+                            for_trade,
+                            &for_account_processing_,
+                        )?;
+                    }
+                    _ = scheduled_entry_guard.wait_for_cancellation() => {
+                        tracing::info!(
+                            "Scheduled entry has been cancelled. amm_market_pubkey: {}.",
+                            &for_trade.amm_market_pubkey,
+                        );
+                        return Ok(false);
+                    }
+                    exit_reason = trade_task_guard.wait_for_exit_request() => {
+                        tracing::info!(
+                            "Scheduled entry has been cancelled by the exit request: {:?}. amm_market_pubkey: {}.",
+                            exit_reason,
+                            &for_trade.amm_market_pubkey,
+                        );
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+    }
+    // Pool account updates keep being consumed while the confirmation is awaited, so the account registry is never blocked on this task.
+    fn wait_for_transaction_confirmation<'a>(
+        transaction_confirmation_receiver: OneshotReceiver<TransactionConfirmation>,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        for_trade: &'a ForTrade,
+        amm_reserve: &'a mut AmmReserve,
+    )-> impl Future<Output = Result<TransactionConfirmation, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let mut transaction_confirmation_receiver_ = std::pin::pin!(transaction_confirmation_receiver);
+            '_a: loop {
+                tokio::select! {
+                    transaction_confirmation = transaction_confirmation_receiver_.as_mut() => {
+                        return transaction_confirmation.map_err(
+                            |_: _| -> _ {
+                                Error::new_(
+                                    Common::UnreachableState,
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )
+                            }
+                        );
+                    }
+                    for_account_processing = trade_trackable_account_receiver.recv() => {
+                        let for_account_processing_ = for_account_processing?;
+                        let _ = amm_reserve.update(
+                            for_trade,
+                            &for_account_processing_,
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+    // The market state does not change in a way that matters for the swap, so it is read once per trade.
+    fn get_serum_market<'a>(
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+    )-> impl Future<Output = Result<SerumMarket, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let serum_market_state = SerumMarketState::unpack(
+                rpc_client.get_account_data(&for_trade.serum_market_pubkey).await.into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?
+                .as_slice(),
+            )?;
+            let serum_vault_signer_pubkey = serum_market_state.get_vault_signer(
+                &for_trade.serum_market_pubkey,
+                &for_trade.serum_program_pubkey,
+            )?;
+            Ok(
+                SerumMarket {
+                    bids_pubkey: serum_market_state.bids,
+                    asks_pubkey: serum_market_state.asks,
+                    event_queue_pubkey: serum_market_state.event_queue,
+                    coin_vault_pubkey: serum_market_state.coin_vault,
+                    pc_vault_pubkey: serum_market_state.pc_vault,
+                    vault_signer_pubkey: serum_vault_signer_pubkey,
+                },
+            )
+        }
+    }
+    // The reserves of the resumed position are read once, then they are updated by the account updates as usual.
+    fn get_amm_reserve<'a>(
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+    )-> impl Future<Output = Result<AmmReserve, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let account_pubkey_registry = [
+                for_trade.amm_market_pubkey,
+                for_trade.amm_open_orders_pubkey,
+                for_trade.amm_coin_vault_pubkey,
+                for_trade.amm_pc_vault_pubkey,
+            ];
+            let account_registry = rpc_client.get_multiple_accounts(account_pubkey_registry.as_slice()).await.into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let mut amm_reserve = AmmReserve {
+                coin_vault_amount: 0,
+                pc_vault_amount: 0,
+                need_take_pnl_coin_amount: 0,
+                need_take_pnl_pc_amount: 0,
+                open_orders_coin_amount: 0,
+                open_orders_pc_amount: 0,
+                is_order_book_enabled: false,
+            };
+            '_a: for (account_pubkey, account) in account_pubkey_registry.into_iter().zip(account_registry.into_iter()) {
+                let account_ = account.into_value_does_not_exist(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?;
+                let _ = amm_reserve.update(
+                    for_trade,
+                    &ForAccountProcessing {
+                        account_pubkey,
+                        data: account_.data,
+                    },
+                )?;
+            }
+            Ok(amm_reserve)
+        }
+    }
+    fn get_token_account_amount<'a>(
+        rpc_client: &'a RpcClient,
+        token_account_pubkey: &'a Pubkey,
+    )-> impl Future<Output = Result<u64, Error>> + Send + Capture<&'a ()> {
+        async move {
+            rpc_client.get_token_account_balance_with_commitment(
+                token_account_pubkey,
+                CommitmentConfig::processed(),
+            )
+            .await
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .value
+            .amount
+            .parse::<u64>()
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+        }
+    }
+    // The bought tokens have landed, so the position is managed whatever the RPC answers. The balance is requested again while
+    // the RPC lags behind the Geyser, then the minimum amount out of the buy is taken, the landed swap has given at least it.
+    fn get_filled_base_amount<'a>(
+        rpc_client: &'a RpcClient,
+        base_token_account_pubkey: &'a Pubkey,
+        minimum_base_amount: u64,
+    )-> impl Future<Output = u64> + Send + Capture<&'a ()> {
+        async move {
+            '_a: for attempt in 1..=Self::FILLED_BASE_AMOUNT_REQUEST_ATTEMPTS_QUANTITY {
+                match Self::get_token_account_amount(
+                    rpc_client,
+                    base_token_account_pubkey,
+                )
+                .await {
+                    Ok(base_amount) if base_amount != 0 => {
+                        return base_amount;
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        tracing::warn!(
+                            "The filled base amount has not been received. attempt: {}. {}",
+                            attempt,
+                            &error,
+                        );
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(Self::FILLED_BASE_AMOUNT_REQUEST_RETRY_DELAY_MILLISECONDS)).await;
+            }
+            tracing::warn!(
+                "The filled base amount is taken as the minimum amount out of the buy: {}. base_token_account_pubkey: {}.",
+                minimum_base_amount,
+                base_token_account_pubkey,
+            );
+            minimum_base_amount
+        }
+    }
+    // The updates of the pool accounts received since the last look are applied first, so the swap is quoted at the latest
+    // reserves right before the signing.
+    fn quote_swap<'a>(
+        for_trade: &'a ForTrade,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        amm_reserve: &'a mut AmmReserve,
+        amount_in: u64,
+        swap_direction: SwapDirection,
+        slippage_basis_points: u64,
+    ) -> Result<SwapQuote, Error> {
+        '_a: while let Some(for_account_processing) = trade_trackable_account_receiver.try_recv()? {
+            let _ = amm_reserve.update(
+                for_trade,
+                &for_account_processing,
+            )?;
+        }
+        let total_pc_amount_without_take_pnl = amm_reserve.get_total_pc_amount_without_take_pnl()?;
+        let total_coin_amount_without_take_pnl = amm_reserve.get_total_coin_amount_without_take_pnl()?;
+        let amount_out = Calcaulator::get_amount_out(
+            amount_in,
+            total_pc_amount_without_take_pnl,
+            total_coin_amount_without_take_pnl,
+            swap_direction,
+        )?;
+        let (
+            total_amount_in_without_take_pnl,
+            total_amount_out_without_take_pnl,
+        ) = match swap_direction {
+            SwapDirection::PcToCoin => (total_pc_amount_without_take_pnl, total_coin_amount_without_take_pnl),
+            SwapDirection::CoinToPc => (total_coin_amount_without_take_pnl, total_pc_amount_without_take_pnl),
+        };
+        let price_impact_basis_points = Calcaulator::get_price_impact_basis_points(
+            amount_in,
+            u64::try_from(amount_out).ok().into_out_of_range(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+            total_amount_in_without_take_pnl,
+            total_amount_out_without_take_pnl,
+        )?;
+        Ok(
+            SwapQuote {
+                minimum_amount_out: Calcaulator::get_minimum_amount_out(
+                    amount_out,
+                    slippage_basis_points,
+                )?,
+                price_impact_basis_points,
+            },
+        )
+    }
+    // The minimum base amount the swap has been signed with is returned together with the signature.
+    fn buy<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        position_id: i64,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        amm_reserve: &'a mut AmmReserve,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
+    ) -> impl Future<Output = Result<(Signature, OneshotReceiver<TransactionConfirmation>, u64), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let compute_budget = PRIORITY_FEE_ESTIMATOR.estimate(
+                &environment_configuration.subject.trading.priority_fee,
+                for_trade.spent_priority_fee_lamports.load(Ordering::Relaxed),
+            )?;
+            let tip = TransactionSender::get()?.get_tip();
+            let (
+                transaction,
+                swap_quote,
+                last_valid_block_height,
+            ) = Self::create_swap_transaction(
+                environment_configuration,
+                rpc_client,
+                move |recent_blockhash: Hash| -> Result<(Transaction, SwapQuote), Error> {
+                    let swap_quote = Self::quote_swap(
+                        for_trade,
+                        trade_trackable_account_receiver,
+                        amm_reserve,
+                        for_trade.initial_pc_amount,
+                        for_trade.buy_swap_direction,
+                        environment_configuration.subject.trading.entry_slippage_basis_points,
+                    )?;
+                    if swap_quote.is_price_impact_exceeded(environment_configuration.subject.trading.maximum_entry_price_impact_basis_points) {
+                        return Err(
+                            Error::new(
+                                format!(
+                                    "The price impact of the buy is too high. price_impact_basis_points: {}.",
+                                    swap_quote.price_impact_basis_points,
+                                )
+                                .into(),
                                 Backtrace::new(
                                     line!(),
                                     file!(),
                                 ),
-                            )
-                        }
+                            ),
+                        );
+                    }
+                    let transaction = TransactionCreator::create_buy(
+                        &environment_configuration.subject.trading.private_key,
+                        for_trade,
+                        serum_market,
+                        for_trade.initial_pc_amount,
+                        swap_quote.minimum_amount_out,
+                        compute_budget,
+                        tip,
+                        recent_blockhash,
                     )?;
-
-                    todo!(" отслеживание аккаунтов для изменения профита. Транзакция на продажу.");
-
-
-
-
-
-                    // Удаление аккаунтов из отлеживаемых после завершения трейдинга на текущую пару монет
-                    let _ = remove_trackable_account_sender.send(
-                        ForAccountTracking2 {
-                            amm_coin_vault_pubkey: for_trade.amm_coin_vault_pubkey,
-                            amm_pc_vault_pubkey: for_trade.amm_pc_vault_pubkey,
-                        }
-                    )
-                    .await;
+                    Ok((transaction, swap_quote))
+                },
+            )
+            .await?;
+            let (
+                signature,
+                transaction_confirmation_receiver,
+            ) = Self::send_transaction(
+                &transaction,
+                last_valid_block_height,
+                track_transaction_sender,
+            )
+            .await?;
+            let _ = for_trade.spent_priority_fee_lamports.fetch_add(compute_budget.get_priority_fee_lamports(), Ordering::Relaxed);
+            Metrics::get()?.observe_geyser_receiving_to_sending_latency(for_trade.geyser_receiving_instant);
+            TradeJournal::get()?.record_order(
+                position_id,
+                OrderSide::Buy,
+                &signature,
+                for_trade.initial_pc_amount,
+                swap_quote.minimum_amount_out,
+            )?;
+            Ok((signature, transaction_confirmation_receiver, swap_quote.minimum_amount_out))
+        }
+    }
+    fn sell<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        position_id: i64,
+        base_amount: u64,
+        trade_trackable_account_receiver: &'a mut AccountMailboxReceiver<ForAccountProcessing>,
+        amm_reserve: &'a mut AmmReserve,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
+    ) -> impl Future<Output = Result<(Signature, OneshotReceiver<TransactionConfirmation>), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let compute_budget = PRIORITY_FEE_ESTIMATOR.estimate(
+                &environment_configuration.subject.trading.priority_fee,
+                for_trade.spent_priority_fee_lamports.load(Ordering::Relaxed),
+            )?;
+            let tip = TransactionSender::get()?.get_tip();
+            // The price impact of the sell is not limited, so the position can always be closed.
+            let (
+                transaction,
+                swap_quote,
+                last_valid_block_height,
+            ) = Self::create_swap_transaction(
+                environment_configuration,
+                rpc_client,
+                move |recent_blockhash: Hash| -> Result<(Transaction, SwapQuote), Error> {
+                    let swap_quote = Self::quote_swap(
+                        for_trade,
+                        trade_trackable_account_receiver,
+                        amm_reserve,
+                        base_amount,
+                        for_trade.buy_swap_direction.reverse(),
+                        environment_configuration.subject.trading.exit_slippage_basis_points,
+                    )?;
+                    let transaction = TransactionCreator::create_sell(
+                        &environment_configuration.subject.trading.private_key,
+                        for_trade,
+                        serum_market,
+                        base_amount,
+                        swap_quote.minimum_amount_out,
+                        compute_budget,
+                        tip,
+                        recent_blockhash,
+                    )?;
+                    Ok((transaction, swap_quote))
+                },
+            )
+            .await?;
+            let (
+                signature,
+                transaction_confirmation_receiver,
+            ) = Self::send_transaction(
+                &transaction,
+                last_valid_block_height,
+                track_transaction_sender,
+            )
+            .await?;
+            let _ = for_trade.spent_priority_fee_lamports.fetch_add(compute_budget.get_priority_fee_lamports(), Ordering::Relaxed);
+            TradeJournal::get()?.record_order(
+                position_id,
+                OrderSide::Sell,
+                &signature,
+                base_amount,
+                swap_quote.minimum_amount_out,
+            )?;
+            Ok((signature, transaction_confirmation_receiver))
+        }
+    }
+    // The swap is created again for every attempt of the pre-flight simulation, so the retried one is signed with the
+    // fresh blockhash and the adjusted one is quoted at the latest reserves.
+    fn create_swap_transaction<'a, F>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        rpc_client: &'a RpcClient,
+        mut create_transaction: F,
+    ) -> impl Future<Output = Result<(Transaction, SwapQuote, u64), Error>> + Send + Capture<&'a ()>
+    where
+        F: FnMut(Hash) -> Result<(Transaction, SwapQuote), Error> + Send + 'a,
+    {
+        async move {
+            let mut attempt: usize = 1;
+            '_a: loop {
+                let (
+                    recent_blockhash,
+                    last_valid_block_height,
+                ) = Self::get_latest_blockhash(rpc_client).await?;
+                let (
+                    transaction,
+                    swap_quote,
+                ) = create_transaction(recent_blockhash)?;
+                if !environment_configuration.subject.trading.is_preflight_simulation_enabled {
+                    return Ok((transaction, swap_quote, last_valid_block_height));
+                }
+                let transaction_failure_reason = match Self::simulate_transaction(
+                    rpc_client,
+                    &transaction,
+                )
+                .await? {
+                    Some(transaction_failure_reason_) => transaction_failure_reason_,
+                    None => {
+                        return Ok((transaction, swap_quote, last_valid_block_height));
+                    }
+                };
+                Metrics::get()?.increment_failed_transactions(
+                    TransactionFailureStage::Simulation,
+                    transaction_failure_reason,
+                );
+                let transaction_failure_action = transaction_failure_reason.get_action();
+                if transaction_failure_action == TransactionFailureAction::Abandon || attempt >= Self::PREFLIGHT_SIMULATION_MAXIMUM_ATTEMPTS_QUANTITY {
+                    return Err(
+                        Error::new(
+                            format!(
+                                "The swap has failed the simulation. transaction_failure_reason: {:?}, attempt: {}.",
+                                transaction_failure_reason,
+                                attempt,
+                            )
+                            .into(),
+                            Backtrace::new(
+                                line!(),
+                                file!(),
+                            ),
+                        ),
+                    );
+                }
+                tracing::warn!(
+                    "The swap has failed the simulation, it is created again. transaction_failure_reason: {:?}, attempt: {}.",
+                    transaction_failure_reason,
+                    attempt,
+                );
+                if transaction_failure_action == TransactionFailureAction::Retry {
+                    tokio::time::sleep(Duration::from_millis(Self::PREFLIGHT_SIMULATION_RETRY_DELAY_MILLISECONDS)).await;
                 }
+                attempt += 1;
             }
-            Ok(())
         }
     }
+    // Returns the reason of the failure, None if the simulation has succeeded. The blockhash is replaced by the node, so
+    // the blockhash the node has not received yet does not fail the simulation.
+    fn simulate_transaction<'a>(
+        rpc_client: &'a RpcClient,
+        transaction: &'a Transaction,
+    ) -> impl Future<Output = Result<Option<TransactionFailureReason>, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let rpc_simulate_transaction_result = rpc_client.simulate_transaction_with_config(
+                transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .value;
+            let transaction_error = match rpc_simulate_transaction_result.err {
+                Some(transaction_error_) => transaction_error_,
+                None => {
+                    return Ok(None);
+                }
+            };
+            let log_message_registry = match rpc_simulate_transaction_result.logs {
+                Some(log_message_registry_) => log_message_registry_,
+                None => vec![],
+            };
+            Ok(
+                Some(
+                    TransactionErrorDecoder::decode(
+                        &transaction_error,
+                        log_message_registry.as_slice(),
+                    ),
+                ),
+            )
+        }
+    }
+    // The blockhash is requested from the RPC only if the blockhash cache is stale.
+    fn get_latest_blockhash<'a>(rpc_client: &'a RpcClient) -> impl Future<Output = Result<(Hash, u64), Error>> + Send + Capture<&'a ()> {
+        async move {
+            if let Some(cached_blockhash) = BLOCKHASH_CACHE.get(Instant::now())? {
+                return Ok((cached_blockhash.blockhash, cached_blockhash.last_valid_block_height));
+            }
+            rpc_client.get_latest_blockhash_with_commitment(rpc_client.commitment()).await.into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )
+        }
+    }
+    // The transaction is registered for tracking before it is sent, so its status can not outrun the registration.
+    fn send_transaction<'a>(
+        transaction: &'a Transaction,
+        last_valid_block_height: u64,
+        track_transaction_sender: &'a Sender<ForTransactionTracking>,
+    ) -> impl Future<Output = Result<(Signature, OneshotReceiver<TransactionConfirmation>), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let (
+                transaction_confirmation_sender,
+                transaction_confirmation_receiver,
+            ) = tokio::sync::oneshot::channel::<TransactionConfirmation>();
+            track_transaction_sender.send(
+                ForTransactionTracking {
+                    signature: transaction.signatures[0],
+                    last_valid_block_height,
+                    transaction_confirmation_sender,
+                },
+            )
+            .await
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            TransactionSender::get()?.send(transaction).await?;
+            Ok((transaction.signatures[0], transaction_confirmation_receiver))
+        }
+    }
+    // Every change of the registry is pushed to the Geyser subscription, so only the tracked accounts are received.
     fn accumulate_trackable_account<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        subscribe_request_sender: &'a WatchSender<SubscribeRequest>,
         accumulate_trackable_account_receiver: &'a mut Receiver<ForAccountTracking1>,
         remove_trackable_account_receiver: &'a mut Receiver<ForAccountTracking2>,
         process_account_receiver: &'a mut Receiver<ForAccountProcessing>,
     )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
         async move {
-            let mut trackable_account_registry = HashMap::<Pubkey, Sender<ForAccountProcessing>, RandomState>::default();
+            let mut trackable_account_registry = HashMap::<Pubkey, AccountMailboxSender<ForAccountProcessing>, RandomState>::default();
             '_a: loop {
                 tokio::select! {
                     biased;
                     for_account_tracking = accumulate_trackable_account_receiver.recv() => {
                         match for_account_tracking {
                             Some(for_account_tracking_) => {
+                                let _ = trackable_account_registry.insert(
+                                    for_account_tracking_.amm_market_pubkey,
+                                    for_account_tracking_.trade_trackable_account_sender.clone(),
+                                );
+                                let _ = trackable_account_registry.insert(
+                                    for_account_tracking_.amm_open_orders_pubkey,
+                                    for_account_tracking_.trade_trackable_account_sender.clone(),
+                                );
                                 let _ = trackable_account_registry.insert(
                                     for_account_tracking_.amm_coin_vault_pubkey,
                                     for_account_tracking_.trade_trackable_account_sender.clone(),
@@ -414,6 +2174,11 @@ impl Robot {
                                     for_account_tracking_.amm_pc_vault_pubkey,
                                     for_account_tracking_.trade_trackable_account_sender,
                                 );
+                                Self::update_subscription(
+                                    environment_configuration,
+                                    subscribe_request_sender,
+                                    &trackable_account_registry,
+                                );
                             }
                             None => {
                                 return Err(
@@ -431,8 +2196,15 @@ impl Robot {
                     for_account_tracking = remove_trackable_account_receiver.recv() => {
                         match for_account_tracking {
                             Some(for_account_tracking_) => {
+                                let _ = trackable_account_registry.remove(&for_account_tracking_.amm_market_pubkey);
+                                let _ = trackable_account_registry.remove(&for_account_tracking_.amm_open_orders_pubkey);
                                 let _ = trackable_account_registry.remove(&for_account_tracking_.amm_coin_vault_pubkey);
                                 let _ = trackable_account_registry.remove(&for_account_tracking_.amm_pc_vault_pubkey);
+                                Self::update_subscription(
+                                    environment_configuration,
+                                    subscribe_request_sender,
+                                    &trackable_account_registry,
+                                );
                             }
                             None => {
                                 return Err(
@@ -450,20 +2222,32 @@ impl Robot {
                     for_account_processing = process_account_receiver.recv() => {
                         match for_account_processing {
                             Some(for_account_processing_) => {
-                                if let Some(trade_trackable_account_sender) = trackable_account_registry.get(
+                                let trade_trackable_account_sender = match trackable_account_registry.get(
                                     &for_account_processing_.account_pubkey,
                                 ) {
-                                    trade_trackable_account_sender.send(for_account_processing_).await.map_err(
-                                        |_: _| -> _ {
-                                            Error::new_(
-                                                Common::UnreachableState,
-                                                Backtrace::new(
-                                                    line!(),
-                                                    file!(),
-                                                ),
-                                            )
+                                    Some(trade_trackable_account_sender_) => trade_trackable_account_sender_,
+                                    None => {
+                                        continue '_a;
+                                    }
+                                };
+                                // The router never waits for a trade, so the trade that is busy with the RPC does not stall the
+                                // updates of the other ones. Every update carries the whole account data, so the update the
+                                // trade has not taken yet is overwritten by the newer one of the same account.
+                                if !trade_trackable_account_sender.send(
+                                    for_account_processing_.account_pubkey,
+                                    for_account_processing_,
+                                )? {
+                                    // The trade has been completed before its accounts have been removed.
+                                    trackable_account_registry.retain(
+                                        |_: _, trade_trackable_account_sender_: _| -> _ {
+                                            !trade_trackable_account_sender_.is_closed()
                                         }
-                                    )?
+                                    );
+                                    Self::update_subscription(
+                                        environment_configuration,
+                                        subscribe_request_sender,
+                                        &trackable_account_registry,
+                                    );
                                 }
                             }
                             None => {
@@ -484,20 +2268,178 @@ impl Robot {
             Ok(())
         }
     }
+    fn update_subscription<'a>(
+        environment_configuration: &'static EnvironmentConfiguration<Trade>,
+        subscribe_request_sender: &'a WatchSender<SubscribeRequest>,
+        trackable_account_registry: &'a HashMap<Pubkey, AccountMailboxSender<ForAccountProcessing>, RandomState>,
+    ) -> () {
+        let trackable_account_pubkey_registry = trackable_account_registry.keys().map(
+            |trackable_account_pubkey: _| -> _ {
+                trackable_account_pubkey.to_string()
+            }
+        )
+        .collect::<Vec<String>>();
+        // The receivers are never dropped before the registry, and the latest request is kept anyway.
+        let _ = subscribe_request_sender.send_replace(
+            GrpcServer::create_subscribe_request(
+                environment_configuration,
+                trackable_account_pubkey_registry,
+            ),
+        );
+    }
+    fn track_transaction<'a>(
+        rpc_client: &'a RpcClient,
+        track_transaction_receiver: &'a mut Receiver<ForTransactionTracking>,
+        process_transaction_status_receiver: &'a mut Receiver<ForTransactionStatusProcessing>,
+    )-> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let mut trackable_transaction_registry = HashMap::<Signature, ForTransactionTracking, RandomState>::default();
+            let mut expiration_check_interval = tokio::time::interval(
+                Duration::from_millis(Self::TRANSACTION_EXPIRATION_CHECK_INTERVAL_MILLISECONDS),
+            );
+            'a: loop {
+                tokio::select! {
+                    biased;
+                    for_transaction_tracking = track_transaction_receiver.recv() => {
+                        match for_transaction_tracking {
+                            Some(for_transaction_tracking_) => {
+                                let _ = trackable_transaction_registry.insert(
+                                    for_transaction_tracking_.signature,
+                                    for_transaction_tracking_,
+                                );
+                            }
+                            None => {
+                                return Err(
+                                    Error::new_(
+                                        Common::UnreachableState,
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                    for_transaction_status_processing = process_transaction_status_receiver.recv() => {
+                        match for_transaction_status_processing {
+                            Some(for_transaction_status_processing_) => {
+                                if let Some(for_transaction_tracking) = trackable_transaction_registry.remove(
+                                    &for_transaction_status_processing_.signature,
+                                ) {
+                                    let transaction_confirmation = match for_transaction_status_processing_.transaction_error {
+                                        Some(transaction_error) => TransactionConfirmation::Failed {
+                                            slot: for_transaction_status_processing_.slot,
+                                            transaction_error,
+                                        },
+                                        None => TransactionConfirmation::Landed {
+                                            slot: for_transaction_status_processing_.slot,
+                                        },
+                                    };
+                                    let _ = for_transaction_tracking.transaction_confirmation_sender.send(transaction_confirmation);
+                                }
+                            }
+                            None => {
+                                return Err(
+                                    Error::new_(
+                                        Common::UnreachableState,
+                                        Backtrace::new(
+                                            line!(),
+                                            file!(),
+                                        ),
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                    _ = expiration_check_interval.tick() => {
+                        if trackable_transaction_registry.is_empty() {
+                            continue 'a;
+                        }
+                        // The height is requested from the RPC only if the blockhash cache is stale.
+                        let block_height = match BLOCKHASH_CACHE.get(Instant::now())? {
+                            Some(cached_blockhash) => cached_blockhash.block_height,
+                            None => {
+                                match rpc_client.get_block_height().await {
+                                    Ok(block_height_) => block_height_,
+                                    Err(error) => {
+                                        tracing::warn!("{}", &error);
+                                        continue 'a;
+                                    }
+                                }
+                            }
+                        };
+                        let expired_signature_registry = trackable_transaction_registry
+                            .values()
+                            .filter(
+                                |for_transaction_tracking: _| -> _ {
+                                    for_transaction_tracking.last_valid_block_height < block_height
+                                }
+                            )
+                            .map(
+                                |for_transaction_tracking: _| -> _ {
+                                    for_transaction_tracking.signature
+                                }
+                            )
+                            .collect::<Vec<Signature>>();
+                        '_b: for expired_signature in expired_signature_registry.iter() {
+                            if let Some(for_transaction_tracking) = trackable_transaction_registry.remove(expired_signature) {
+                                let _ = for_transaction_tracking.transaction_confirmation_sender.send(TransactionConfirmation::Expired);
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
 }
 pub struct ForTrade {
-    amm_market_pubkey: Pubkey,
-    amm_coin_vault_pubkey: Pubkey,
-    init_coin_amount: u64,
-    amm_pc_vault_pubkey: Pubkey,
-    init_pc_amount: u64,
+    pub amm_market_pubkey: Pubkey,
+    pub amm_authority_pubkey: Pubkey,
+    pub amm_open_orders_pubkey: Pubkey,
+    pub amm_target_orders_pubkey: Pubkey,
+    pub amm_coin_mint_pubkey: Pubkey,
+    pub amm_pc_mint_pubkey: Pubkey,
+    pub amm_coin_vault_pubkey: Pubkey,
+    pub init_coin_amount: u64,
+    pub amm_pc_vault_pubkey: Pubkey,
+    pub init_pc_amount: u64,
+    pub serum_program_pubkey: Pubkey,
+    pub serum_market_pubkey: Pubkey,
+    pub quote_mint_pubkey: Pubkey,
+    pub base_mint_pubkey: Pubkey,
+    // The direction of the buy in the terms of the pool sides, the sell goes the reverse way.
+    pub buy_swap_direction: SwapDirection,
+    // The amount of the quote mint spent on the buy.
+    pub initial_pc_amount: u64,
+    // The Unix time in seconds the pool can be swapped from.
+    pub open_time: u64,
+    // The moment the pool initialization has been received from the Geyser.
+    pub geyser_receiving_instant: Instant,
+    // None for the position resumed from the trade journal, its verdict has been journaled on the opening.
+    pub token_verdict: Option<TokenVerdict>,
+    // The priority fee of the swaps that have been sent, the landed ones and the rest alike.
+    pub spent_priority_fee_lamports: AtomicU64,
+}
+pub struct SerumMarket {
+    pub bids_pubkey: Pubkey,
+    pub asks_pubkey: Pubkey,
+    pub event_queue_pubkey: Pubkey,
+    pub coin_vault_pubkey: Pubkey,
+    pub pc_vault_pubkey: Pubkey,
+    pub vault_signer_pubkey: Pubkey,
 }
 pub struct ForAccountTracking1 {
+    amm_market_pubkey: Pubkey,
+    amm_open_orders_pubkey: Pubkey,
     amm_coin_vault_pubkey: Pubkey,
     amm_pc_vault_pubkey: Pubkey,
-    trade_trackable_account_sender: Sender<ForAccountProcessing>
+    trade_trackable_account_sender: AccountMailboxSender<ForAccountProcessing>
 }
 pub struct ForAccountTracking2 {
+    amm_market_pubkey: Pubkey,
+    amm_open_orders_pubkey: Pubkey,
     amm_coin_vault_pubkey: Pubkey,
     amm_pc_vault_pubkey: Pubkey,
 }
@@ -505,8 +2447,130 @@ pub struct ForAccountProcessing {
     account_pubkey: Pubkey,
     data: Vec<u8>,
 }
+pub struct ForTransactionTracking {
+    signature: Signature,
+    last_valid_block_height: u64,
+    transaction_confirmation_sender: OneshotSender<TransactionConfirmation>,
+}
+pub struct ForTransactionStatusProcessing {
+    pub signature: Signature,
+    pub slot: u64,
+    pub transaction_error: Option<TransactionError>,
+}
+pub enum TransactionConfirmation {
+    Landed {
+        slot: u64,
+    },
+    Failed {
+        slot: u64,
+        transaction_error: TransactionError,
+    },
+    Expired,
+}
+struct SwapQuote {
+    // The expected amount out reduced by the slippage tolerance, the swap fails on chain if it gives less.
+    minimum_amount_out: u64,
+    price_impact_basis_points: u64,
+}
+impl SwapQuote {
+    // Zero disables the rule.
+    fn is_price_impact_exceeded<'a>(&'a self, maximum_price_impact_basis_points: u64) -> bool {
+        maximum_price_impact_basis_points != 0 && self.price_impact_basis_points > maximum_price_impact_basis_points
+    }
+}
+// https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/processor.rs
+struct AmmReserve {
+    coin_vault_amount: u64,
+    pc_vault_amount: u64,
+    need_take_pnl_coin_amount: u64,
+    need_take_pnl_pc_amount: u64,
+    open_orders_coin_amount: u64,
+    open_orders_pc_amount: u64,
+    is_order_book_enabled: bool,
+}
+impl AmmReserve {
+    // Returns false if the account is not one of the pool accounts.
+    fn update<'a>(&'a mut self, for_trade: &'a ForTrade, for_account_processing: &'a ForAccountProcessing) -> Result<bool, Error> {
+        if for_account_processing.account_pubkey == for_trade.amm_market_pubkey {
+            let raydium_amm_info = RaydiumAmmInfo::unpack(for_account_processing.data.as_slice())?;
+            self.need_take_pnl_coin_amount = raydium_amm_info.need_take_pnl_coin;
+            self.need_take_pnl_pc_amount = raydium_amm_info.need_take_pnl_pc;
+            self.is_order_book_enabled = raydium_amm_info.is_order_book_enabled();
+            return Ok(true);
+        }
+        if for_account_processing.account_pubkey == for_trade.amm_open_orders_pubkey {
+            let serum_open_orders = SerumOpenOrders::unpack(for_account_processing.data.as_slice())?;
+            self.open_orders_coin_amount = serum_open_orders.native_coin_total;
+            self.open_orders_pc_amount = serum_open_orders.native_pc_total;
+            return Ok(true);
+        }
+        let is_coin_vault = for_account_processing.account_pubkey == for_trade.amm_coin_vault_pubkey;
+        if !is_coin_vault && for_account_processing.account_pubkey != for_trade.amm_pc_vault_pubkey {
+            return Ok(false);
+        }
+        let token_account = Account::unpack(for_account_processing.data.as_slice()).map_err(
+            |_: _| -> _ {
+                Error::new_(
+                    Common::UnreachableState,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+            }
+        )?;
+        if is_coin_vault {
+            self.coin_vault_amount = token_account.amount;
+        } else {
+            self.pc_vault_amount = token_account.amount;
+        }
+        Ok(true)
+    }
+    fn get_total_coin_amount_without_take_pnl<'a>(&'a self) -> Result<u64, Error> {
+        Self::get_total_amount_without_take_pnl(
+            self.coin_vault_amount,
+            self.open_orders_coin_amount,
+            self.need_take_pnl_coin_amount,
+            self.is_order_book_enabled,
+        )
+    }
+    fn get_total_pc_amount_without_take_pnl<'a>(&'a self) -> Result<u64, Error> {
+        Self::get_total_amount_without_take_pnl(
+            self.pc_vault_amount,
+            self.open_orders_pc_amount,
+            self.need_take_pnl_pc_amount,
+            self.is_order_book_enabled,
+        )
+    }
+    // Raydium counts the open orders funds only while the pool is allowed to place orders on the order book.
+    fn get_total_amount_without_take_pnl(
+        vault_amount: u64,
+        open_orders_amount: u64,
+        need_take_pnl_amount: u64,
+        is_order_book_enabled: bool,
+    ) -> Result<u64, Error> {
+        let total_amount = if is_order_book_enabled {
+            vault_amount.checked_add(open_orders_amount).into_out_of_range(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+        } else {
+            vault_amount
+        };
+        total_amount.checked_sub(need_take_pnl_amount).into_out_of_range(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
 struct MpscReceiverGuard {
     accumulate_trackable_account_sender: Sender<ForAccountTracking1>,
     remove_trackable_account_sender: Sender<ForAccountTracking2>,
     process_account_sender: Sender<ForAccountProcessing>,
+    track_transaction_sender: Sender<ForTransactionTracking>,
+    process_transaction_status_sender: Sender<ForTransactionStatusProcessing>,
 }
\ No newline at end of file
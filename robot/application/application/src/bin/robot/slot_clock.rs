@@ -0,0 +1,120 @@
+use super::error::{
+    Backtrace,
+    Error,
+    LockResultConverter,
+};
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+pub static SLOT_CLOCK: SlotClock = SlotClock::new();
+// The start of the slots is estimated by the moments the new slots are received from the Geyser. The duration of the
+// slot is smoothed, because the updates arrive with the jitter and some slots are skipped by the cluster.
+pub struct SlotClock {
+    slot_observation: Mutex<SlotObservation>,
+}
+impl SlotClock {
+    const DEFAULT_SLOT_DURATION_MICROSECONDS: u64 = 400000;
+    const MINIMUM_SLOT_DURATION_MICROSECONDS: u64 = 100000;
+    const MAXIMUM_SLOT_DURATION_MICROSECONDS: u64 = 2000000;
+    // The weight of the last observation in the smoothed duration, in percents.
+    const SMOOTHING_PERCENTS: u64 = 10;
+    const fn new() -> Self {
+        Self {
+            slot_observation: Mutex::new(
+                SlotObservation {
+                    last_slot: None,
+                    slot_duration_microseconds: Self::DEFAULT_SLOT_DURATION_MICROSECONDS,
+                },
+            ),
+        }
+    }
+    // The slot that is not newer than the last observed one is ignored.
+    pub fn observe<'a>(&'a self, slot: u64, instant: Instant) -> Result<(), Error> {
+        let mut slot_observation = self.lock()?;
+        if let Some((last_slot, last_slot_instant)) = slot_observation.last_slot {
+            if slot <= last_slot {
+                return Ok(());
+            }
+            let slot_duration_microseconds = (instant.saturating_duration_since(last_slot_instant).as_micros() as u64 / (slot - last_slot)).clamp(
+                Self::MINIMUM_SLOT_DURATION_MICROSECONDS,
+                Self::MAXIMUM_SLOT_DURATION_MICROSECONDS,
+            );
+            slot_observation.slot_duration_microseconds = (
+                slot_observation.slot_duration_microseconds * (100 - Self::SMOOTHING_PERCENTS) + slot_duration_microseconds * Self::SMOOTHING_PERCENTS
+            ) / 100;
+        }
+        slot_observation.last_slot = Some((slot, instant));
+        Ok(())
+    }
+    // Returns the estimated start of the first slot that starts not earlier than the 'instant'. The 'instant' itself is
+    // returned until the first slot has been observed.
+    pub fn align<'a>(&'a self, instant: Instant) -> Result<Instant, Error> {
+        let slot_observation = self.lock()?;
+        let last_slot_instant = match slot_observation.last_slot {
+            Some((_, last_slot_instant_)) => last_slot_instant_,
+            None => {
+                return Ok(instant);
+            }
+        };
+        if instant <= last_slot_instant {
+            return Ok(instant);
+        }
+        let slot_duration_microseconds = slot_observation.slot_duration_microseconds as u128;
+        let slots_quantity = (instant.duration_since(last_slot_instant).as_micros() + slot_duration_microseconds - 1) / slot_duration_microseconds;
+        Ok(last_slot_instant + Duration::from_micros((slots_quantity * slot_duration_microseconds) as u64))
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, SlotObservation>, Error> {
+        self.slot_observation.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct SlotObservation {
+    last_slot: Option<(u64, Instant)>,
+    slot_duration_microseconds: u64,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn align_to_next_slot() -> Result<(), Error> {
+        let slot_clock = SlotClock::new();
+        let instant = Instant::now();
+        // Nothing to align to until the first slot is observed.
+        assert_eq!(slot_clock.align(instant + Duration::from_millis(100))?, instant + Duration::from_millis(100));
+        slot_clock.observe(302307654, instant)?;
+        assert_eq!(slot_clock.align(instant + Duration::from_millis(100))?, instant + Duration::from_millis(400));
+        assert_eq!(slot_clock.align(instant + Duration::from_millis(400))?, instant + Duration::from_millis(400));
+        assert_eq!(slot_clock.align(instant + Duration::from_millis(401))?, instant + Duration::from_millis(800));
+        // The moment that is not later than the last slot is not moved.
+        assert_eq!(slot_clock.align(instant)?, instant);
+        Ok(())
+    }
+    #[test]
+    fn smooth_slot_duration() -> Result<(), Error> {
+        let slot_clock = SlotClock::new();
+        let instant = Instant::now();
+        slot_clock.observe(302307654, instant)?;
+        // 500 milliseconds are weighted with 400 milliseconds by default.
+        slot_clock.observe(302307655, instant + Duration::from_millis(500))?;
+        assert_eq!(slot_clock.lock()?.slot_duration_microseconds, 410000);
+        // The old slot is ignored.
+        slot_clock.observe(302307654, instant + Duration::from_millis(600))?;
+        assert_eq!(slot_clock.lock()?.last_slot, Some((302307655, instant + Duration::from_millis(500))));
+        assert_eq!(slot_clock.align(instant + Duration::from_millis(501))?, instant + Duration::from_millis(910));
+        // The skipped slots are divided out, the burst of the slots is clamped to the minimum duration.
+        slot_clock.observe(302307657, instant + Duration::from_millis(1300))?;
+        assert_eq!(slot_clock.lock()?.slot_duration_microseconds, 409000);
+        slot_clock.observe(302307757, instant + Duration::from_millis(1301))?;
+        assert_eq!(slot_clock.lock()?.slot_duration_microseconds, 378100);
+        Ok(())
+    }
+}
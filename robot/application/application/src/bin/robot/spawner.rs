@@ -22,4 +22,11 @@ impl Spawner {
     {
         tokio::spawn(future)
     }
+    pub fn spawn_tokio_blocking_task_processed<F, T>(function: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        tokio::task::spawn_blocking(function)
+    }
 }
\ No newline at end of file
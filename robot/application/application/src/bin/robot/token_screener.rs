@@ -0,0 +1,439 @@
+use super::capture::Capture;
+use super::environment_configuration::TokenScreening;
+use super::error::{
+    Backtrace,
+    Error,
+    OptionConverter,
+    ResultConverter,
+};
+use super::workflow_data::{
+    Calculation,
+    TransactionCreation,
+    TransactionDifferentiation,
+    WorkflowData,
+};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::{
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+};
+use spl_token_2022::state::{
+    Account,
+    Mint,
+};
+use std::future::Future;
+// The base mint is screened before the buy. Only the enabled checks can reject the pool, but the verdict keeps all the
+// facts that have been read, so the journal shows them for every trade.
+pub struct TokenScreener;
+impl TokenScreener {
+    // The extensions that let the authority of the mint take or lock the tokens after the buy.
+    const RISKY_EXTENSION_TYPE_REGISTRY: [ExtensionType; 3] = [
+        ExtensionType::TransferFeeConfig,
+        ExtensionType::TransferHook,
+        ExtensionType::PermanentDelegate,
+    ];
+    // The creator supply is read from the associated token account of the creator only. The account is derived for
+    // both token programs, so the mint and the account are loaded by the single request.
+    pub fn screen<'a>(
+        rpc_client: &'a RpcClient,
+        token_screening: &'a TokenScreening,
+        base_mint_pubkey: &'a Pubkey,
+        creator_pubkey: &'a Pubkey,
+    )-> impl Future<Output = Result<TokenVerdict, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let account_pubkey_registry = [
+                *base_mint_pubkey,
+                Self::get_associated_token_account_pubkey(
+                    creator_pubkey,
+                    &WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+                    base_mint_pubkey,
+                ),
+                Self::get_associated_token_account_pubkey(
+                    creator_pubkey,
+                    &WorkflowData::<TransactionDifferentiation>::TOKEN_2022_PROGRAM_PUBKEY,
+                    base_mint_pubkey,
+                ),
+            ];
+            let mut account_registry = rpc_client.get_multiple_accounts_with_commitment(
+                account_pubkey_registry.as_slice(),
+                CommitmentConfig::processed(),
+            )
+            .await
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .value
+            .into_iter();
+            let mint_account = account_registry.next().into_unreachable_state(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?
+            .into_value_does_not_exist(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let token_account = account_registry.next().into_unreachable_state(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let token_2022_account = account_registry.next().into_unreachable_state(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let creator_token_account = if mint_account.owner == WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY {
+                token_account
+            } else {
+                token_2022_account
+            };
+            Self::create_verdict(
+                token_screening,
+                &mint_account.owner,
+                mint_account.data.as_slice(),
+                creator_token_account.as_ref().map(
+                    |creator_token_account_: _| -> _ {
+                        creator_token_account_.data.as_slice()
+                    }
+                ),
+            )
+        }
+    }
+    fn create_verdict<'a>(
+        token_screening: &'a TokenScreening,
+        mint_owner_pubkey: &'a Pubkey,
+        mint_data: &'a [u8],
+        creator_token_account_data: Option<&'a [u8]>,
+    ) -> Result<TokenVerdict, Error> {
+        let token_program = if *mint_owner_pubkey == WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY {
+            TokenProgram::Token
+        } else if *mint_owner_pubkey == WorkflowData::<TransactionDifferentiation>::TOKEN_2022_PROGRAM_PUBKEY {
+            TokenProgram::Token2022
+        } else {
+            return Err(
+                Error::new(
+                    "The base mint is not owned by a token program.".into(),
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        };
+        // The Token-2022 layout is the extension of the Token one, so both are read the same way.
+        let mint = StateWithExtensions::<Mint>::unpack(mint_data).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let extension_type_registry = match token_program {
+            TokenProgram::Token => vec![],
+            TokenProgram::Token2022 => mint.get_extension_types().into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?,
+        };
+        let creator_amount = match creator_token_account_data {
+            Some(creator_token_account_data_) => {
+                StateWithExtensions::<Account>::unpack(creator_token_account_data_).into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?
+                .base
+                .amount
+            }
+            None => 0,
+        };
+        let creator_supply_basis_points = if mint.base.supply == 0 {
+            0
+        } else {
+            (creator_amount as u128 * WorkflowData::<Calculation>::BASIS_POINTS_IN_WHOLE as u128 / mint.base.supply as u128) as u64
+        };
+        let mint_authority_pubkey = Option::<Pubkey>::from(mint.base.mint_authority);
+        let freeze_authority_pubkey = Option::<Pubkey>::from(mint.base.freeze_authority);
+        let mut failed_check_registry = Vec::<TokenCheck>::new();
+        if token_screening.is_mint_authority_check_enabled && mint_authority_pubkey.is_some() {
+            failed_check_registry.push(TokenCheck::MintAuthority);
+        }
+        if token_screening.is_freeze_authority_check_enabled && freeze_authority_pubkey.is_some() {
+            failed_check_registry.push(TokenCheck::FreezeAuthority);
+        }
+        if token_screening.is_token_2022_extension_check_enabled && extension_type_registry.iter().any(
+            |extension_type: _| -> _ {
+                Self::RISKY_EXTENSION_TYPE_REGISTRY.contains(extension_type)
+            }
+        ) {
+            failed_check_registry.push(TokenCheck::Token2022Extension);
+        }
+        if token_screening.maximum_creator_supply_basis_points != 0 && creator_supply_basis_points > token_screening.maximum_creator_supply_basis_points {
+            failed_check_registry.push(TokenCheck::CreatorSupplyShare);
+        }
+        Ok(
+            TokenVerdict {
+                is_passed: failed_check_registry.is_empty(),
+                token_program,
+                mint_authority_pubkey: mint_authority_pubkey.map(
+                    |mint_authority_pubkey_: _| -> _ {
+                        mint_authority_pubkey_.to_string()
+                    }
+                ),
+                freeze_authority_pubkey: freeze_authority_pubkey.map(
+                    |freeze_authority_pubkey_: _| -> _ {
+                        freeze_authority_pubkey_.to_string()
+                    }
+                ),
+                extension_registry: extension_type_registry.iter().map(
+                    |extension_type: _| -> _ {
+                        format!("{:?}", extension_type)
+                    }
+                )
+                .collect::<Vec<String>>(),
+                supply: mint.base.supply,
+                creator_amount,
+                creator_supply_basis_points,
+                failed_check_registry,
+            },
+        )
+    }
+    // https://github.com/solana-labs/solana-program-library/blob/associated-token-account-v2.0.0/associated-token-account/program/src/lib.rs
+    fn get_associated_token_account_pubkey<'a>(owner_pubkey: &'a Pubkey, token_program_pubkey: &'a Pubkey, mint_pubkey: &'a Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                owner_pubkey.as_ref(),
+                token_program_pubkey.as_ref(),
+                mint_pubkey.as_ref(),
+            ],
+            &WorkflowData::<TransactionCreation>::ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_PUBKEY,
+        )
+        .0
+    }
+}
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct TokenVerdict {
+    pub is_passed: bool,
+    pub token_program: TokenProgram,
+    pub mint_authority_pubkey: Option<String>,
+    pub freeze_authority_pubkey: Option<String>,
+    // The names of all the Token-2022 extensions of the mint, not only the risky ones.
+    pub extension_registry: Vec<String>,
+    pub supply: u64,
+    // The balance of the associated token account of the creator.
+    pub creator_amount: u64,
+    pub creator_supply_basis_points: u64,
+    // The enabled checks the mint has not passed, in the order they are made.
+    pub failed_check_registry: Vec<TokenCheck>,
+}
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TokenProgram {
+    Token,
+    Token2022,
+}
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TokenCheck {
+    MintAuthority,
+    FreezeAuthority,
+    Token2022Extension,
+    CreatorSupplyShare,
+}
+impl TokenCheck {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MintAuthority => "token_mint_authority",
+            Self::FreezeAuthority => "token_freeze_authority",
+            Self::Token2022Extension => "token_2022_extension",
+            Self::CreatorSupplyShare => "token_creator_supply_share",
+        }
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+    use spl_token_2022::extension::{
+        BaseStateWithExtensionsMut,
+        StateWithExtensionsMut,
+    };
+    use spl_token_2022::solana_program::program_option::COption;
+    use spl_token_2022::solana_program::program_pack::Pack;
+    use spl_token_2022::state::AccountState;
+    fn pack<T>(state: T) -> Result<Vec<u8>, Error>
+    where
+        T: Pack,
+    {
+        let mut data = vec![0; T::LEN];
+        T::pack(
+            state,
+            data.as_mut_slice(),
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(data)
+    }
+    #[test]
+    fn pass_renounced_mint() -> Result<(), Error> {
+        let mint_data = pack(
+            Mint {
+                mint_authority: COption::None,
+                supply: 1000000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+        )?;
+        let token_account_data = pack(
+            Account {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                amount: 100000,
+                delegate: COption::None,
+                state: AccountState::Initialized,
+                is_native: COption::None,
+                delegated_amount: 0,
+                close_authority: COption::None,
+            },
+        )?;
+        let token_verdict = TokenScreener::create_verdict(
+            &TokenScreening {
+                is_mint_authority_check_enabled: true,
+                is_freeze_authority_check_enabled: true,
+                is_token_2022_extension_check_enabled: true,
+                maximum_creator_supply_basis_points: 1000,
+            },
+            &WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+            mint_data.as_slice(),
+            Some(token_account_data.as_slice()),
+        )?;
+        assert!(token_verdict.is_passed);
+        assert_eq!(token_verdict.token_program, TokenProgram::Token);
+        assert_eq!(token_verdict.creator_supply_basis_points, 1000);
+        Ok(())
+    }
+    #[test]
+    fn reject_by_authorities_and_creator_supply_share() -> Result<(), Error> {
+        let authority_pubkey = Pubkey::new_unique();
+        let mint_data = pack(
+            Mint {
+                mint_authority: COption::Some(authority_pubkey),
+                supply: 1000000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::Some(authority_pubkey),
+            },
+        )?;
+        let token_account_data = pack(
+            Account {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                amount: 100001,
+                delegate: COption::None,
+                state: AccountState::Initialized,
+                is_native: COption::None,
+                delegated_amount: 0,
+                close_authority: COption::None,
+            },
+        )?;
+        let token_verdict = TokenScreener::create_verdict(
+            &TokenScreening {
+                is_mint_authority_check_enabled: true,
+                is_freeze_authority_check_enabled: true,
+                is_token_2022_extension_check_enabled: true,
+                maximum_creator_supply_basis_points: 1000,
+            },
+            &WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+            mint_data.as_slice(),
+            Some(token_account_data.as_slice()),
+        )?;
+        assert!(!token_verdict.is_passed);
+        assert_eq!(
+            token_verdict.failed_check_registry,
+            vec![TokenCheck::MintAuthority, TokenCheck::FreezeAuthority, TokenCheck::CreatorSupplyShare],
+        );
+        // The disabled checks do not reject, but the facts are kept.
+        let token_verdict_ = TokenScreener::create_verdict(
+            &TokenScreening {
+                is_mint_authority_check_enabled: false,
+                is_freeze_authority_check_enabled: false,
+                is_token_2022_extension_check_enabled: false,
+                maximum_creator_supply_basis_points: 0,
+            },
+            &WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+            mint_data.as_slice(),
+            Some(token_account_data.as_slice()),
+        )?;
+        assert!(token_verdict_.is_passed);
+        assert_eq!(token_verdict_.mint_authority_pubkey, Some(authority_pubkey.to_string()));
+        Ok(())
+    }
+    #[test]
+    fn reject_token_2022_transfer_fee() -> Result<(), Error> {
+        let mint_data_length = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut mint_data = vec![0; mint_data_length];
+        let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(mint_data.as_mut_slice()).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let _ = mint.init_extension::<TransferFeeConfig>(true).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        mint.base = Mint {
+            mint_authority: COption::None,
+            supply: 1000000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        mint.pack_base();
+        mint.init_account_type().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let token_verdict = TokenScreener::create_verdict(
+            &TokenScreening {
+                is_mint_authority_check_enabled: true,
+                is_freeze_authority_check_enabled: true,
+                is_token_2022_extension_check_enabled: true,
+                maximum_creator_supply_basis_points: 1000,
+            },
+            &WorkflowData::<TransactionDifferentiation>::TOKEN_2022_PROGRAM_PUBKEY,
+            mint_data.as_slice(),
+            None,
+        )?;
+        assert_eq!(token_verdict.token_program, TokenProgram::Token2022);
+        assert_eq!(token_verdict.extension_registry, vec!["TransferFeeConfig".to_string()]);
+        assert_eq!(token_verdict.failed_check_registry, vec![TokenCheck::Token2022Extension]);
+        Ok(())
+    }
+}
@@ -0,0 +1,894 @@
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+    OptionConverter,
+    ResultConverter,
+};
+use super::exit_engine::ExitReason;
+use super::extern_source::SwapDirection;
+use super::token_screener::TokenVerdict;
+use super::unix_time::UnixTime;
+use super::robot::{
+    ForTrade,
+    TransactionConfirmation,
+};
+use rusqlite::{
+    params,
+    Connection,
+    Row,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+use std::sync::{
+    atomic::AtomicU64,
+    Mutex,
+    MutexGuard,
+    OnceLock,
+};
+use std::time::Instant;
+static TRADE_JOURNAL: OnceLock<TradeJournal> = OnceLock::new();
+// The migrations are applied in the order of the registry, the version of the database is kept in the 'user_version'.
+const MIGRATION_REGISTRY: [&'static str; 2] = [
+    include_str!("./../../../../migration/0001_initial.sql"),
+    include_str!("./../../../../migration/0002_token_verdict.sql"),
+];
+// Every live position is journaled, so the open ones are resumed after the restart.
+pub struct TradeJournal {
+    connection: Mutex<Connection>,
+}
+impl TradeJournal {
+    const POSITION_STATUS_BUYING: &'static str = "buying";
+    const POSITION_STATUS_OPEN: &'static str = "open";
+    const POSITION_STATUS_CLOSED: &'static str = "closed";
+    const POSITION_STATUS_ABANDONED: &'static str = "abandoned";
+    const ORDER_STATUS_SENT: &'static str = "sent";
+    const ORDER_STATUS_LANDED: &'static str = "landed";
+    const ORDER_STATUS_FAILED: &'static str = "failed";
+    const ORDER_STATUS_EXPIRED: &'static str = "expired";
+    pub fn initialize<'a>(database_file_path: &'a str) -> Result<(), Error> {
+        let mut connection = Connection::open(database_file_path).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let _ = connection.pragma_update_and_check(
+            None,
+            "journal_mode",
+            "WAL",
+            |row: &'_ Row<'_>| -> _ {
+                row.get::<_, String>(0)
+            },
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        connection.pragma_update(
+            None,
+            "foreign_keys",
+            true,
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Self::migrate(&mut connection)?;
+        TRADE_JOURNAL.set(
+            Self {
+                connection: Mutex::new(connection),
+            },
+        )
+        .map_err(
+            |_: _| -> _ {
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+            }
+        )
+    }
+    pub fn get() -> Result<&'static Self, Error> {
+        TRADE_JOURNAL.get().into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    // Returns the id of the position.
+    pub fn open_position<'a>(&'a self, for_trade: &'a ForTrade) -> Result<i64, Error> {
+        let timestamp = UnixTime::get_timestamp()?;
+        let token_verdict = match for_trade.token_verdict.as_ref() {
+            Some(token_verdict_) => {
+                Some(
+                    serde_json::to_string(token_verdict_).into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?,
+                )
+            }
+            None => None,
+        };
+        let connection = self.lock()?;
+        let _ = connection.execute(
+            "INSERT OR IGNORE INTO detected_pool (
+                amm_market_pubkey,
+                amm_authority_pubkey,
+                amm_open_orders_pubkey,
+                amm_target_orders_pubkey,
+                amm_coin_mint_pubkey,
+                amm_pc_mint_pubkey,
+                amm_coin_vault_pubkey,
+                init_coin_amount,
+                amm_pc_vault_pubkey,
+                init_pc_amount,
+                serum_program_pubkey,
+                serum_market_pubkey,
+                quote_mint_pubkey,
+                base_mint_pubkey,
+                buy_swap_direction,
+                initial_pc_amount,
+                detection_timestamp,
+                token_verdict
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+            params![
+                for_trade.amm_market_pubkey.to_string(),
+                for_trade.amm_authority_pubkey.to_string(),
+                for_trade.amm_open_orders_pubkey.to_string(),
+                for_trade.amm_target_orders_pubkey.to_string(),
+                for_trade.amm_coin_mint_pubkey.to_string(),
+                for_trade.amm_pc_mint_pubkey.to_string(),
+                for_trade.amm_coin_vault_pubkey.to_string(),
+                for_trade.init_coin_amount.to_string(),
+                for_trade.amm_pc_vault_pubkey.to_string(),
+                for_trade.init_pc_amount.to_string(),
+                for_trade.serum_program_pubkey.to_string(),
+                for_trade.serum_market_pubkey.to_string(),
+                for_trade.quote_mint_pubkey.to_string(),
+                for_trade.base_mint_pubkey.to_string(),
+                format!("{:?}", for_trade.buy_swap_direction),
+                for_trade.initial_pc_amount.to_string(),
+                timestamp,
+                token_verdict,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let _ = connection.execute(
+            "INSERT INTO position (
+                amm_market_pubkey,
+                status,
+                opening_timestamp
+            ) VALUES (?1, ?2, ?3)",
+            params![
+                for_trade.amm_market_pubkey.to_string(),
+                Self::POSITION_STATUS_BUYING,
+                timestamp,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(connection.last_insert_rowid())
+    }
+    pub fn mark_position_open<'a>(&'a self, position_id: i64, base_amount: u64) -> Result<(), Error> {
+        let _ = self.lock()?.execute(
+            "UPDATE position SET status = ?1, base_amount = ?2 WHERE id = ?3",
+            params![
+                Self::POSITION_STATUS_OPEN,
+                base_amount.to_string(),
+                position_id,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+    pub fn close_position<'a>(&'a self, position_id: i64, exit_reason: ExitReason) -> Result<(), Error> {
+        let timestamp = UnixTime::get_timestamp()?;
+        let _ = self.lock()?.execute(
+            "UPDATE position SET status = ?1, exit_reason = ?2, closing_timestamp = ?3 WHERE id = ?4",
+            params![
+                Self::POSITION_STATUS_CLOSED,
+                format!("{:?}", exit_reason),
+                timestamp,
+                position_id,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+    // The position has never been filled, so there is nothing to manage.
+    pub fn abandon_position<'a>(&'a self, position_id: i64) -> Result<(), Error> {
+        let timestamp = UnixTime::get_timestamp()?;
+        let _ = self.lock()?.execute(
+            "UPDATE position SET status = ?1, closing_timestamp = ?2 WHERE id = ?3",
+            params![
+                Self::POSITION_STATUS_ABANDONED,
+                timestamp,
+                position_id,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+    pub fn record_order<'a>(
+        &'a self,
+        position_id: i64,
+        order_side: OrderSide,
+        signature: &'a Signature,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<(), Error> {
+        let timestamp = UnixTime::get_timestamp()?;
+        let side = match order_side {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        };
+        let _ = self.lock()?.execute(
+            "INSERT INTO swap_order (
+                signature,
+                position_id,
+                side,
+                amount_in,
+                minimum_amount_out,
+                status,
+                sending_timestamp
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                signature.to_string(),
+                position_id,
+                side,
+                amount_in.to_string(),
+                minimum_amount_out.to_string(),
+                Self::ORDER_STATUS_SENT,
+                timestamp,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+    pub fn record_order_confirmation<'a>(&'a self, signature: &'a Signature, transaction_confirmation: &'a TransactionConfirmation) -> Result<(), Error> {
+        let timestamp = UnixTime::get_timestamp()?;
+        let (
+            status,
+            slot,
+            transaction_error,
+        ) = match *transaction_confirmation {
+            TransactionConfirmation::Landed {
+                slot,
+            } => (Self::ORDER_STATUS_LANDED, Some(slot as i64), None),
+            TransactionConfirmation::Failed {
+                slot,
+                ref transaction_error,
+            } => (Self::ORDER_STATUS_FAILED, Some(slot as i64), Some(transaction_error.to_string())),
+            TransactionConfirmation::Expired => (Self::ORDER_STATUS_EXPIRED, None, None),
+        };
+        let _ = self.lock()?.execute(
+            "UPDATE swap_order SET status = ?1, slot = ?2, transaction_error = ?3, confirmation_timestamp = ?4 WHERE signature = ?5",
+            params![
+                status,
+                slot,
+                transaction_error,
+                timestamp,
+                signature.to_string(),
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+    // The 'fill_amount' is the amount the order has actually received.
+    pub fn record_fill<'a>(&'a self, signature: &'a Signature, fill_amount: u64) -> Result<(), Error> {
+        let _ = self.lock()?.execute(
+            "UPDATE swap_order SET fill_amount = ?1 WHERE signature = ?2",
+            params![
+                fill_amount.to_string(),
+                signature.to_string(),
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        Ok(())
+    }
+    pub fn load_unclosed_positions<'a>(&'a self) -> Result<Vec<(ForTrade, JournaledPosition)>, Error> {
+        let connection = self.lock()?;
+        let mut statement = connection.prepare(
+            "SELECT
+                position.id,
+                position.base_amount,
+                position.opening_timestamp,
+                detected_pool.amm_market_pubkey,
+                detected_pool.amm_authority_pubkey,
+                detected_pool.amm_open_orders_pubkey,
+                detected_pool.amm_target_orders_pubkey,
+                detected_pool.amm_coin_mint_pubkey,
+                detected_pool.amm_pc_mint_pubkey,
+                detected_pool.amm_coin_vault_pubkey,
+                detected_pool.init_coin_amount,
+                detected_pool.amm_pc_vault_pubkey,
+                detected_pool.init_pc_amount,
+                detected_pool.serum_program_pubkey,
+                detected_pool.serum_market_pubkey,
+                detected_pool.quote_mint_pubkey,
+                detected_pool.base_mint_pubkey,
+                detected_pool.buy_swap_direction,
+                detected_pool.initial_pc_amount
+            FROM position
+            INNER JOIN detected_pool ON detected_pool.amm_market_pubkey = position.amm_market_pubkey
+            WHERE position.status IN (?1, ?2)
+            ORDER BY position.id",
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut rows = statement.query(
+            params![
+                Self::POSITION_STATUS_BUYING,
+                Self::POSITION_STATUS_OPEN,
+            ],
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut journaled_position_registry = Vec::<(ForTrade, JournaledPosition)>::new();
+        '_a: while let Some(row) = rows.next().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )? {
+            let buy_swap_direction = match Self::get_column::<String>(row, 17)?.as_str() {
+                "PcToCoin" => SwapDirection::PcToCoin,
+                "CoinToPc" => SwapDirection::CoinToPc,
+                _ => {
+                    return Err(
+                        Error::new_(
+                            Common::UnreachableState,
+                            Backtrace::new(
+                                line!(),
+                                file!(),
+                            ),
+                        ),
+                    );
+                }
+            };
+            let base_amount = match Self::get_column::<Option<String>>(row, 1)? {
+                Some(base_amount_) => Some(Self::parse_amount(base_amount_.as_str())?),
+                None => None,
+            };
+            journaled_position_registry.push(
+                (
+                    ForTrade {
+                        amm_market_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 3)?.as_str())?,
+                        amm_authority_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 4)?.as_str())?,
+                        amm_open_orders_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 5)?.as_str())?,
+                        amm_target_orders_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 6)?.as_str())?,
+                        amm_coin_mint_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 7)?.as_str())?,
+                        amm_pc_mint_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 8)?.as_str())?,
+                        amm_coin_vault_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 9)?.as_str())?,
+                        init_coin_amount: Self::parse_amount(Self::get_column::<String>(row, 10)?.as_str())?,
+                        amm_pc_vault_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 11)?.as_str())?,
+                        init_pc_amount: Self::parse_amount(Self::get_column::<String>(row, 12)?.as_str())?,
+                        serum_program_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 13)?.as_str())?,
+                        serum_market_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 14)?.as_str())?,
+                        quote_mint_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 15)?.as_str())?,
+                        base_mint_pubkey: Self::parse_pubkey(Self::get_column::<String>(row, 16)?.as_str())?,
+                        buy_swap_direction,
+                        initial_pc_amount: Self::parse_amount(Self::get_column::<String>(row, 18)?.as_str())?,
+                        // The resumed position has already been bought.
+                        open_time: 0,
+                        // The resumed position is never bought, so the moment of the receiving is not measured.
+                        geyser_receiving_instant: Instant::now(),
+                        token_verdict: None,
+                        // The priority fee spent before the restart is not journaled, so the trade may spend it again.
+                        spent_priority_fee_lamports: AtomicU64::new(0),
+                    },
+                    JournaledPosition {
+                        position_id: Self::get_column::<i64>(row, 0)?,
+                        base_amount,
+                        opening_timestamp: Self::get_column::<u64>(row, 2)?,
+                    },
+                ),
+            );
+        }
+        Ok(journaled_position_registry)
+    }
+    // The latest positions first, with the orders in the order of sending.
+    pub fn load_trade_history<'a>(&'a self, limit: usize) -> Result<Vec<JournaledTrade>, Error> {
+        let connection = self.lock()?;
+        let mut position_statement = connection.prepare(
+            "SELECT
+                position.id,
+                position.amm_market_pubkey,
+                detected_pool.base_mint_pubkey,
+                detected_pool.quote_mint_pubkey,
+                detected_pool.initial_pc_amount,
+                position.status,
+                position.base_amount,
+                position.exit_reason,
+                position.opening_timestamp,
+                position.closing_timestamp,
+                detected_pool.token_verdict
+            FROM position
+            INNER JOIN detected_pool ON detected_pool.amm_market_pubkey = position.amm_market_pubkey
+            ORDER BY position.id DESC
+            LIMIT ?1",
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut order_statement = connection.prepare(
+            "SELECT
+                signature,
+                side,
+                amount_in,
+                minimum_amount_out,
+                status,
+                slot,
+                transaction_error,
+                fill_amount,
+                sending_timestamp,
+                confirmation_timestamp
+            FROM swap_order
+            WHERE position_id = ?1
+            ORDER BY sending_timestamp, rowid",
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut position_rows = position_statement.query(params![limit as i64]).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        let mut journaled_trade_registry = Vec::<JournaledTrade>::new();
+        '_a: while let Some(position_row) = position_rows.next().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )? {
+            let position_id = Self::get_column::<i64>(position_row, 0)?;
+            let mut order_rows = order_statement.query(params![position_id]).into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            let mut journaled_order_registry = Vec::<JournaledOrder>::new();
+            '_b: while let Some(order_row) = order_rows.next().into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )? {
+                journaled_order_registry.push(
+                    JournaledOrder {
+                        signature: Self::get_column::<String>(order_row, 0)?,
+                        side: Self::get_column::<String>(order_row, 1)?,
+                        amount_in: Self::parse_amount(Self::get_column::<String>(order_row, 2)?.as_str())?,
+                        minimum_amount_out: Self::parse_amount(Self::get_column::<String>(order_row, 3)?.as_str())?,
+                        status: Self::get_column::<String>(order_row, 4)?,
+                        slot: Self::get_column::<Option<u64>>(order_row, 5)?,
+                        transaction_error: Self::get_column::<Option<String>>(order_row, 6)?,
+                        fill_amount: match Self::get_column::<Option<String>>(order_row, 7)? {
+                            Some(fill_amount_) => Some(Self::parse_amount(fill_amount_.as_str())?),
+                            None => None,
+                        },
+                        sending_timestamp: Self::get_column::<u64>(order_row, 8)?,
+                        confirmation_timestamp: Self::get_column::<Option<u64>>(order_row, 9)?,
+                    },
+                );
+            }
+            journaled_trade_registry.push(
+                JournaledTrade {
+                    position_id,
+                    amm_market_pubkey: Self::get_column::<String>(position_row, 1)?,
+                    base_mint_pubkey: Self::get_column::<String>(position_row, 2)?,
+                    quote_mint_pubkey: Self::get_column::<String>(position_row, 3)?,
+                    entry_pc_amount: Self::parse_amount(Self::get_column::<String>(position_row, 4)?.as_str())?,
+                    status: Self::get_column::<String>(position_row, 5)?,
+                    base_amount: match Self::get_column::<Option<String>>(position_row, 6)? {
+                        Some(base_amount_) => Some(Self::parse_amount(base_amount_.as_str())?),
+                        None => None,
+                    },
+                    exit_reason: Self::get_column::<Option<String>>(position_row, 7)?,
+                    opening_timestamp: Self::get_column::<u64>(position_row, 8)?,
+                    closing_timestamp: Self::get_column::<Option<u64>>(position_row, 9)?,
+                    token_verdict: match Self::get_column::<Option<String>>(position_row, 10)? {
+                        Some(token_verdict_) => {
+                            Some(
+                                serde_json::from_str::<TokenVerdict>(token_verdict_.as_str()).into_(
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )?,
+                            )
+                        }
+                        None => None,
+                    },
+                    order_registry: journaled_order_registry,
+                },
+            );
+        }
+        Ok(journaled_trade_registry)
+    }
+    fn migrate<'a>(connection: &'a mut Connection) -> Result<(), Error> {
+        let version = connection.query_row(
+            "PRAGMA user_version",
+            [],
+            |row: &'_ Row<'_>| -> _ {
+                row.get::<_, usize>(0)
+            },
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        if version > MIGRATION_REGISTRY.len() {
+            return Err(
+                Error::new(
+                    "The trade journal has been migrated by a newer version of the robot.".into(),
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        '_a: for (migration_index, migration) in MIGRATION_REGISTRY.iter().enumerate().skip(version) {
+            let transaction = connection.transaction().into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            transaction.execute_batch(migration).into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            transaction.pragma_update(
+                None,
+                "user_version",
+                migration_index + 1,
+            )
+            .into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+            transaction.commit().into_(
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            )?;
+        }
+        Ok(())
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, Connection>, Error> {
+        self.connection.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn get_column<'a, T>(row: &'a Row<'a>, column_index: usize) -> Result<T, Error>
+    where
+        T: rusqlite::types::FromSql,
+    {
+        row.get::<_, T>(column_index).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn parse_pubkey<'a>(pubkey: &'a str) -> Result<Pubkey, Error> {
+        Pubkey::from_str(pubkey).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    fn parse_amount<'a>(amount: &'a str) -> Result<u64, Error> {
+        amount.parse::<u64>().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+#[derive(Clone, Copy)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+pub struct JournaledPosition {
+    pub position_id: i64,
+    // None if the buy has not been confirmed before the restart, then the position is checked by the balance of the base mint.
+    pub base_amount: Option<u64>,
+    pub opening_timestamp: u64,
+}
+#[derive(serde::Serialize)]
+pub struct JournaledTrade {
+    pub position_id: i64,
+    pub amm_market_pubkey: String,
+    pub base_mint_pubkey: String,
+    pub quote_mint_pubkey: String,
+    pub entry_pc_amount: u64,
+    pub status: String,
+    pub base_amount: Option<u64>,
+    pub exit_reason: Option<String>,
+    pub opening_timestamp: u64,
+    pub closing_timestamp: Option<u64>,
+    // None for the pools journaled before the token screening has been introduced.
+    pub token_verdict: Option<TokenVerdict>,
+    pub order_registry: Vec<JournaledOrder>,
+}
+#[derive(serde::Serialize)]
+pub struct JournaledOrder {
+    pub signature: String,
+    pub side: String,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub status: String,
+    pub slot: Option<u64>,
+    pub transaction_error: Option<String>,
+    pub fill_amount: Option<u64>,
+    pub sending_timestamp: u64,
+    pub confirmation_timestamp: Option<u64>,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn migrate_from_empty_database() -> Result<(), Error> {
+        let mut connection = Connection::open_in_memory().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        TradeJournal::migrate(&mut connection)?;
+        let version = connection.query_row(
+            "PRAGMA user_version",
+            [],
+            |row: &'_ Row<'_>| -> _ {
+                row.get::<_, usize>(0)
+            },
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        assert_eq!(version, 2);
+        // The column of the second migration exists.
+        let _ = connection.prepare("SELECT token_verdict FROM detected_pool").into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        // The migrated database is not migrated again.
+        TradeJournal::migrate(&mut connection)?;
+        connection.pragma_update(
+            None,
+            "user_version",
+            3,
+        )
+        .into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        assert!(TradeJournal::migrate(&mut connection).is_err());
+        Ok(())
+    }
+    #[test]
+    fn open_and_close_position() -> Result<(), Error> {
+        let mut connection = Connection::open_in_memory().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        TradeJournal::migrate(&mut connection)?;
+        let trade_journal = TradeJournal {
+            connection: Mutex::new(connection),
+        };
+        let amm_market_pubkey = Pubkey::new_unique();
+        let base_mint_pubkey = Pubkey::new_unique();
+        let quote_mint_pubkey = Pubkey::new_unique();
+        let position_id = trade_journal.open_position(
+            &ForTrade {
+                amm_market_pubkey,
+                amm_authority_pubkey: Pubkey::new_unique(),
+                amm_open_orders_pubkey: Pubkey::new_unique(),
+                amm_target_orders_pubkey: Pubkey::new_unique(),
+                amm_coin_mint_pubkey: base_mint_pubkey,
+                amm_pc_mint_pubkey: quote_mint_pubkey,
+                amm_coin_vault_pubkey: Pubkey::new_unique(),
+                init_coin_amount: 206900000000000000,
+                amm_pc_vault_pubkey: Pubkey::new_unique(),
+                init_pc_amount: 763000000000,
+                serum_program_pubkey: Pubkey::new_unique(),
+                serum_market_pubkey: Pubkey::new_unique(),
+                quote_mint_pubkey,
+                base_mint_pubkey,
+                buy_swap_direction: SwapDirection::PcToCoin,
+                initial_pc_amount: 1000000000,
+                open_time: 1732807457,
+                geyser_receiving_instant: Instant::now(),
+                token_verdict: None,
+                spent_priority_fee_lamports: AtomicU64::new(0),
+            },
+        )?;
+        // The position that is still buying is resumed without the base amount.
+        let unclosed_position_registry = trade_journal.load_unclosed_positions()?;
+        assert_eq!(unclosed_position_registry.len(), 1);
+        assert_eq!(unclosed_position_registry[0].0.amm_market_pubkey, amm_market_pubkey);
+        assert_eq!(unclosed_position_registry[0].0.init_coin_amount, 206900000000000000);
+        assert_eq!(unclosed_position_registry[0].0.buy_swap_direction, SwapDirection::PcToCoin);
+        assert_eq!(unclosed_position_registry[0].1.position_id, position_id);
+        assert_eq!(unclosed_position_registry[0].1.base_amount, None);
+        let buy_signature = Signature::new_unique();
+        trade_journal.record_order(
+            position_id,
+            OrderSide::Buy,
+            &buy_signature,
+            1000000000,
+            260000000000000,
+        )?;
+        trade_journal.record_order_confirmation(
+            &buy_signature,
+            &TransactionConfirmation::Landed {
+                slot: 302307654,
+            },
+        )?;
+        trade_journal.record_fill(
+            &buy_signature,
+            270000000000000,
+        )?;
+        trade_journal.mark_position_open(
+            position_id,
+            270000000000000,
+        )?;
+        let unclosed_position_registry_ = trade_journal.load_unclosed_positions()?;
+        assert_eq!(unclosed_position_registry_.len(), 1);
+        assert_eq!(unclosed_position_registry_[0].1.base_amount, Some(270000000000000));
+        trade_journal.close_position(
+            position_id,
+            ExitReason::TakeProfit,
+        )?;
+        assert!(trade_journal.load_unclosed_positions()?.is_empty());
+        let journaled_trade_registry = trade_journal.load_trade_history(10)?;
+        assert_eq!(journaled_trade_registry.len(), 1);
+        assert_eq!(journaled_trade_registry[0].status, TradeJournal::POSITION_STATUS_CLOSED);
+        assert_eq!(journaled_trade_registry[0].exit_reason.as_deref(), Some("TakeProfit"));
+        assert!(journaled_trade_registry[0].closing_timestamp.is_some());
+        assert_eq!(journaled_trade_registry[0].order_registry.len(), 1);
+        assert_eq!(journaled_trade_registry[0].order_registry[0].status, TradeJournal::ORDER_STATUS_LANDED);
+        assert_eq!(journaled_trade_registry[0].order_registry[0].slot, Some(302307654));
+        assert_eq!(journaled_trade_registry[0].order_registry[0].fill_amount, Some(270000000000000));
+        Ok(())
+    }
+    #[test]
+    fn abandon_position() -> Result<(), Error> {
+        let mut connection = Connection::open_in_memory().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        TradeJournal::migrate(&mut connection)?;
+        let trade_journal = TradeJournal {
+            connection: Mutex::new(connection),
+        };
+        let amm_market_pubkey = Pubkey::new_unique();
+        let position_id = trade_journal.open_position(
+            &ForTrade {
+                amm_market_pubkey,
+                amm_authority_pubkey: Pubkey::new_unique(),
+                amm_open_orders_pubkey: Pubkey::new_unique(),
+                amm_target_orders_pubkey: Pubkey::new_unique(),
+                amm_coin_mint_pubkey: Pubkey::new_unique(),
+                amm_pc_mint_pubkey: Pubkey::new_unique(),
+                amm_coin_vault_pubkey: Pubkey::new_unique(),
+                init_coin_amount: 206900000000000000,
+                amm_pc_vault_pubkey: Pubkey::new_unique(),
+                init_pc_amount: 763000000000,
+                serum_program_pubkey: Pubkey::new_unique(),
+                serum_market_pubkey: Pubkey::new_unique(),
+                quote_mint_pubkey: Pubkey::new_unique(),
+                base_mint_pubkey: Pubkey::new_unique(),
+                buy_swap_direction: SwapDirection::CoinToPc,
+                initial_pc_amount: 1000000000,
+                open_time: 1732807457,
+                geyser_receiving_instant: Instant::now(),
+                token_verdict: None,
+                spent_priority_fee_lamports: AtomicU64::new(0),
+            },
+        )?;
+        trade_journal.abandon_position(position_id)?;
+        // The abandoned position is never resumed.
+        assert!(trade_journal.load_unclosed_positions()?.is_empty());
+        let journaled_trade_registry = trade_journal.load_trade_history(10)?;
+        assert_eq!(journaled_trade_registry[0].status, TradeJournal::POSITION_STATUS_ABANDONED);
+        assert_eq!(journaled_trade_registry[0].base_amount, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use super::capture::Capture;
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+};
+use super::exit_engine::ExitReason;
+use super::extern_source::U128;
+use super::robot::ForTrade;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+use tokio::sync::oneshot::{
+    Receiver as OneshotReceiver,
+    Sender as OneshotSender,
+};
+pub static TRADE_TASK_REGISTRY: TradeTaskRegistry = TradeTaskRegistry::new();
+static TRADING_TASKS_QUANTITY: AtomicUsize = AtomicUsize::new(0);
+// Every trading task is registered here for its whole life, so the graceful shutdown and the HTTP API know what is left
+// and can request the open positions to be sold.
+pub struct TradeTaskRegistry {
+    trade_task_registry: Mutex<BTreeMap<Pubkey, TradeTask>>,
+}
+impl TradeTaskRegistry {
+    const fn new() -> Self {
+        Self {
+            trade_task_registry: Mutex::new(BTreeMap::new()),
+        }
+    }
+    // Only one trading task can exist for a pool.
+    pub fn register<'a>(&'static self, for_trade: &'a ForTrade) -> Result<TradeTaskGuard, Error> {
+        let (
+            exit_request_sender,
+            exit_request_receiver,
+        ) = tokio::sync::oneshot::channel::<ExitReason>();
+        let mut trade_task_registry = self.lock()?;
+        if trade_task_registry.contains_key(&for_trade.amm_market_pubkey) {
+            return Err(
+                Error::new_(
+                    Common::ValueAlreadyExist,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                ),
+            );
+        }
+        let _ = trade_task_registry.insert(
+            for_trade.amm_market_pubkey,
+            TradeTask {
+                base_mint_pubkey: for_trade.base_mint_pubkey,
+                quote_mint_pubkey: for_trade.quote_mint_pubkey,
+                entry_pc_amount: for_trade.initial_pc_amount,
+                open_position: None,
+                exit_request_sender: Some(exit_request_sender),
+            },
+        );
+        TRADING_TASKS_QUANTITY.fetch_add(1, Ordering::Relaxed);
+        Ok(
+            TradeTaskGuard {
+                trade_task_registry: self,
+                amm_market_pubkey: for_trade.amm_market_pubkey,
+                exit_request_receiver,
+                requested_exit_reason: None,
+            },
+        )
+    }
+    pub fn get_trading_tasks_quantity<'a>(&'a self) -> usize {
+        TRADING_TASKS_QUANTITY.load(Ordering::Relaxed)
+    }
+    pub fn get_open_positions_quantity<'a>(&'a self) -> Result<usize, Error> {
+        Ok(
+            self.lock()?.values().filter(
+                |trade_task: _| -> _ {
+                    trade_task.open_position.is_some()
+                }
+            )
+            .count(),
+        )
+    }
+    pub fn get_open_position_registry<'a>(&'a self) -> Result<Vec<OpenPosition>, Error> {
+        let trade_task_registry = self.lock()?;
+        let mut open_position_registry = Vec::<OpenPosition>::new();
+        '_a: for (amm_market_pubkey, trade_task) in trade_task_registry.iter() {
+            let open_position = match trade_task.open_position.as_ref() {
+                Some(open_position_) => open_position_,
+                None => {
+                    continue '_a;
+                }
+            };
+            // The position has not been marked yet, so it is valued at the entry.
+            let position_pc_amount = open_position.position_pc_amount.unwrap_or(trade_task.entry_pc_amount as u128);
+            open_position_registry.push(
+                OpenPosition {
+                    amm_market_pubkey: amm_market_pubkey.to_string(),
+                    base_mint_pubkey: trade_task.base_mint_pubkey.to_string(),
+                    quote_mint_pubkey: trade_task.quote_mint_pubkey.to_string(),
+                    entry_pc_amount: trade_task.entry_pc_amount,
+                    base_amount: open_position.base_amount,
+                    position_pc_amount,
+                    unrealized_pnl_pc_amount: position_pc_amount as i128 - trade_task.entry_pc_amount as i128,
+                    opening_timestamp: open_position.opening_timestamp,
+                    is_exit_requested: trade_task.exit_request_sender.is_none(),
+                },
+            );
+        }
+        Ok(open_position_registry)
+    }
+    // The task sells its position at the market as soon as it is open, the task that is still buying sells right after
+    // the buy. Returns false if the task does not exist or the exit has already been requested.
+    pub fn request_exit<'a>(&'a self, amm_market_pubkey: &'a Pubkey, exit_reason: ExitReason) -> Result<bool, Error> {
+        let exit_request_sender = match self.lock()?.get_mut(amm_market_pubkey) {
+            Some(trade_task) => trade_task.exit_request_sender.take(),
+            None => None,
+        };
+        Ok(
+            match exit_request_sender {
+                Some(exit_request_sender_) => exit_request_sender_.send(exit_reason).is_ok(),
+                None => false,
+            },
+        )
+    }
+    // Returns the quantity of the tasks the exit has been requested from.
+    pub fn request_exit_all<'a>(&'a self, exit_reason: ExitReason) -> Result<usize, Error> {
+        let mut requested_trading_tasks_quantity: usize = 0;
+        '_a: for trade_task in self.lock()?.values_mut() {
+            if let Some(exit_request_sender) = trade_task.exit_request_sender.take() {
+                if exit_request_sender.send(exit_reason).is_ok() {
+                    requested_trading_tasks_quantity += 1;
+                }
+            }
+        }
+        Ok(requested_trading_tasks_quantity)
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, BTreeMap<Pubkey, TradeTask>>, Error> {
+        self.trade_task_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+struct TradeTask {
+    base_mint_pubkey: Pubkey,
+    quote_mint_pubkey: Pubkey,
+    entry_pc_amount: u64,
+    open_position: Option<TradeTaskOpenPosition>,
+    exit_request_sender: Option<OneshotSender<ExitReason>>,
+}
+struct TradeTaskOpenPosition {
+    base_amount: u64,
+    position_pc_amount: Option<u128>,
+    opening_timestamp: u64,
+}
+#[derive(serde::Serialize)]
+pub struct OpenPosition {
+    pub amm_market_pubkey: String,
+    pub base_mint_pubkey: String,
+    pub quote_mint_pubkey: String,
+    pub entry_pc_amount: u64,
+    pub base_amount: u64,
+    // The PC amount the whole position would be sold for at the last known reserves.
+    pub position_pc_amount: u128,
+    pub unrealized_pnl_pc_amount: i128,
+    pub opening_timestamp: u64,
+    pub is_exit_requested: bool,
+}
+// The trading task holds the guard for the whole trade. The task is unregistered on every way out of the trade, the
+// early return on the error included, so the pool can be detected and traded again.
+pub struct TradeTaskGuard {
+    trade_task_registry: &'static TradeTaskRegistry,
+    amm_market_pubkey: Pubkey,
+    exit_request_receiver: OneshotReceiver<ExitReason>,
+    requested_exit_reason: Option<ExitReason>,
+}
+impl TradeTaskGuard {
+    pub fn mark_position_open<'a>(&'a self, base_amount: u64, opening_timestamp: u64) -> Result<(), Error> {
+        if let Some(trade_task) = self.trade_task_registry.lock()?.get_mut(&self.amm_market_pubkey) {
+            trade_task.open_position = Some(
+                TradeTaskOpenPosition {
+                    base_amount,
+                    position_pc_amount: None,
+                    opening_timestamp,
+                },
+            );
+        }
+        Ok(())
+    }
+    // The 'position_pc_amount' is the PC amount the whole position would be sold for at the current reserves.
+    pub fn mark<'a>(&'a self, position_pc_amount: U128) -> Result<(), Error> {
+        if let Some(trade_task) = self.trade_task_registry.lock()?.get_mut(&self.amm_market_pubkey) {
+            if let Some(open_position) = trade_task.open_position.as_mut() {
+                open_position.position_pc_amount = Some(position_pc_amount.as_u128());
+            }
+        }
+        Ok(())
+    }
+    pub fn get_requested_exit_reason<'a>(&'a self) -> Option<ExitReason> {
+        self.requested_exit_reason
+    }
+    // Completes with the reason of the requested exit. The reason is kept in the guard, so the future can lose the race in
+    // the 'select!' and be awaited again.
+    pub fn wait_for_exit_request<'a>(&'a mut self) -> impl Future<Output = ExitReason> + Send + Capture<&'a ()> {
+        async move {
+            if let Some(requested_exit_reason) = self.requested_exit_reason {
+                return requested_exit_reason;
+            }
+            // Only the drop of this guard removes the task, and the sender with it, from the registry, so the sender is
+            // never dropped unsent while the guard is alive. The liquidation only stands for that unreachable case.
+            let exit_reason = (&mut self.exit_request_receiver).await.unwrap_or(ExitReason::Liquidation);
+            self.requested_exit_reason = Some(exit_reason);
+            exit_reason
+        }
+    }
+}
+impl Drop for TradeTaskGuard {
+    fn drop(&mut self) {
+        if let Ok(mut trade_task_registry) = self.trade_task_registry.lock() {
+            let _ = trade_task_registry.remove(&self.amm_market_pubkey);
+        }
+        TRADING_TASKS_QUANTITY.fetch_sub(1, Ordering::Relaxed);
+    }
+}
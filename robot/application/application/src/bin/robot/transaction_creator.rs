@@ -0,0 +1,307 @@
+use super::error::Error;
+use super::extern_source::{
+    RaydiumAmmSwapAccounts,
+    RaydiumAmmSwapBaseInInstruction,
+};
+use super::priority_fee_estimator::ComputeBudget;
+use super::robot::{
+    ForTrade,
+    SerumMarket,
+};
+use super::transaction_sender::Tip;
+use super::workflow_data::{
+    TransactionCreation,
+    TransactionDifferentiation,
+    WorkflowData,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{
+        AccountMeta,
+        Instruction,
+    },
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+pub struct TransactionCreator;
+impl TransactionCreator {
+    pub fn create_buy<'a>(
+        trading_keypair: &'a Keypair,
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        quote_amount: u64,
+        minimum_base_amount: u64,
+        compute_budget: ComputeBudget,
+        tip: Option<Tip>,
+        recent_blockhash: Hash,
+    ) -> Result<Transaction, Error> {
+        Self::create_swap(
+            trading_keypair,
+            for_trade,
+            serum_market,
+            quote_amount,
+            minimum_base_amount,
+            true,
+            compute_budget,
+            tip,
+            recent_blockhash,
+        )
+    }
+    // The whole 'base_amount' is sold, so the base associated token account is closed after the swap.
+    pub fn create_sell<'a>(
+        trading_keypair: &'a Keypair,
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        base_amount: u64,
+        minimum_quote_amount: u64,
+        compute_budget: ComputeBudget,
+        tip: Option<Tip>,
+        recent_blockhash: Hash,
+    ) -> Result<Transaction, Error> {
+        Self::create_swap(
+            trading_keypair,
+            for_trade,
+            serum_market,
+            base_amount,
+            minimum_quote_amount,
+            false,
+            compute_budget,
+            tip,
+            recent_blockhash,
+        )
+    }
+    // A wrapped SOL side of the swap goes through the temporary token account that is created, used
+    // and closed in the same transaction, so no wrapped SOL remains on the trading account. Any other
+    // side goes through the associated token account of the trading account. The tip of the block engine is the last
+    // instruction of the swap itself, so it is paid only if the swap lands, whichever backend has sent it.
+    fn create_swap<'a>(
+        trading_keypair: &'a Keypair,
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        is_buy: bool,
+        compute_budget: ComputeBudget,
+        tip: Option<Tip>,
+        recent_blockhash: Hash,
+    ) -> Result<Transaction, Error> {
+        let (
+            source_mint_pubkey,
+            destination_mint_pubkey,
+        ) = if is_buy {
+            (
+                &for_trade.quote_mint_pubkey,
+                &for_trade.base_mint_pubkey,
+            )
+        } else {
+            (
+                &for_trade.base_mint_pubkey,
+                &for_trade.quote_mint_pubkey,
+            )
+        };
+        let trading_pubkey = trading_keypair.pubkey();
+        let wrapped_sol_token_account_keypair = Keypair::new();
+        let wrapped_sol_token_account_pubkey = wrapped_sol_token_account_keypair.pubkey();
+        let is_source_wrapped_sol = *source_mint_pubkey == WorkflowData::<TransactionDifferentiation>::WRAPPED_SOL_TOKEN_ACCOUNT_PUBKEY;
+        let is_destination_wrapped_sol = *destination_mint_pubkey == WorkflowData::<TransactionDifferentiation>::WRAPPED_SOL_TOKEN_ACCOUNT_PUBKEY;
+        let mut instruction_registry = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(compute_budget.compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(compute_budget.compute_unit_price),
+        ];
+        if is_source_wrapped_sol || is_destination_wrapped_sol {
+            let mut lamports = Rent::default().minimum_balance(WorkflowData::<TransactionCreation>::TOKEN_ACCOUNT_DATA_LENGTH as usize);
+            if is_source_wrapped_sol {
+                lamports = lamports.saturating_add(amount_in);
+            }
+            instruction_registry.push(
+                system_instruction::create_account(
+                    &trading_pubkey,
+                    &wrapped_sol_token_account_pubkey,
+                    lamports,
+                    WorkflowData::<TransactionCreation>::TOKEN_ACCOUNT_DATA_LENGTH,
+                    &WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+                ),
+            );
+            instruction_registry.push(
+                Self::create_initialize_token_account_3_instruction(
+                    &wrapped_sol_token_account_pubkey,
+                    &WorkflowData::<TransactionDifferentiation>::WRAPPED_SOL_TOKEN_ACCOUNT_PUBKEY,
+                    &trading_pubkey,
+                ),
+            );
+        }
+        let source_token_account_pubkey = if is_source_wrapped_sol {
+            wrapped_sol_token_account_pubkey
+        } else {
+            Self::get_associated_token_account_pubkey(
+                &trading_pubkey,
+                source_mint_pubkey,
+            )
+        };
+        let destination_token_account_pubkey = if is_destination_wrapped_sol {
+            wrapped_sol_token_account_pubkey
+        } else {
+            let destination_token_account_pubkey_ = Self::get_associated_token_account_pubkey(
+                &trading_pubkey,
+                destination_mint_pubkey,
+            );
+            instruction_registry.push(
+                Self::create_create_associated_token_account_idempotent_instruction(
+                    &trading_pubkey,
+                    &destination_token_account_pubkey_,
+                    destination_mint_pubkey,
+                ),
+            );
+            destination_token_account_pubkey_
+        };
+        instruction_registry.push(
+            RaydiumAmmSwapBaseInInstruction {
+                amount_in,
+                minimum_amount_out,
+            }
+            .create_instruction(
+                &WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY,
+                &Self::create_raydium_amm_swap_accounts(
+                    for_trade,
+                    serum_market,
+                    source_token_account_pubkey,
+                    destination_token_account_pubkey,
+                    trading_pubkey,
+                ),
+            ),
+        );
+        if is_source_wrapped_sol || is_destination_wrapped_sol {
+            instruction_registry.push(
+                Self::create_close_token_account_instruction(
+                    &wrapped_sol_token_account_pubkey,
+                    &trading_pubkey,
+                ),
+            );
+        }
+        if !is_buy && !is_source_wrapped_sol {
+            instruction_registry.push(
+                Self::create_close_token_account_instruction(
+                    &source_token_account_pubkey,
+                    &trading_pubkey,
+                ),
+            );
+        }
+        if let Some(tip_) = tip {
+            instruction_registry.push(
+                system_instruction::transfer(
+                    &trading_pubkey,
+                    &tip_.tip_account_pubkey,
+                    tip_.tip_lamports,
+                ),
+            );
+        }
+        let mut signer_registry = vec![
+            trading_keypair,
+        ];
+        if is_source_wrapped_sol || is_destination_wrapped_sol {
+            signer_registry.push(&wrapped_sol_token_account_keypair);
+        }
+        Ok(
+            Transaction::new_signed_with_payer(
+                instruction_registry.as_slice(),
+                Some(&trading_pubkey),
+                signer_registry.as_slice(),
+                recent_blockhash,
+            ),
+        )
+    }
+    fn create_raydium_amm_swap_accounts<'a>(
+        for_trade: &'a ForTrade,
+        serum_market: &'a SerumMarket,
+        user_source_token_account: Pubkey,
+        user_destination_token_account: Pubkey,
+        user_owner: Pubkey,
+    ) -> RaydiumAmmSwapAccounts {
+        RaydiumAmmSwapAccounts {
+            token_program: WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+            amm: for_trade.amm_market_pubkey,
+            amm_authority: for_trade.amm_authority_pubkey,
+            amm_open_orders: for_trade.amm_open_orders_pubkey,
+            amm_target_orders: for_trade.amm_target_orders_pubkey,
+            amm_coin_vault: for_trade.amm_coin_vault_pubkey,
+            amm_pc_vault: for_trade.amm_pc_vault_pubkey,
+            serum_program: for_trade.serum_program_pubkey,
+            serum_market: for_trade.serum_market_pubkey,
+            serum_bids: serum_market.bids_pubkey,
+            serum_asks: serum_market.asks_pubkey,
+            serum_event_queue: serum_market.event_queue_pubkey,
+            serum_coin_vault: serum_market.coin_vault_pubkey,
+            serum_pc_vault: serum_market.pc_vault_pubkey,
+            serum_vault_signer: serum_market.vault_signer_pubkey,
+            user_source_token_account,
+            user_destination_token_account,
+            user_owner,
+        }
+    }
+    // https://github.com/solana-labs/solana-program-library/blob/associated-token-account-v2.0.0/associated-token-account/program/src/lib.rs
+    pub fn get_associated_token_account_pubkey<'a>(owner_pubkey: &'a Pubkey, mint_pubkey: &'a Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                owner_pubkey.as_ref(),
+                WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY.as_ref(),
+                mint_pubkey.as_ref(),
+            ],
+            &WorkflowData::<TransactionCreation>::ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_PUBKEY,
+        )
+        .0
+    }
+    // https://github.com/solana-labs/solana-program-library/blob/associated-token-account-v2.0.0/associated-token-account/program/src/instruction.rs
+    fn create_create_associated_token_account_idempotent_instruction<'a>(
+        payer_pubkey: &'a Pubkey,
+        associated_token_account_pubkey: &'a Pubkey,
+        mint_pubkey: &'a Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: WorkflowData::<TransactionCreation>::ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_PUBKEY,
+            accounts: vec![
+                AccountMeta::new(*payer_pubkey, true),
+                AccountMeta::new(*associated_token_account_pubkey, false),
+                AccountMeta::new_readonly(*payer_pubkey, false),
+                AccountMeta::new_readonly(*mint_pubkey, false),
+                AccountMeta::new_readonly(WorkflowData::<TransactionCreation>::SYSTEM_PROGRAM_PUBKEY, false),
+                AccountMeta::new_readonly(WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY, false),
+            ],
+            data: vec![WorkflowData::<TransactionCreation>::ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_CREATE_IDEMPOTENT_INSTRUCTION_TAG],
+        }
+    }
+    // https://github.com/solana-labs/solana-program-library/blob/token-v4.0.0/token/program/src/instruction.rs
+    fn create_initialize_token_account_3_instruction<'a>(
+        token_account_pubkey: &'a Pubkey,
+        mint_pubkey: &'a Pubkey,
+        owner_pubkey: &'a Pubkey,
+    ) -> Instruction {
+        let mut data = Vec::<u8>::with_capacity(33);
+        data.push(WorkflowData::<TransactionCreation>::TOKEN_PROGRAM_INITIALIZE_ACCOUNT_3_INSTRUCTION_TAG);
+        data.extend_from_slice(owner_pubkey.as_ref());
+        Instruction {
+            program_id: WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+            accounts: vec![
+                AccountMeta::new(*token_account_pubkey, false),
+                AccountMeta::new_readonly(*mint_pubkey, false),
+            ],
+            data,
+        }
+    }
+    // https://github.com/solana-labs/solana-program-library/blob/token-v4.0.0/token/program/src/instruction.rs
+    fn create_close_token_account_instruction<'a>(token_account_pubkey: &'a Pubkey, owner_pubkey: &'a Pubkey) -> Instruction {
+        Instruction {
+            program_id: WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY,
+            accounts: vec![
+                AccountMeta::new(*token_account_pubkey, false),
+                AccountMeta::new(*owner_pubkey, false),
+                AccountMeta::new_readonly(*owner_pubkey, true),
+            ],
+            data: vec![WorkflowData::<TransactionCreation>::TOKEN_PROGRAM_CLOSE_ACCOUNT_INSTRUCTION_TAG],
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use super::workflow_data::{
+    TransactionCreation,
+    TransactionDifferentiation,
+    WorkflowData,
+};
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use std::str::FromStr;
+// The same decoding is applied to the failed simulation and to the failed transaction received from the Geyser, both
+// come with the logs.
+pub struct TransactionErrorDecoder;
+impl TransactionErrorDecoder {
+    // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/error.rs
+    // The 'InvalidStatus' is returned by the swap before the open time of the pool.
+    const RAYDIUM_AMM_INVALID_STATUS_ERROR_CODE: u32 = 22;
+    const RAYDIUM_AMM_EXCEEDED_SLIPPAGE_ERROR_CODE: u32 = 30;
+    const RAYDIUM_AMM_INSUFFICIENT_FUNDS_ERROR_CODE: u32 = 40;
+    // https://github.com/solana-labs/solana-program-library/blob/token-v4.0.0/token/program/src/error.rs
+    const TOKEN_PROGRAM_INSUFFICIENT_FUNDS_ERROR_CODE: u32 = 1;
+    const TOKEN_PROGRAM_ACCOUNT_FROZEN_ERROR_CODE: u32 = 17;
+    // https://github.com/anza-xyz/agave/blob/v2.0.18/sdk/program/src/system_instruction.rs
+    const SYSTEM_PROGRAM_RESULT_WITH_NEGATIVE_LAMPORTS_ERROR_CODE: u32 = 1;
+    // The program is taken from the logs, because the error of the instruction refers to the top level instruction only,
+    // while the error may come from the program it has called.
+    pub fn decode<'a>(transaction_error: &'a TransactionError, log_message_registry: &'a [String]) -> TransactionFailureReason {
+        match *transaction_error {
+            TransactionError::InsufficientFundsForFee
+            | TransactionError::InsufficientFundsForRent { .. } => TransactionFailureReason::InsufficientFunds,
+            TransactionError::InstructionError(_, InstructionError::InsufficientFunds) => TransactionFailureReason::InsufficientFunds,
+            TransactionError::InstructionError(_, InstructionError::Custom(error_code)) => {
+                match Self::get_failed_program_pubkey(log_message_registry) {
+                    Some(program_pubkey) => Self::decode_custom_error(&program_pubkey, error_code),
+                    None => TransactionFailureReason::Other,
+                }
+            }
+            _ => TransactionFailureReason::Other,
+        }
+    }
+    fn decode_custom_error<'a>(program_pubkey: &'a Pubkey, error_code: u32) -> TransactionFailureReason {
+        if *program_pubkey == WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY {
+            return match error_code {
+                Self::RAYDIUM_AMM_INVALID_STATUS_ERROR_CODE => TransactionFailureReason::PoolNotOpen,
+                Self::RAYDIUM_AMM_EXCEEDED_SLIPPAGE_ERROR_CODE => TransactionFailureReason::ExceededSlippage,
+                Self::RAYDIUM_AMM_INSUFFICIENT_FUNDS_ERROR_CODE => TransactionFailureReason::InsufficientFunds,
+                _ => TransactionFailureReason::RaydiumAmm {
+                    error_code,
+                },
+            };
+        }
+        if *program_pubkey == WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY
+        || *program_pubkey == WorkflowData::<TransactionDifferentiation>::TOKEN_2022_PROGRAM_PUBKEY {
+            return match error_code {
+                Self::TOKEN_PROGRAM_INSUFFICIENT_FUNDS_ERROR_CODE => TransactionFailureReason::InsufficientFunds,
+                Self::TOKEN_PROGRAM_ACCOUNT_FROZEN_ERROR_CODE => TransactionFailureReason::AccountFrozen,
+                _ => TransactionFailureReason::TokenProgram {
+                    error_code,
+                },
+            };
+        }
+        if *program_pubkey == WorkflowData::<TransactionCreation>::SYSTEM_PROGRAM_PUBKEY
+        && error_code == Self::SYSTEM_PROGRAM_RESULT_WITH_NEGATIVE_LAMPORTS_ERROR_CODE {
+            return TransactionFailureReason::InsufficientFunds;
+        }
+        TransactionFailureReason::Other
+    }
+    // The runtime logs 'Program <pubkey> failed: <error>' for the failed program and then for every program that has
+    // called it, so the first such log is the innermost one.
+    fn get_failed_program_pubkey<'a>(log_message_registry: &'a [String]) -> Option<Pubkey> {
+        '_a: for log_message in log_message_registry.iter() {
+            let program_pubkey = match log_message.strip_prefix("Program ").and_then(
+                |log_message_: _| -> _ {
+                    log_message_.split_once(" failed: ")
+                }
+            ) {
+                Some((program_pubkey_, _)) => program_pubkey_,
+                None => {
+                    continue '_a;
+                }
+            };
+            return Pubkey::from_str(program_pubkey).ok();
+        }
+        None
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionFailureReason {
+    // The amount out is less than the minimum one.
+    ExceededSlippage,
+    // The pool can not be swapped before its open time.
+    PoolNotOpen,
+    // The lamports or the tokens are not enough for the swap or for the fee.
+    InsufficientFunds,
+    // The token account has been frozen by the freeze authority of the mint.
+    AccountFrozen,
+    RaydiumAmm {
+        error_code: u32,
+    },
+    TokenProgram {
+        error_code: u32,
+    },
+    Other,
+}
+impl TransactionFailureReason {
+    pub fn get_action(self) -> TransactionFailureAction {
+        match self {
+            Self::ExceededSlippage => TransactionFailureAction::Adjust,
+            Self::PoolNotOpen => TransactionFailureAction::Retry,
+            Self::InsufficientFunds
+            | Self::AccountFrozen
+            | Self::RaydiumAmm { .. }
+            | Self::TokenProgram { .. }
+            | Self::Other => TransactionFailureAction::Abandon,
+        }
+    }
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ExceededSlippage => "exceeded_slippage",
+            Self::PoolNotOpen => "pool_not_open",
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::AccountFrozen => "account_frozen",
+            Self::RaydiumAmm { .. } => "raydium_amm",
+            Self::TokenProgram { .. } => "token_program",
+            Self::Other => "other",
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionFailureAction {
+    // The same transaction is created again a bit later.
+    Retry,
+    // The transaction is created again at once against the latest reserves.
+    Adjust,
+    // The transaction is not sent.
+    Abandon,
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Error;
+    #[test]
+    fn decode_raydium_amm_error() -> Result<(), Error> {
+        let raydium_amm_program_pubkey = WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY;
+        let transaction_failure_reason = TransactionErrorDecoder::decode(
+            &TransactionError::InstructionError(2, InstructionError::Custom(30)),
+            &[
+                format!("Program {} invoke [1]", raydium_amm_program_pubkey),
+                format!("Program {} failed: custom program error: {:#x}", raydium_amm_program_pubkey, 30),
+            ],
+        );
+        assert_eq!(transaction_failure_reason, TransactionFailureReason::ExceededSlippage);
+        assert_eq!(transaction_failure_reason.get_action(), TransactionFailureAction::Adjust);
+        assert_eq!(
+            TransactionErrorDecoder::decode(
+                &TransactionError::InstructionError(2, InstructionError::Custom(22)),
+                &[
+                    format!("Program {} invoke [1]", raydium_amm_program_pubkey),
+                    format!("Program {} failed: custom program error: {:#x}", raydium_amm_program_pubkey, 22),
+                ],
+            ),
+            TransactionFailureReason::PoolNotOpen,
+        );
+        assert_eq!(
+            TransactionErrorDecoder::decode(
+                &TransactionError::InstructionError(2, InstructionError::Custom(9)),
+                &[
+                    format!("Program {} invoke [1]", raydium_amm_program_pubkey),
+                    format!("Program {} failed: custom program error: {:#x}", raydium_amm_program_pubkey, 9),
+                ],
+            ),
+            TransactionFailureReason::RaydiumAmm {
+                error_code: 9,
+            },
+        );
+        Ok(())
+    }
+    #[test]
+    fn decode_innermost_program_error() -> Result<(), Error> {
+        let raydium_amm_program_pubkey = WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY;
+        let token_program_pubkey = WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY;
+        // The token program has been called by the Raydium AMM v4, the error is reported for the top level instruction.
+        let log_message_registry = vec![
+            format!("Program {} invoke [1]", raydium_amm_program_pubkey),
+            format!("Program {} invoke [2]", token_program_pubkey),
+            "Program log: Error: Account is frozen".to_string(),
+            format!("Program {} failed: custom program error: 0x11", token_program_pubkey),
+            format!("Program {} failed: custom program error: 0x11", raydium_amm_program_pubkey),
+        ];
+        assert_eq!(
+            TransactionErrorDecoder::decode(
+                &TransactionError::InstructionError(2, InstructionError::Custom(17)),
+                log_message_registry.as_slice(),
+            ),
+            TransactionFailureReason::AccountFrozen,
+        );
+        // The program is unknown without the logs.
+        assert_eq!(
+            TransactionErrorDecoder::decode(
+                &TransactionError::InstructionError(2, InstructionError::Custom(17)),
+                &[],
+            ),
+            TransactionFailureReason::Other,
+        );
+        Ok(())
+    }
+    #[test]
+    fn decode_insufficient_funds() -> Result<(), Error> {
+        let system_program_pubkey = WorkflowData::<TransactionCreation>::SYSTEM_PROGRAM_PUBKEY;
+        assert_eq!(
+            TransactionErrorDecoder::decode(
+                &TransactionError::InstructionError(0, InstructionError::Custom(1)),
+                &[
+                    format!("Program {} invoke [1]", system_program_pubkey),
+                    format!("Program {} failed: custom program error: {:#x}", system_program_pubkey, 1),
+                ],
+            ),
+            TransactionFailureReason::InsufficientFunds,
+        );
+        let transaction_failure_reason = TransactionErrorDecoder::decode(
+            &TransactionError::InsufficientFundsForFee,
+            &[],
+        );
+        assert_eq!(transaction_failure_reason, TransactionFailureReason::InsufficientFunds);
+        assert_eq!(transaction_failure_reason.get_action(), TransactionFailureAction::Abandon);
+        Ok(())
+    }
+}
@@ -1,15 +1,10 @@
 use solana_rpc_client_api::config::RpcTransactionConfig;
-use solana_sdk::message::VersionedMessage;
 use solana_sdk::signature::Signature;
 use solana_sdk::transaction::VersionedTransaction;
 use solana_sdk::commitment_config::CommitmentConfig;
-use solana_transaction_status::option_serializer::OptionSerializer;
-use solana_transaction_status::UiInstruction;
 use crate::capture::Capture;
 use crate::error::Common;
 use crate::error::OptionConverter;
-use crate::workflow_data::TransactionDifferentiation;
-use crate::workflow_data::WorkflowData;
 use super::environment_configuration::EnvironmentConfiguration;
 use std::future::Future;
 use solana_sdk::commitment_config::CommitmentLevel;
@@ -24,7 +19,10 @@ use super::error::{
     ResultConverter,
     Backtrace,
 };
-use super::extern_source::RaydiumAmmInitializeInstruction2;
+use super::pool_detector::{
+    DetectableTransaction,
+    PoolDetector,
+};
 use super::environment_configuration::ParseTransaction;
 pub struct TransactionParser;
 impl TransactionParser {
@@ -101,13 +99,6 @@ impl TransactionParser {
                         ),
                     );
                 }
-                let message = match versioned_transaction.message {
-                    VersionedMessage::V0(message) => message,
-                    _ => {
-                        tracing::info!("{} - invalid.", solana_transaction_signature.as_str());
-                        continue 'a;
-                    }
-                };
                 let ui_transaction_status_meta = encoded_confirmed_transaction_with_status_meta.transaction.meta
                 .into_value_does_not_exist(
                     Backtrace::new(
@@ -119,93 +110,26 @@ impl TransactionParser {
                     tracing::info!("{} - failed.", solana_transaction_signature.as_str());
                     continue 'a;
                 }
-                let mut is_right_transaction = true;
-                if message.instructions.len() != WorkflowData::<TransactionDifferentiation>::INSTRUCTIONS_QUANTITY {
-                    is_right_transaction = false;
-                }
-                let initialize_2_compiled_instruction = &message.instructions[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_VECTOR_INDEX];
-                if is_right_transaction {
-                    if message.account_keys[initialize_2_compiled_instruction.program_id_index as usize] != WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY {
-                        is_right_transaction = false;
-                    }
-                }
-                let ui_inner_instruction_registry_stub = vec![];
-                let ui_inner_instruction_registry = if is_right_transaction {
-                    match ui_transaction_status_meta.inner_instructions {
-                        OptionSerializer::Some(ref ui_inner_instruction_registry_) => ui_inner_instruction_registry_,
-                        _ => &ui_inner_instruction_registry_stub,
-                    }
-                } else {
-                    &ui_inner_instruction_registry_stub
-                };
-                is_right_transaction =
-                ui_inner_instruction_registry.len() == WorkflowData::<TransactionDifferentiation>::INSTRUCTIONS_WITH_INNER_INSTRUCTIONS_QUANTITY
-                && ui_inner_instruction_registry[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX].index as usize == WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_VECTOR_INDEX
-                && ui_inner_instruction_registry[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX].instructions.len() == WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTIONS_QUANTITY;
-                let log_message_registry_stub = vec![];
-                let log_message_registry = if is_right_transaction {
-                    match ui_transaction_status_meta.log_messages {
-                        OptionSerializer::Some(ref log_message_registry_) => log_message_registry_,
-                        _ => &log_message_registry_stub,
+                let detected_pool = match PoolDetector::detect(
+                    &DetectableTransaction::from_rpc(
+                        &versioned_transaction,
+                        &ui_transaction_status_meta,
+                    )?,
+                )? {
+                    Some(detected_pool_) => detected_pool_,
+                    None => {
+                        tracing::info!("{} - invalid.", solana_transaction_signature.as_str());
+                        continue 'a;
                     }
-                } else {
-                    &log_message_registry_stub
                 };
-                is_right_transaction = log_message_registry.len() >= WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_LOG_VECTOR_INDEX + 1
-                && log_message_registry[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_LOG_VECTOR_INDEX].as_bytes()[0..=47] == *WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_LOG_PATTERN;
-                if is_right_transaction {
-                    let create_token_account_compiled_instruction = &ui_inner_instruction_registry[
-                        WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX
-                    ].instructions[
-                        WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_PC_VAULT_TOKEN_ACCOUNT_INITIALIZING_INSTRUCTION_VECTOR_INDEX
-                    ];
-                    let ui_compiled_instruction = match create_token_account_compiled_instruction {
-                        &UiInstruction::Compiled(ref ui_compiled_instruction_) => ui_compiled_instruction_,
-                        &UiInstruction::Parsed(ref ui_parsed_instruction) => {
-                            return Err(
-                                Error::new_(
-                                    Common::UnreachableState,
-                                    Backtrace::new(
-                                        line!(),
-                                        file!(),
-                                    ),
-                                ),
-                            );
-                        }
-                    };
-                    is_right_transaction = message.account_keys[ui_compiled_instruction.program_id_index as usize] == WorkflowData::<TransactionDifferentiation>::TOKEN_PROGRAM_PUBKEY
-                    && message.account_keys[ui_compiled_instruction.accounts[1] as usize] == WorkflowData::<TransactionDifferentiation>::WRAPPED_SOL_TOKEN_ACCOUNT_PUBKEY
-                    && ui_compiled_instruction.accounts[0] == initialize_2_compiled_instruction.accounts[WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_VAULT_PUBKEY_VECTOR_INDEX];
-                }
-                if !is_right_transaction {
-                    tracing::info!("{} - invalid.", solana_transaction_signature.as_str());
-                    continue 'a;
-                }
-                let amm_market_pubkey = message.account_keys[
-                    initialize_2_compiled_instruction.accounts[
-                        WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_MARKET_PUBKEY_VECTOR_INDEX
-                    ] as usize
-                ];
-                let amm_coin_vault_pubkey = message.account_keys[
-                    initialize_2_compiled_instruction.accounts[
-                        WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_COIN_VAULT_PUBKEY_VECTOR_INDEX
-                    ] as usize
-                ];
-                let amm_pc_vault_pubkey = message.account_keys[
-                    initialize_2_compiled_instruction.accounts[
-                        WorkflowData::<TransactionDifferentiation>::RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_VAULT_PUBKEY_VECTOR_INDEX
-                    ] as usize
-                ];
-                let raydium_amm_initialize_instruction_2 = RaydiumAmmInitializeInstruction2::unpack(
-                    initialize_2_compiled_instruction.data.as_slice(),
-                )?;
                 tracing::info!(
-                    "\n{} - right.\namm_market_pubkey: {}\namm_coin_vault_pubkey: {}\namm_pc_vault_pubkey: {}\nraydium_amm_initialize_instruction_2: {:?}",
+                    "\n{} - right.\namm_market_pubkey: {}\namm_coin_vault_pubkey: {}\namm_pc_vault_pubkey: {}\namm_pc_mint_pubkey: {}\nraydium_amm_initialize_instruction_2: {:?}",
                     solana_transaction_signature.as_str(),
-                    &amm_market_pubkey,
-                    &amm_coin_vault_pubkey,
-                    &amm_pc_vault_pubkey,
-                    &raydium_amm_initialize_instruction_2,
+                    &detected_pool.amm_market_pubkey,
+                    &detected_pool.amm_coin_vault_pubkey,
+                    &detected_pool.amm_pc_vault_pubkey,
+                    &detected_pool.amm_pc_mint_pubkey,
+                    &detected_pool.raydium_amm_initialize_instruction_2,
                 );
             }
             Ok(())
@@ -0,0 +1,597 @@
+use super::capture::Capture;
+use super::environment_configuration::TransactionSenderBackend;
+use super::error::{
+    Backtrace,
+    Common,
+    Error,
+    LockResultConverter,
+    ResultConverter,
+};
+use super::grpc_server::GEYSER_LAST_PROCESSED_SLOT;
+use super::robot::ForTransactionStatusProcessing;
+use solana_client::nonblocking::tpu_client::TpuClient;
+use solana_client::tpu_client::TpuClientConfig;
+use solana_quic_client::{
+    QuicConfig,
+    QuicConnectionManager,
+    QuicPool,
+};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use std::future::Future;
+use std::sync::atomic::Ordering;
+use std::sync::{
+    Arc,
+    Mutex,
+    MutexGuard,
+    OnceLock,
+};
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+static TRANSACTION_SENDER: OnceLock<TransactionSender> = OnceLock::new();
+// Every transaction is sent through all the configured backends at once, the sending succeeds if any of them has accepted
+// the transaction. The transaction is signed before, so the copies can land only once. The tip of the block engine is
+// the instruction of the transaction itself, so every backend sends the same transaction and the bundle holds only it.
+pub struct TransactionSender {
+    backend_registry: Vec<Backend>,
+    tip: Option<Tip>,
+}
+impl TransactionSender {
+    const TPU_CLIENT_NAME: &'static str = "robot";
+    // The 'rpc_client' is the one of the 'solana_rpc' node, the TPU backend follows the leader schedule through it.
+    pub fn create<'a>(
+        backend_registry: &'a [TransactionSenderBackend],
+        rpc_client: Arc<RpcClient>,
+        process_transaction_status_sender: &'a Sender<ForTransactionStatusProcessing>,
+    ) -> impl Future<Output = Result<Self, Error>> + Send + Capture<&'a ()> {
+        async move {
+            let mut backend_registry_ = Vec::<Backend>::with_capacity(backend_registry.len());
+            let mut tip: Option<Tip> = None;
+            '_a: for backend in backend_registry.iter() {
+                backend_registry_.push(
+                    match *backend {
+                        TransactionSenderBackend::Rpc {
+                            ref url,
+                            maximum_attempts_quantity,
+                            retry_delay,
+                        } => {
+                            Backend::Rpc {
+                                rpc_client: RpcClient::new(url.clone()),
+                                maximum_attempts_quantity,
+                                retry_delay,
+                            }
+                        }
+                        TransactionSenderBackend::Tpu {
+                            ref websocket_url,
+                            fanout_slots,
+                        } => {
+                            Backend::Tpu {
+                                tpu_client: TpuClient::new(
+                                    Self::TPU_CLIENT_NAME,
+                                    rpc_client.clone(),
+                                    websocket_url.as_str(),
+                                    TpuClientConfig {
+                                        fanout_slots,
+                                    },
+                                )
+                                .await
+                                .into_(
+                                    Backtrace::new(
+                                        line!(),
+                                        file!(),
+                                    ),
+                                )?,
+                            }
+                        }
+                        TransactionSenderBackend::Bundle {
+                            ref url,
+                            tip_account_pubkey,
+                            tip_lamports,
+                        } => {
+                            if tip_lamports != 0 {
+                                tip = Some(
+                                    Tip {
+                                        tip_account_pubkey,
+                                        tip_lamports,
+                                    },
+                                );
+                            }
+                            Backend::Bundle {
+                                http_client: reqwest::Client::new(),
+                                url: url.clone(),
+                            }
+                        }
+                        TransactionSenderBackend::Mock {
+                            is_landing_simulated,
+                        } => {
+                            Backend::Mock {
+                                mock_sender: MockSender::new(
+                                    if is_landing_simulated {
+                                        Some(process_transaction_status_sender.clone())
+                                    } else {
+                                        None
+                                    },
+                                    false,
+                                ),
+                            }
+                        }
+                    },
+                );
+            }
+            Ok(
+                Self {
+                    backend_registry: backend_registry_,
+                    tip,
+                },
+            )
+        }
+    }
+    pub fn initialize(transaction_sender: Self) -> Result<(), Error> {
+        TRANSACTION_SENDER.set(transaction_sender).map_err(
+            |_: _| -> _ {
+                Error::new_(
+                    Common::UnreachableState,
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )
+            }
+        )
+    }
+    pub fn get() -> Result<&'static Self, Error> {
+        TRANSACTION_SENDER.get().ok_or(
+            Error::new_(
+                Common::UnreachableState,
+                Backtrace::new(
+                    line!(),
+                    file!(),
+                ),
+            ),
+        )
+    }
+    pub fn get_tip<'a>(&'a self) -> Option<Tip> {
+        self.tip
+    }
+    // The error of the backend is logged, the error of the last backend is returned if all of them have failed.
+    pub fn send<'a>(&'a self, transaction: &'a Transaction) -> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            let sending_result_registry = futures::future::join_all(
+                self.backend_registry.iter().map(
+                    |backend: _| -> _ {
+                        backend.send(transaction)
+                    }
+                ),
+            )
+            .await;
+            let mut last_error: Option<Error> = None;
+            let mut is_sent = false;
+            '_a: for (backend, sending_result) in self.backend_registry.iter().zip(sending_result_registry.into_iter()) {
+                match sending_result {
+                    Ok(()) => {
+                        is_sent = true;
+                    }
+                    Err(error) => {
+                        tracing::warn!(
+                            "The {} transaction sender backend has failed to send {}: {}.",
+                            backend.get_name(),
+                            &transaction.signatures[0],
+                            &error,
+                        );
+                        last_error = Some(error);
+                    }
+                }
+            }
+            match last_error {
+                Some(error) if !is_sent => Err(error),
+                _ => Ok(()),
+            }
+        }
+    }
+    // Returns the transactions recorded by the mock backends.
+    #[cfg(test)]
+    pub fn get_mock_transaction_registry<'a>(&'a self) -> Result<Vec<Transaction>, Error> {
+        let mut transaction_registry = Vec::<Transaction>::new();
+        '_a: for backend in self.backend_registry.iter() {
+            if let Backend::Mock {
+                mock_sender,
+            } = backend {
+                transaction_registry.extend(mock_sender.lock()?.iter().cloned());
+            }
+        }
+        Ok(transaction_registry)
+    }
+}
+enum Backend {
+    Rpc {
+        rpc_client: RpcClient,
+        maximum_attempts_quantity: usize,
+        retry_delay: Duration,
+    },
+    Tpu {
+        tpu_client: TpuClient<QuicPool, QuicConnectionManager, QuicConfig>,
+    },
+    Bundle {
+        http_client: reqwest::Client,
+        url: String,
+    },
+    Mock {
+        mock_sender: MockSender,
+    },
+}
+impl Backend {
+    fn get_name<'a>(&'a self) -> &'static str {
+        match *self {
+            Self::Rpc { .. } => "rpc",
+            Self::Tpu { .. } => "tpu",
+            Self::Bundle { .. } => "bundle",
+            Self::Mock { .. } => "mock",
+        }
+    }
+    fn send<'a>(&'a self, transaction: &'a Transaction) -> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            match *self {
+                Self::Rpc {
+                    ref rpc_client,
+                    maximum_attempts_quantity,
+                    retry_delay,
+                } => {
+                    let mut attempt: usize = 1;
+                    'a: loop {
+                        match rpc_client.send_transaction_with_config(
+                            transaction,
+                            RpcSendTransactionConfig {
+                                skip_preflight: true,
+                                preflight_commitment: None,
+                                encoding: None,
+                                max_retries: None,
+                                min_context_slot: None,
+                            },
+                        )
+                        .await {
+                            Ok(_) => {
+                                break 'a;
+                            }
+                            Err(error) => {
+                                if attempt >= maximum_attempts_quantity {
+                                    return Err(
+                                        Error::new(
+                                            error.into(),
+                                            Backtrace::new(
+                                                line!(),
+                                                file!(),
+                                            ),
+                                        ),
+                                    );
+                                }
+                                attempt += 1;
+                                tokio::time::sleep(retry_delay).await;
+                            }
+                        }
+                    }
+                }
+                Self::Tpu {
+                    ref tpu_client,
+                } => {
+                    tpu_client.try_send_transaction(transaction).await.into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?;
+                }
+                Self::Bundle {
+                    ref http_client,
+                    ref url,
+                } => {
+                    let response = http_client.post(url.as_str()).json(
+                        &serde_json::json!(
+                            {
+                                "jsonrpc": "2.0",
+                                "id": 1,
+                                "method": "sendBundle",
+                                "params": [
+                                    [
+                                        Self::encode(transaction)?,
+                                    ],
+                                ],
+                            }
+                        ),
+                    )
+                    .send()
+                    .await
+                    .into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?
+                    .json::<serde_json::Value>()
+                    .await
+                    .into_(
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    )?;
+                    if let Some(error) = response.get("error") {
+                        return Err(
+                            Error::new(
+                                format!("The bundle has been rejected: {}.", error).into(),
+                                Backtrace::new(
+                                    line!(),
+                                    file!(),
+                                ),
+                            ),
+                        );
+                    }
+                }
+                Self::Mock {
+                    ref mock_sender,
+                } => {
+                    mock_sender.send(transaction).await?;
+                }
+            }
+            Ok(())
+        }
+    }
+    // The block engine accepts the base58 of the serialized transaction by default.
+    fn encode<'a>(transaction: &'a Transaction) -> Result<String, Error> {
+        Ok(
+            bs58::encode(
+                bincode::serialize(transaction).into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?,
+            )
+            .into_string(),
+        )
+    }
+}
+// The transfer of the tip of the block engine that is added to the end of the swap.
+#[derive(Clone, Copy)]
+pub struct Tip {
+    pub tip_account_pubkey: Pubkey,
+    pub tip_lamports: u64,
+}
+// The transaction is recorded instead of being sent. If the landing is simulated, its status goes the same way as the one
+// received from the Geyser, so the confirmation tracking can be run without the network. The quoting, the simulation and
+// the blockhash of the trade still need the RPC. The failing one rejects every transaction without recording it.
+struct MockSender {
+    transaction_registry: Mutex<Vec<Transaction>>,
+    process_transaction_status_sender: Option<Sender<ForTransactionStatusProcessing>>,
+    is_failing: bool,
+}
+impl MockSender {
+    fn new(process_transaction_status_sender: Option<Sender<ForTransactionStatusProcessing>>, is_failing: bool) -> Self {
+        Self {
+            transaction_registry: Mutex::new(vec![]),
+            process_transaction_status_sender,
+            is_failing,
+        }
+    }
+    fn send<'a>(&'a self, transaction: &'a Transaction) -> impl Future<Output = Result<(), Error>> + Send + Capture<&'a ()> {
+        async move {
+            if self.is_failing {
+                return Err(
+                    Error::new(
+                        "The mock transaction sender backend is failing.".into(),
+                        Backtrace::new(
+                            line!(),
+                            file!(),
+                        ),
+                    ),
+                );
+            }
+            self.lock()?.push(transaction.clone());
+            if let Some(process_transaction_status_sender) = self.process_transaction_status_sender.as_ref() {
+                process_transaction_status_sender.send(
+                    ForTransactionStatusProcessing {
+                        signature: transaction.signatures[0],
+                        slot: GEYSER_LAST_PROCESSED_SLOT.load(Ordering::Relaxed),
+                        transaction_error: None,
+                    },
+                )
+                .await
+                .into_(
+                    Backtrace::new(
+                        line!(),
+                        file!(),
+                    ),
+                )?;
+            }
+            Ok(())
+        }
+    }
+    fn lock<'a>(&'a self) -> Result<MutexGuard<'a, Vec<Transaction>>, Error> {
+        self.transaction_registry.lock().into_unreachable_state(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::error::OptionConverter;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::signer::Signer;
+    use solana_sdk::system_instruction;
+    fn create_transaction() -> Transaction {
+        let keypair = Keypair::new();
+        Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(
+                    &keypair.pubkey(),
+                    &Pubkey::new_unique(),
+                    1,
+                ),
+            ],
+            Some(&keypair.pubkey()),
+            &[
+                &keypair,
+            ],
+            Hash::new_unique(),
+        )
+    }
+    #[tokio::test]
+    async fn record_in_every_mock() -> Result<(), Error> {
+        let (
+            process_transaction_status_sender,
+            _process_transaction_status_receiver,
+        ) = tokio::sync::mpsc::channel::<ForTransactionStatusProcessing>(1);
+        let transaction_sender = TransactionSender::create(
+            &[
+                TransactionSenderBackend::Mock {
+                    is_landing_simulated: false,
+                },
+                TransactionSenderBackend::Mock {
+                    is_landing_simulated: false,
+                },
+            ],
+            Arc::new(RpcClient::new_mock("fails".to_string())),
+            &process_transaction_status_sender,
+        )
+        .await?;
+        let transaction = create_transaction();
+        transaction_sender.send(&transaction).await?;
+        let transaction_registry = transaction_sender.get_mock_transaction_registry()?;
+        assert_eq!(transaction_registry.len(), 2);
+        assert_eq!(transaction_registry[0].signatures[0], transaction.signatures[0]);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn take_tip_from_bundle_backend() -> Result<(), Error> {
+        let (
+            process_transaction_status_sender,
+            _process_transaction_status_receiver,
+        ) = tokio::sync::mpsc::channel::<ForTransactionStatusProcessing>(1);
+        let tip_account_pubkey = Pubkey::new_unique();
+        let transaction_sender = TransactionSender::create(
+            &[
+                TransactionSenderBackend::Mock {
+                    is_landing_simulated: false,
+                },
+                TransactionSenderBackend::Bundle {
+                    url: "http://127.0.0.1:1".to_string(),
+                    tip_account_pubkey,
+                    tip_lamports: 1000,
+                },
+            ],
+            Arc::new(RpcClient::new_mock("fails".to_string())),
+            &process_transaction_status_sender,
+        )
+        .await?;
+        let tip = transaction_sender.get_tip().into_value_does_not_exist(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        assert_eq!(tip.tip_account_pubkey, tip_account_pubkey);
+        assert_eq!(tip.tip_lamports, 1000);
+        // The zero tip is not added to the swap.
+        let transaction_sender_ = TransactionSender::create(
+            &[
+                TransactionSenderBackend::Bundle {
+                    url: "http://127.0.0.1:1".to_string(),
+                    tip_account_pubkey,
+                    tip_lamports: 0,
+                },
+            ],
+            Arc::new(RpcClient::new_mock("fails".to_string())),
+            &process_transaction_status_sender,
+        )
+        .await?;
+        assert!(transaction_sender_.get_tip().is_none());
+        Ok(())
+    }
+    #[tokio::test]
+    async fn send_if_any_backend_accepts() -> Result<(), Error> {
+        let transaction_sender = TransactionSender {
+            backend_registry: vec![
+                Backend::Mock {
+                    mock_sender: MockSender::new(None, true),
+                },
+                Backend::Rpc {
+                    rpc_client: RpcClient::new_mock("fails".to_string()),
+                    maximum_attempts_quantity: 2,
+                    retry_delay: Duration::ZERO,
+                },
+                Backend::Mock {
+                    mock_sender: MockSender::new(None, false),
+                },
+            ],
+            tip: None,
+        };
+        let transaction = create_transaction();
+        transaction_sender.send(&transaction).await?;
+        // Only the accepting backend has recorded the transaction.
+        assert_eq!(transaction_sender.get_mock_transaction_registry()?.len(), 1);
+        // The RPC node that accepts the transaction is enough as well.
+        let transaction_sender_ = TransactionSender {
+            backend_registry: vec![
+                Backend::Mock {
+                    mock_sender: MockSender::new(None, true),
+                },
+                Backend::Rpc {
+                    rpc_client: RpcClient::new_mock("succeeds".to_string()),
+                    maximum_attempts_quantity: 1,
+                    retry_delay: Duration::ZERO,
+                },
+            ],
+            tip: None,
+        };
+        transaction_sender_.send(&transaction).await?;
+        Ok(())
+    }
+    #[tokio::test]
+    async fn fail_if_every_backend_fails() -> Result<(), Error> {
+        let transaction_sender = TransactionSender {
+            backend_registry: vec![
+                Backend::Rpc {
+                    rpc_client: RpcClient::new_mock("fails".to_string()),
+                    maximum_attempts_quantity: 3,
+                    retry_delay: Duration::ZERO,
+                },
+                Backend::Mock {
+                    mock_sender: MockSender::new(None, true),
+                },
+                Backend::Mock {
+                    mock_sender: MockSender::new(None, true),
+                },
+            ],
+            tip: None,
+        };
+        let transaction = create_transaction();
+        assert!(transaction_sender.send(&transaction).await.is_err());
+        assert!(transaction_sender.get_mock_transaction_registry()?.is_empty());
+        Ok(())
+    }
+    #[tokio::test]
+    async fn simulate_landing() -> Result<(), Error> {
+        let (
+            process_transaction_status_sender,
+            mut process_transaction_status_receiver,
+        ) = tokio::sync::mpsc::channel::<ForTransactionStatusProcessing>(1);
+        let mock_sender = MockSender::new(Some(process_transaction_status_sender), false);
+        let transaction = create_transaction();
+        mock_sender.send(&transaction).await?;
+        let for_transaction_status_processing = process_transaction_status_receiver.try_recv().into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )?;
+        assert_eq!(for_transaction_status_processing.signature, transaction.signatures[0]);
+        assert!(for_transaction_status_processing.transaction_error.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use super::error::{
+    Backtrace,
+    Error,
+    ResultConverter,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+pub struct UnixTime;
+impl UnixTime {
+    pub fn get() -> Result<Duration, Error> {
+        SystemTime::now().duration_since(UNIX_EPOCH).into_(
+            Backtrace::new(
+                line!(),
+                file!(),
+            ),
+        )
+    }
+    pub fn get_timestamp() -> Result<u64, Error> {
+        Ok(Self::get()?.as_secs())
+    }
+    pub fn get_timestamp_microseconds() -> Result<u64, Error> {
+        Ok(Self::get()?.as_micros() as u64)
+    }
+}
@@ -9,23 +9,58 @@ impl WorkflowData<TransactionDifferentiation> {
     pub const RAYDIUM_LIQUIDITY_POOL_V4_CONTRACT_PUBKEY: Pubkey = Pubkey::new_from_array(
         [75, 217, 73, 196, 54, 2, 195, 63, 32, 119, 144, 237, 22, 163, 82, 76, 161, 185, 151, 92, 241, 33, 162, 169, 12, 255, 236, 125, 248, 182, 138, 205]
     );
-    pub const RAYDIUM_LIQUIDITY_POOL_V4_LOG_PATTERN: &'static [u8] = b"Program log: initialize2: InitializeInstruction2".as_slice();
-    pub const RAYDIUM_LIQUIDITY_POOL_V4_LOG_VECTOR_INDEX: usize = 7;
-    pub const RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_VECTOR_INDEX: usize = 2;
-    pub const RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTIONS_QUANTITY: usize = 32;
+    // https://github.com/raydium-io/raydium-amm/blob/d10a8e9fab9f7a3d87b4ae3891e3e4c24b75c041/program/src/instruction.rs
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_INSTRUCTION_TAG: u8 = 1;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_INITIALIZE_2_ACCOUNTS_QUANTITY: usize = 21;
     pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_MARKET_PUBKEY_VECTOR_INDEX: usize = 4;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_AUTHORITY_PUBKEY_VECTOR_INDEX: usize = 5;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_OPEN_ORDERS_PUBKEY_VECTOR_INDEX: usize = 6;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_COIN_MINT_PUBKEY_VECTOR_INDEX: usize = 8;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_MINT_PUBKEY_VECTOR_INDEX: usize = 9;
     pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_COIN_VAULT_PUBKEY_VECTOR_INDEX: usize = 10;
     pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_PC_VAULT_PUBKEY_VECTOR_INDEX: usize = 11;
-    pub const RAYDIUM_LIQUIDITY_POOL_V4_INNER_INSTRUCTION_VECTOR_INDEX: usize = 0;
-    pub const RAYDIUM_LIQUIDITY_POOL_V4_PC_VAULT_TOKEN_ACCOUNT_INITIALIZING_INSTRUCTION_VECTOR_INDEX: usize = 16;
-    pub const INSTRUCTIONS_QUANTITY: usize = 4;
-    pub const INSTRUCTIONS_WITH_INNER_INSTRUCTIONS_QUANTITY: usize = 1;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_AMM_TARGET_ORDERS_PUBKEY_VECTOR_INDEX: usize = 12;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_SERUM_PROGRAM_PUBKEY_VECTOR_INDEX: usize = 15;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_SERUM_MARKET_PUBKEY_VECTOR_INDEX: usize = 16;
+    pub const RAYDIUM_LIQUIDITY_POOL_V4_USER_WALLET_PUBKEY_VECTOR_INDEX: usize = 17;
     // 'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA' pubkey.
     pub const TOKEN_PROGRAM_PUBKEY: Pubkey = Pubkey::new_from_array(
         [6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133, 237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169]
     );
+    // 'TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb' pubkey.
+    pub const TOKEN_2022_PROGRAM_PUBKEY: Pubkey = Pubkey::new_from_array(
+        [6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252, 77, 131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252]
+    );
     // 'So11111111111111111111111111111111111111112' pubkey.
     pub const WRAPPED_SOL_TOKEN_ACCOUNT_PUBKEY: Pubkey = Pubkey::new_from_array(
         [6, 155, 136, 87, 254, 171, 129, 132, 251, 104, 127, 99, 70, 24, 192, 53, 218, 196, 57, 220, 26, 235, 59, 85, 152, 160, 240, 0, 0, 0, 0, 1]
     );
+    // 'ComputeBudget111111111111111111111111111111' pubkey.
+    pub const COMPUTE_BUDGET_PROGRAM_PUBKEY: Pubkey = Pubkey::new_from_array(
+        [3, 6, 70, 111, 229, 33, 23, 50, 255, 236, 173, 186, 114, 195, 155, 231, 188, 140, 229, 187, 197, 247, 18, 107, 44, 67, 155, 58, 64, 0, 0, 0]
+    );
+    // https://github.com/anza-xyz/agave/blob/v2.0.18/sdk/src/compute_budget.rs
+    pub const COMPUTE_BUDGET_SET_COMPUTE_UNIT_PRICE_INSTRUCTION_TAG: u8 = 3;
+}
+pub struct TransactionCreation;
+impl WorkflowData<TransactionCreation> {
+    // '11111111111111111111111111111111' pubkey.
+    pub const SYSTEM_PROGRAM_PUBKEY: Pubkey = Pubkey::new_from_array(
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    // 'ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL' pubkey.
+    pub const ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_PUBKEY: Pubkey = Pubkey::new_from_array(
+        [140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89]
+    );
+    // https://github.com/solana-labs/solana-program-library/blob/token-v4.0.0/token/program/src/state.rs
+    pub const TOKEN_ACCOUNT_DATA_LENGTH: u64 = 165;
+    // https://github.com/solana-labs/solana-program-library/blob/token-v4.0.0/token/program/src/instruction.rs
+    pub const TOKEN_PROGRAM_CLOSE_ACCOUNT_INSTRUCTION_TAG: u8 = 9;
+    pub const TOKEN_PROGRAM_INITIALIZE_ACCOUNT_3_INSTRUCTION_TAG: u8 = 18;
+    // https://github.com/solana-labs/solana-program-library/blob/associated-token-account-v2.0.0/associated-token-account/program/src/instruction.rs
+    pub const ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_CREATE_IDEMPOTENT_INSTRUCTION_TAG: u8 = 1;
+}
+pub struct Calculation;
+impl WorkflowData<Calculation> {
+    pub const BASIS_POINTS_IN_WHOLE: u64 = 10000;
 }
\ No newline at end of file
@@ -0,0 +1,46 @@
+-- The amounts are stored as the decimal text, because u64 does not fit into the SQLite INTEGER.
+CREATE TABLE detected_pool (
+    amm_market_pubkey TEXT NOT NULL PRIMARY KEY,
+    amm_authority_pubkey TEXT NOT NULL,
+    amm_open_orders_pubkey TEXT NOT NULL,
+    amm_target_orders_pubkey TEXT NOT NULL,
+    amm_coin_mint_pubkey TEXT NOT NULL,
+    amm_pc_mint_pubkey TEXT NOT NULL,
+    amm_coin_vault_pubkey TEXT NOT NULL,
+    init_coin_amount TEXT NOT NULL,
+    amm_pc_vault_pubkey TEXT NOT NULL,
+    init_pc_amount TEXT NOT NULL,
+    serum_program_pubkey TEXT NOT NULL,
+    serum_market_pubkey TEXT NOT NULL,
+    quote_mint_pubkey TEXT NOT NULL,
+    base_mint_pubkey TEXT NOT NULL,
+    buy_swap_direction TEXT NOT NULL,
+    initial_pc_amount TEXT NOT NULL,
+    detection_timestamp INTEGER NOT NULL
+);
+-- The status is one of 'buying', 'open', 'closed' and 'abandoned'.
+CREATE TABLE position (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    amm_market_pubkey TEXT NOT NULL REFERENCES detected_pool (amm_market_pubkey),
+    status TEXT NOT NULL,
+    base_amount TEXT,
+    exit_reason TEXT,
+    opening_timestamp INTEGER NOT NULL,
+    closing_timestamp INTEGER
+);
+CREATE INDEX position_status_index ON position (status);
+-- The side is one of 'buy' and 'sell', the status is one of 'sent', 'landed', 'failed' and 'expired'.
+CREATE TABLE swap_order (
+    signature TEXT NOT NULL PRIMARY KEY,
+    position_id INTEGER NOT NULL REFERENCES position (id),
+    side TEXT NOT NULL,
+    amount_in TEXT NOT NULL,
+    minimum_amount_out TEXT NOT NULL,
+    status TEXT NOT NULL,
+    slot INTEGER,
+    transaction_error TEXT,
+    fill_amount TEXT,
+    sending_timestamp INTEGER NOT NULL,
+    confirmation_timestamp INTEGER
+);
+CREATE INDEX swap_order_position_id_index ON swap_order (position_id);
@@ -0,0 +1,2 @@
+-- The verdict of the token screening as the JSON, NULL for the pools journaled before the screening.
+ALTER TABLE detected_pool ADD COLUMN token_verdict TEXT;